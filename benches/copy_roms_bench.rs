@@ -1,4 +1,5 @@
 use criterion::{black_box, Criterion, criterion_group, criterion_main};
+use roms_curator::core::progress::NoProgress;
 use roms_curator::core::roms_service::RomsExt;
 
 mod utils;
@@ -17,7 +18,7 @@ fn copy_roms(c: &mut Criterion) {
                 roms_curator::run(&args).unwrap()
             );
             black_box(
-                results.copy_roms(&args).expect("Error copying roms")
+                results.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms")
             );
         });
     });
@@ -1,37 +1,124 @@
 extern crate core;
 
 use std::{env, fs};
+use std::collections::HashSet;
 use std::error::Error;
+use std::ffi::OsString;
 use std::fs::{File, metadata};
-use std::path::Path;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-use clap::{Arg, Command, crate_authors, crate_description, crate_name, crate_version};
+use clap::{Arg, ArgMatches, Command, crate_authors, crate_description, crate_name, crate_version};
+use strum_macros::{Display, EnumString};
+
+use crate::core::config::{ArgValues, Config, env_values};
+use crate::core::curator::CopyOptions;
+use crate::core::export::retroarch::{DETECT, PlaylistGrouping};
+use crate::core::feature_rules::FeatureRules;
+use crate::core::filter::Filter;
+use crate::core::identify::UnknownFilesAction;
+use crate::core::progress::{NoProgress, Progress, TerminalProgress};
+use crate::core::scanner::{IgnorePatterns, ScanOptions};
+use crate::core::serve::DEFAULT_ADDRESS;
+use crate::core::verify::VerifyOptions;
+use crate::error::{CuratorError, CuratorResult};
+use crate::models::report::ReportFormat;
+use crate::models::roms::UnknownValuePolicy;
+
+/// Subcommands of the command line application, see [command].
+#[derive(Display, Debug, Default, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum CommandType {
+    /// Categorize roms and optionally save the categorization as folder INI files.
+    Categorize,
+    /// Categorize roms and copy them to the destination folders.
+    #[default]
+    Copy,
+    /// Move roms already in the destination to their new folders and copy only new ones.
+    Upgrade,
+    /// Check source paths for missing and unknown roms.
+    Verify,
+    /// Compare with a previous MAME release.
+    Diff,
+    /// Write frontend files for roms already in the destination folders.
+    Export,
+    /// Show everything known about one or more machines.
+    Info,
+    /// Show statistics about the categorized roms.
+    Stats,
+    /// Serve the categorized roms as a read-only JSON HTTP API.
+    Serve,
+}
 
 /// Stores startup program arguments
 ///
 /// ## Arguments
+/// - command: Which [CommandType] to run.
+/// - names: Machine names to look up (`info` command).
 /// - mamexml_path: Path to MAME ROM database file. See README on how to get this.
 /// - catver_path: Path to MAME support file. See README on how to get this.
+/// - unknown_values: How to categorize machines with `mame.xml` values unknown to this version.
+/// - feature_rules: Which imperfect or unemulated features make a machine not working.
 /// - source_path: Where the original ROM collection is. Can be more than one directory.
+/// - scan_depth: How many levels of sub-directories of `source_path` are scanned for roms.
+/// - ignore: Glob patterns of files and directories in `source_path` that are left out.
+/// - check_contents: If true, the files in each zip, 7z or folder are checked against `mame.xml` (`verify` command).
 /// - destination_path: Where to copy the roms.
-/// - report_path: Path to the generated report in markdown format. Ex: report.md.
+/// - report_path: Path to the generated report. Ex: report.md.
+/// - report_format: Format of the generated report (markdown, json, csv or html).
 /// - ignore_not_working_chd: If true, not-working CHD ROMs and Directories will not be copied.
 /// - simulation: If true, no ROMs will be copied, but the report will still be generated as if they were (Needs valid `report_path`).
 /// - subset_start: If set, only roms which ascii name alphabetical order is higher than this will be copied.
 /// - subset_end: If set, only roms which ascii name alphabetical order is lower than this will be copied.
+/// - filter: If set, only roms matching this [Filter](crate::core::filter::Filter) expression will be copied.
+/// - unknown_files: What to do with source files that don't match any machine (keep, copy or move them to `unknown`).
+/// - rename_identified: If true, unknown files identified by checksum are renamed after their machine.
+/// - torrentzip: If true, copied zip files are rewritten in deterministic, TorrentZip-style form.
+/// - gamelist: If true, an EmulationStation `gamelist.xml` is written to each destination folder.
+/// - retroarch_playlist_path: If set, RetroArch playlists are written to this directory.
+/// - retroarch_core_path: Core path used in RetroArch playlists.
+/// - retroarch_core_name: Core name used in RetroArch playlists.
+/// - retroarch_playlist_grouping: One RetroArch playlist per destination folder or per rom category.
+/// - attract_mode_romlist_path: If set, Attract-Mode romlists are written to this directory.
+/// - attract_mode_emulator: Attract-Mode emulator name used in romlists.
+/// - folder_ini_path: If set, the categorization is written as MAME folder filter INI files to this directory.
+/// - catalog_path: If set, the categorized roms are saved as a JSON catalog to this file (`categorize` command).
+/// - sqlite_path: If set, the categorized roms are saved as a SQLite database to this file (`categorize` command).
+/// - previous_mame_xml_path: If set, the roms are compared against this (older) MAME ROM database.
+/// - previous_catver_path: MAME support file of the older release, optional.
+/// - diff_path: Path to the generated diff report, printed to console if not set.
+/// - address: Local address the JSON HTTP API listens on (`serve` command).
 ///
 #[derive(Default, Debug, PartialEq, Eq)]
 pub struct Args {
+    /// Which [CommandType] to run.
+    pub command: CommandType,
+    /// Machine names to look up (`info` command).
+    pub names: Vec<String>,
     /// Path to MAME ROM database file. See README on how to get this.
     pub mame_xml_path: String,
     /// Path to MAME support file. See README on how to get this.
     pub catver_path: String,
+    /// How to categorize machines with `mame.xml` values unknown to this version.
+    pub unknown_values: UnknownValuePolicy,
+    /// Which imperfect or unemulated features make a machine not working, see [FeatureRules].
+    pub feature_rules: FeatureRules,
     /// Where the original ROM collection is. Can be more than one directory.
     pub source_path: Vec<String>,
+    /// How many levels of sub-directories of `source_path` are scanned for roms, 0 for none.
+    pub scan_depth: usize,
+    /// Files and directories in `source_path` that are left out, see [IgnorePatterns].
+    pub ignore: IgnorePatterns,
+    /// If true, the files in each zip, 7z or folder are checked against `mame.xml` (`verify` command),
+    /// see [check_contents](crate::core::container::check_contents).
+    pub check_contents: bool,
     /// Where to copy the roms.
     pub destination_path: String,
-    /// Path to the generated report in markdown format. Ex: report.md.
+    /// Path to the generated report. Ex: report.md.
     pub report_path: String,
+    /// Format of the generated report, from `--report-format` or the `report_path` extension.
+    pub report_format: ReportFormat,
     /// If true, not-working CHD ROMs and Directories will not be copied.
     pub ignore_not_working_chd: bool,
     /// If true, no ROMs will be copied, but the report will still be generated as if they were.
@@ -41,6 +128,44 @@ pub struct Args {
     pub subset_start: String,
     /// If set, only roms which ascii name alphabetical order is lower than this will be copied.
     pub subset_end: String,
+    /// If set, only roms matching this [Filter](crate::core::filter::Filter) expression will be copied.
+    pub filter: String,
+    /// What to do with source files that don't match any machine, see [UnknownFilesAction].
+    pub unknown_files: UnknownFilesAction,
+    /// If true, unknown files identified by checksum are renamed after their machine.
+    pub rename_identified: bool,
+    /// If true, copied zip files are rewritten in deterministic, TorrentZip-style form, see [torrentzip](crate::core::torrentzip).
+    pub torrentzip: bool,
+    /// If true, an EmulationStation `gamelist.xml` is written to each destination folder.
+    pub gamelist: bool,
+    /// If set, RetroArch playlists are written to this directory.
+    pub retroarch_playlist_path: String,
+    /// Core path used in RetroArch playlists, `DETECT` to choose on launch.
+    pub retroarch_core_path: String,
+    /// Core name used in RetroArch playlists, `DETECT` to choose on launch.
+    pub retroarch_core_name: String,
+    /// One RetroArch playlist per destination folder or per rom category.
+    pub retroarch_playlist_grouping: PlaylistGrouping,
+    /// If set, Attract-Mode romlists are written to this directory.
+    pub attract_mode_romlist_path: String,
+    /// Attract-Mode emulator name used in romlists.
+    pub attract_mode_emulator: String,
+    /// If set, the categorization is written as MAME folder filter INI files to this directory.
+    pub folder_ini_path: String,
+    /// If set, the categorized roms are saved as a JSON catalog to this file (`categorize` command),
+    /// see [catalog](crate::core::catalog).
+    pub catalog_path: String,
+    /// If set, the categorized roms are saved as a SQLite database to this file (`categorize` command),
+    /// see [write_sqlite](crate::core::export::sqlite::write_sqlite).
+    pub sqlite_path: String,
+    /// If set, the roms are compared against this (older) MAME ROM database.
+    pub previous_mame_xml_path: String,
+    /// MAME support file of the older release, optional.
+    pub previous_catver_path: String,
+    /// Path to the generated diff report (.md or .json), printed to console if not set.
+    pub diff_path: String,
+    /// Local address the JSON HTTP API listens on (`serve` command).
+    pub address: String,
     /// If true, show progress bar.
     pub progress: bool,
 }
@@ -49,6 +174,59 @@ impl Args {
     pub fn new() -> Args {
         Default::default()
     }
+
+    ///
+    /// @return [CopyOptions] from the copy related arguments.
+    ///
+    pub fn copy_options(&self) -> CuratorResult<CopyOptions> {
+        let filter = self.parsed_filter()?;
+        let not_empty = |value: &String| if value.is_empty() { None } else { Some(value.clone()) };
+        Ok(CopyOptions {
+            ignore_not_working_chd: self.ignore_not_working_chd,
+            simulation: self.simulation,
+            subset_start: not_empty(&self.subset_start),
+            subset_end: not_empty(&self.subset_end),
+            filter,
+            scan: self.scan_options(),
+            unknown_files: self.unknown_files,
+            rename_identified: self.rename_identified,
+            torrentzip: self.torrentzip,
+            ..CopyOptions::new(
+                self.source_path.iter().map(PathBuf::from).collect(),
+                PathBuf::from(&self.destination_path),
+            )
+        })
+    }
+
+    ///
+    /// @return [VerifyOptions] from the verify related arguments.
+    ///
+    pub fn verify_options(&self) -> CuratorResult<VerifyOptions> {
+        Ok(VerifyOptions {
+            scan: self.scan_options(),
+            filter: self.parsed_filter()?,
+            check_contents: self.check_contents,
+        })
+    }
+
+    fn parsed_filter(&self) -> CuratorResult<Option<Filter>> {
+        if self.filter.is_empty() { return Ok(None); }
+        Filter::from_str(&self.filter).map(Some).map_err(CuratorError::Filter)
+    }
+
+    ///
+    /// @return [ScanOptions] from the source scan related arguments.
+    ///
+    pub fn scan_options(&self) -> ScanOptions {
+        ScanOptions { max_depth: self.scan_depth, ignore: self.ignore.clone() }
+    }
+
+    ///
+    /// @return [TerminalProgress] if `progress` is set, [NoProgress] otherwise.
+    ///
+    pub fn progress(&self) -> Box<dyn Progress> {
+        if self.progress { Box::new(TerminalProgress::default()) } else { Box::new(NoProgress) }
+    }
 }
 
 pub fn command() -> Command {
@@ -56,129 +234,557 @@ pub fn command() -> Command {
         .version(crate_version!())
         .author(crate_authors!("\n"))
         .about(crate_description!())
+        .subcommand_required(true)
         .arg_required_else_help(true)
-        .after_help("Examples:
-    roms-curator --mame-xml-path=/mame/mame.xml --catver-path=/mame/catver.ini --source-path=/roms --destination-path=/roms-new/
-    roms-curator -m /mame/mame.xml -c /mame/catver.ini -s /roms -d /roms-new/")
         .arg(
-            Arg::new("mame_xml_path")
-                .help("File path of Mame xml file. Extract with 'mame.exe -listxml > mame.xml'")
-                .long("mame-xml-path")
-                .short('m')
+            Arg::new("config")
+                .help("Configuration file with default argument values. Default is ./roms-curator.toml or ~/.config/roms-curator/config.toml")
+                .long("config")
                 .num_args(1)
-                .required(true)
-                .value_parser(validate_mame_xml_file)
+                .global(true)
+                .required(false),
         )
         .arg(
-            Arg::new("catver_path")
-                .help("File path of roms category file. Download pack from here [https://www.progettosnaps.net/support/]")
-                .long("catver-path")
-                .short('c')
+            Arg::new("profile")
+                .help("Profile of the configuration file to use, for ex, 'cabinet' for [profile.cabinet]")
+                .long("profile")
                 .num_args(1)
-                .required(true)
-                .value_parser(validate_catver_ini_file),
+                .global(true)
+                .required(false),
         )
-        .arg(
-            Arg::new("source_path")
-                .help("Directory path(s) where your roms are. If more than one separate with a comma ','")
-                .long("source-path")
-                .short('s')
-                .num_args(1)
-                .required(true)
-                .value_parser(validate_source_paths),
+        .after_help("Examples:
+    roms-curator copy --mame-xml-path=/mame/mame.xml --catver-path=/mame/catver.ini --source-path=/roms --destination-path=/roms-new/
+    roms-curator copy -m /mame/mame.xml -c /mame/catver.ini -s /roms -d /roms-new/
+    roms-curator stats -m /mame/mame.xml -c /mame/catver.ini")
+        .subcommand(
+            Command::new("categorize")
+                .about("Categorize roms and show how many are in each category")
+                .arg(mame_xml_path_arg())
+                .arg(catver_path_arg())
+                .arg(unknown_values_arg())
+                .arg(feature_rules_arg())
+                .arg(filter_arg())
+                .arg(folder_ini_path_arg())
+                .arg(
+                    Arg::new("catalog_path")
+                        .help("File path where the categorized roms should be saved as a JSON catalog (.json or .json.gz)")
+                        .long("catalog-path")
+                        .num_args(1)
+                        .required(false)
+                        .default_value("")
+                        .value_parser(validate_catalog_path),
+                )
+                .arg(
+                    Arg::new("sqlite_path")
+                        .help("File path where the categorized roms should be saved as a SQLite database (.db or .sqlite)")
+                        .long("sqlite-path")
+                        .num_args(1)
+                        .required(false)
+                        .default_value("")
+                        .value_parser(validate_sqlite_path),
+                )
+                .arg(progress_arg())
         )
-        .arg(
-            Arg::new("destination_path")
-                .help("Directory path where your roms will be copied and categorized")
-                .long("destination-path")
-                .short('d')
-                .num_args(1)
-                .required(true)
-                .value_parser(validate_destination_path),
+        .subcommand(
+            Command::new("copy")
+                .about("Categorize roms and copy them to the destination folders")
+                .args(copy_args())
         )
-        .arg(
-            Arg::new("report_path")
-                .help("File path where the report should be saved. Contains all operations separated by successful and unsuccessful status")
-                .long("report-path")
-                .short('r')
-                .num_args(1)
-                .required(false)
-                .default_value("")
-                .value_parser(validate_report_path),
+        .subcommand(
+            Command::new("upgrade")
+                .about("Move roms already in the destination to the folder of their new category and copy only the missing ones")
+                .args(copy_args())
         )
-        .arg(
-            Arg::new("ignore_not_working_chd")
-                .help("If true, not working CHD files will not be copied to [destination_path]. (true|false)")
-                .long("ignore-not-working-chd")
-                .short('i')
-                .num_args(1)
-                .required(false)
-                .default_value("false")
-                .value_parser(validate_ignore_not_working_chd),
+        .subcommand(
+            Command::new("verify")
+                .about("Check the source paths for missing working roms, missing CHDs and unknown files")
+                .arg(mame_xml_path_arg())
+                .arg(catver_path_arg())
+                .arg(unknown_values_arg())
+                .arg(feature_rules_arg())
+                .arg(source_path_arg())
+                .arg(scan_depth_arg())
+                .arg(ignore_arg())
+                .arg(filter_arg())
+                .arg(
+                    Arg::new("check_contents")
+                        .help("If true, the files in each zip, 7z or folder are checked against the CRC32 and size in mame.xml. (true|false)")
+                        .long("check-contents")
+                        .num_args(1)
+                        .required(false)
+                        .default_value("false")
+                        .value_parser(validate_check_contents),
+                )
+                .arg(progress_arg())
         )
-        .arg(
-            Arg::new("simulation")
-                .help("If true, not working CHD files will not be copied to [destination_path]. (true|false)")
-                .long("simulation")
-                .num_args(1)
-                .required(false)
-                .default_value("false")
-                .value_parser(validate_simulation),
+        .subcommand(
+            Command::new("diff")
+                .about("Compare with a previous MAME release and report status changes")
+                .arg(mame_xml_path_arg())
+                .arg(catver_path_arg().required(false).default_value("").value_parser(validate_optional_catver_ini_file))
+                .arg(unknown_values_arg())
+                .arg(feature_rules_arg())
+                .arg(
+                    Arg::new("previous_mame_xml_path")
+                        .help("File path of the older Mame xml file")
+                        .long("previous-mame-xml-path")
+                        .num_args(1)
+                        .required(true)
+                        .value_parser(validate_mame_xml_file),
+                )
+                .arg(
+                    Arg::new("previous_catver_path")
+                        .help("File path of the roms category file matching --previous-mame-xml-path")
+                        .long("previous-catver-path")
+                        .num_args(1)
+                        .required(false)
+                        .default_value("")
+                        .value_parser(validate_optional_catver_ini_file),
+                )
+                .arg(
+                    Arg::new("diff_path")
+                        .help("File path where the diff report should be saved (.md or .json). If not set, it's printed to the console")
+                        .long("diff-path")
+                        .num_args(1)
+                        .required(false)
+                        .default_value("")
+                        .value_parser(validate_diff_path),
+                )
+                .arg(progress_arg())
         )
-        .arg(
-            Arg::new("subset_start")
-                .help("If true, not working CHD files will not be copied to [destination_path]. (true|false)")
-                .long("subset-start")
-                .num_args(1)
-                .required(false)
-                .default_value("")
-                .value_parser(validate_subset),
+        .subcommand(
+            Command::new("export")
+                .about("Write frontend files for the roms already in the destination folders")
+                .arg(mame_xml_path_arg())
+                .arg(catver_path_arg())
+                .arg(unknown_values_arg())
+                .arg(feature_rules_arg())
+                .arg(destination_path_arg())
+                .args(export_args())
+                .arg(progress_arg())
         )
-        .arg(
-            Arg::new("subset_end")
-                .help("If true, not working CHD files will not be copied to [destination_path]. (true|false)")
-                .long("subset-end")
-                .num_args(1)
-                .required(false)
-                .default_value("")
-                .value_parser(validate_subset),
+        .subcommand(
+            Command::new("info")
+                .about("Show category, status, features, dependencies and CHDs of one or more machines")
+                .arg(
+                    Arg::new("names")
+                        .help("Machine names, for ex, robocop")
+                        .num_args(1..)
+                        .required(true),
+                )
+                .arg(mame_xml_path_arg())
+                .arg(catver_path_arg())
+                .arg(unknown_values_arg())
+                .arg(feature_rules_arg())
+                .arg(source_path_arg()
+                    .help("Directory path(s) where your roms are, to check if the machines are there. If more than one separate with a comma ','")
+                    .required(false))
+                .arg(scan_depth_arg())
+                .arg(ignore_arg())
+                .arg(progress_arg())
         )
-        .arg(
-            Arg::new("progress")
-                .help("If true, shows a progress bar. Default is true. (true|false).")
-                .long("progress")
-                .short('p')
-                .num_args(1)
-                .required(false)
-                .default_value("true")
-                .value_parser(validate_progress),
+        .subcommand(
+            Command::new("stats")
+                .about("Show statistics about the categorized roms")
+                .arg(mame_xml_path_arg())
+                .arg(catver_path_arg())
+                .arg(unknown_values_arg())
+                .arg(feature_rules_arg())
+                .arg(filter_arg())
+                .arg(progress_arg())
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serve the categorized roms as a read-only JSON HTTP API on localhost")
+                .arg(mame_xml_path_arg())
+                .arg(catver_path_arg())
+                .arg(unknown_values_arg())
+                .arg(feature_rules_arg())
+                .arg(
+                    Arg::new("address")
+                        .help("Local address to listen on, for ex, 127.0.0.1:8080")
+                        .long("address")
+                        .short('a')
+                        .num_args(1)
+                        .required(false)
+                        .default_value(DEFAULT_ADDRESS)
+                        .value_parser(validate_address),
+                )
+                .arg(progress_arg())
         )
 }
 
+fn copy_args() -> Vec<Arg> {
+    let mut args = vec![
+        mame_xml_path_arg(),
+        catver_path_arg(),
+        unknown_values_arg(),
+        feature_rules_arg(),
+        source_path_arg(),
+        scan_depth_arg(),
+        ignore_arg(),
+        destination_path_arg(),
+        Arg::new("report_path")
+            .help("File path where the report should be saved. Contains all operations separated by successful and unsuccessful status")
+            .long("report-path")
+            .short('r')
+            .num_args(1)
+            .required(false)
+            .default_value("")
+            .value_parser(validate_report_path),
+        Arg::new("report_format")
+            .help("Format of the report file, if not set it's chosen by the report file extension. (md|json|csv|html)")
+            .long("report-format")
+            .num_args(1)
+            .required(false)
+            .value_parser(validate_report_format),
+        Arg::new("ignore_not_working_chd")
+            .help("If true, not working CHD files will not be copied to [destination_path]. (true|false)")
+            .long("ignore-not-working-chd")
+            .short('i')
+            .num_args(1)
+            .required(false)
+            .default_value("false")
+            .value_parser(validate_ignore_not_working_chd),
+        Arg::new("simulation")
+            .help("If true, nothing is copied but the report is still generated. Needs [report_path]. (true|false)")
+            .long("simulation")
+            .num_args(1)
+            .required(false)
+            .default_value("false")
+            .value_parser(validate_simulation),
+        Arg::new("subset_start")
+            .help("Only roms which name, in alphabetical order, is higher than this will be copied")
+            .long("subset-start")
+            .num_args(1)
+            .required(false)
+            .default_value("")
+            .value_parser(validate_subset),
+        Arg::new("subset_end")
+            .help("Only roms which name, in alphabetical order, is lower than this will be copied")
+            .long("subset-end")
+            .num_args(1)
+            .required(false)
+            .default_value("")
+            .value_parser(validate_subset),
+        filter_arg(),
+        Arg::new("unknown_files")
+            .help("What to do with source files that don't match any machine: keep them where they are, or copy or move them to [destination_path]/unknown, where they are identified by checksum. (keep|copy|move)")
+            .long("unknown-files")
+            .num_args(1)
+            .required(false)
+            .default_value("keep")
+            .value_parser(validate_unknown_files),
+        Arg::new("rename_identified")
+            .help("If true, unknown files identified by checksum are renamed after their machine, for ex, 'robocop (world).zip' to 'robocop.zip'. (true|false)")
+            .long("rename-identified")
+            .num_args(1)
+            .required(false)
+            .default_value("false")
+            .value_parser(validate_rename_identified),
+        Arg::new("torrentzip")
+            .help("If true, copied zip files are rewritten in deterministic, TorrentZip-style form: sorted entries, fixed timestamps and maximum deflate compression, so the same roms always give the same zip. (true|false)")
+            .long("torrentzip")
+            .num_args(1)
+            .required(false)
+            .default_value("false")
+            .value_parser(validate_torrentzip),
+    ];
+    args.extend(export_args());
+    args.push(progress_arg());
+    args
+}
+
+fn export_args() -> Vec<Arg> {
+    vec![
+        Arg::new("gamelist")
+            .help("If true, writes an EmulationStation / ES-DE gamelist.xml to each destination folder. (true|false)")
+            .long("gamelist")
+            .num_args(1)
+            .required(false)
+            .default_value("false")
+            .value_parser(validate_gamelist),
+        Arg::new("retroarch_playlist_path")
+            .help("Directory path where RetroArch playlists (.lpl) for the copied roms should be saved")
+            .long("retroarch-playlist-path")
+            .num_args(1)
+            .required(false)
+            .default_value("")
+            .value_parser(validate_retroarch_playlist_path),
+        Arg::new("retroarch_core_path")
+            .help("Core path used in RetroArch playlists, for ex, /cores/mame_libretro.so. Default is DETECT")
+            .long("retroarch-core-path")
+            .num_args(1)
+            .required(false)
+            .default_value(DETECT),
+        Arg::new("retroarch_core_name")
+            .help("Core name used in RetroArch playlists, for ex, 'Arcade (MAME)'. Default is DETECT")
+            .long("retroarch-core-name")
+            .num_args(1)
+            .required(false)
+            .default_value(DETECT),
+        Arg::new("retroarch_playlist_grouping")
+            .help("Create one RetroArch playlist per destination folder or per rom category. (folder|category)")
+            .long("retroarch-playlist-grouping")
+            .num_args(1)
+            .required(false)
+            .default_value("folder")
+            .value_parser(validate_retroarch_playlist_grouping),
+        Arg::new("attract_mode_romlist_path")
+            .help("Directory path where Attract-Mode romlists for the copied roms should be saved")
+            .long("attract-mode-romlist-path")
+            .num_args(1)
+            .required(false)
+            .default_value("")
+            .value_parser(validate_attract_mode_romlist_path),
+        Arg::new("attract_mode_emulator")
+            .help("Attract-Mode emulator name used to launch the roms. Default is mame")
+            .long("attract-mode-emulator")
+            .num_args(1)
+            .required(false)
+            .default_value("mame"),
+        folder_ini_path_arg(),
+    ]
+}
+
+fn mame_xml_path_arg() -> Arg {
+    Arg::new("mame_xml_path")
+        .help("File path of Mame xml file. Extract with 'mame.exe -listxml > mame.xml'")
+        .long("mame-xml-path")
+        .short('m')
+        .num_args(1)
+        .required(true)
+        .value_parser(validate_mame_xml_file)
+}
+
+fn catver_path_arg() -> Arg {
+    Arg::new("catver_path")
+        .help("File path of roms category file. Download pack from here [https://www.progettosnaps.net/support/]")
+        .long("catver-path")
+        .short('c')
+        .num_args(1)
+        .required(true)
+        .value_parser(validate_catver_ini_file)
+}
+
+fn source_path_arg() -> Arg {
+    Arg::new("source_path")
+        .help("Directory path(s) where your roms are. If more than one separate with a comma ','")
+        .long("source-path")
+        .short('s')
+        .num_args(1)
+        .required(true)
+        .value_parser(validate_source_paths)
+}
+
+fn destination_path_arg() -> Arg {
+    Arg::new("destination_path")
+        .help("Directory path where your roms will be copied and categorized")
+        .long("destination-path")
+        .short('d')
+        .num_args(1)
+        .required(true)
+        .value_parser(validate_destination_path)
+}
+
+fn filter_arg() -> Arg {
+    Arg::new("filter")
+        .help("Only include roms matching this expression, for ex, 'status == working && category ~ Shooter && !chd'")
+        .long("filter")
+        .short('f')
+        .num_args(1)
+        .required(false)
+        .default_value("")
+        .value_parser(validate_filter)
+}
+
+fn folder_ini_path_arg() -> Arg {
+    Arg::new("folder_ini_path")
+        .help("Directory path where the categorization should be saved as MAME/MAMEUI folder filter INI files")
+        .long("folder-ini-path")
+        .num_args(1)
+        .required(false)
+        .default_value("")
+        .value_parser(validate_folder_ini_path)
+}
+
+fn unknown_values_arg() -> Arg {
+    Arg::new("unknown_values")
+        .help("How to categorize machines with mame.xml values unknown to this version, for ex, from a newer MAME. (not-working|working|uncategorized|fail)")
+        .long("unknown-values")
+        .num_args(1)
+        .required(false)
+        .default_value("not-working")
+        .value_parser(validate_unknown_values)
+}
+
+fn feature_rules_arg() -> Arg {
+    Arg::new("feature_rules")
+        .help("Rules, added to the default ones, deciding which features make a machine not working, for ex, 'protection:unemulated=not-working,sound=ignore'. (type[:status]=not-working|imperfect|ignore)")
+        .long("feature-rules")
+        .num_args(1)
+        .required(false)
+        .default_value("")
+        .value_parser(validate_feature_rules)
+}
+
+fn scan_depth_arg() -> Arg {
+    Arg::new("scan_depth")
+        .help("How many levels of sub-directories of [source_path] are scanned for roms, for ex, 2 for roms/A-M/robocop.zip. Directories named after a machine are always taken as rom or CHD directories. Default is 0")
+        .long("scan-depth")
+        .num_args(1)
+        .required(false)
+        .default_value("0")
+        .value_parser(validate_scan_depth)
+}
+
+fn ignore_arg() -> Arg {
+    Arg::new("ignore")
+        .help("Glob patterns of files and directories in [source_path] that should be left out, for ex, '*.txt,.trash'. If more than one separate with a comma ','")
+        .long("ignore")
+        .num_args(1)
+        .required(false)
+        .default_value("")
+        .value_parser(validate_ignore)
+}
+
+fn progress_arg() -> Arg {
+    Arg::new("progress")
+        .help("If true, shows a progress bar. Default is true. (true|false).")
+        .long("progress")
+        .short('p')
+        .num_args(1)
+        .required(false)
+        .default_value("true")
+        .value_parser(validate_progress)
+}
+
+///
+/// Builds [Args] from the command line arguments, exits with usage
+/// information if they are not valid.
+///
+/// Arguments not in the command line are taken from `ROMS_CURATOR_*` environment
+/// variables and the configuration file, see [config](crate::core::config).
+///
 pub fn build_args() -> Result<Args, Box<dyn Error>> {
+    let arguments: Vec<OsString> = env::args_os().collect();
+    let command = configured_command(&arguments, env::vars())?;
+    args_from_matches(&command.get_matches_from(arguments))
+}
+
+///
+/// Same as [build_args] but from the given arguments, the first one being the binary name.
+///
+pub fn build_args_from<I, T>(arguments: I) -> Result<Args, Box<dyn Error>>
+where
+    I: IntoIterator<Item=T>,
+    T: Into<OsString> + Clone,
+{
+    let arguments: Vec<OsString> = arguments.into_iter().map(Into::into).collect();
+    let command = configured_command(&arguments, env::vars())?;
+    args_from_matches(&command.try_get_matches_from(arguments)?)
+}
+
+///
+/// @return [command] with the values from the configuration file, selected profile
+/// and environment variables as argument defaults, so that command line arguments
+/// still take precedence.
+///
+pub(crate) fn configured_command(
+    arguments: &[OsString],
+    env_vars: impl Iterator<Item=(String, String)>,
+) -> Result<Command, Box<dyn Error>> {
+    let mut env_values = env_values(env_vars);
+
+    let config_path = find_argument(arguments, "config")
+        .or_else(|| env_values.remove("config"))
+        .map(PathBuf::from)
+        .or_else(Config::discover);
+    let profile = find_argument(arguments, "profile")
+        .or_else(|| env_values.remove("profile"));
+
+    let config = match &config_path {
+        Some(path) => Config::from_file(path)?,
+        None if profile.is_some() => return Err("[profile] requires a configuration file.".into()),
+        None => Config::default(),
+    };
+
     let command = command();
-    let matches = command.get_matches();
+    let known: HashSet<String> = command.get_subcommands()
+        .flat_map(|subcommand| subcommand.get_arguments())
+        .filter(|arg| !arg.is_global_set())
+        .map(|arg| arg.get_id().to_string())
+        .collect();
 
-    let mame_xml_path: &String = matches.get_one("mame_xml_path").expect("validated in args parser");
+    let mut values = config.values(profile.as_deref())?;
+    if let Some(unknown) = values.keys().find(|key| !known.contains(*key)) {
+        return Err(format!("Unknown argument '{unknown}' in configuration file.").into());
+    }
+    // other ROMS_CURATOR_* variables are not arguments, for ex, set by the user for other purposes
+    values.extend(env_values.into_iter().filter(|(key, _)| known.contains(key)));
 
-    let catver_path: &String = matches.get_one("catver_path").expect("validated in args parser");
+    Ok(with_default_values(command, &values))
+}
 
-    let source_path: &Vec<String> = matches.get_one("source_path").expect("validated in args parser");
+fn with_default_values(mut command: Command, values: &ArgValues) -> Command {
+    let names: Vec<String> = command.get_subcommands().map(|subcommand| subcommand.get_name().to_string()).collect();
+    for name in names {
+        command = command.mut_subcommand(name, |mut subcommand| {
+            let ids: Vec<String> = subcommand.get_arguments()
+                .map(|arg| arg.get_id().to_string())
+                .filter(|id| values.contains_key(id))
+                .collect();
+            for id in ids {
+                let value = values[&id].clone();
+                subcommand = subcommand.mut_arg(id, |arg| arg.default_value(value).required(false));
+            }
+            subcommand
+        });
+    }
+    command
+}
 
-    let destination: &String = matches.get_one("destination_path").expect("validated in args parser");
-    let destination_path = Path::new(destination.as_str());
-    if destination_path.is_file() {
-        return Err("Destination path needs to be a directory.".into());
-    } else if !destination_path.exists() &&
-        fs::create_dir_all(destination_path).is_err() {
-        return Err("Destination directory cannot be created, verify path and/or permissions.".into());
+///
+/// @return Value of `--<name> value` or `--<name>=value`, before the arguments are parsed.
+///
+fn find_argument(arguments: &[OsString], name: &str) -> Option<String> {
+    let flag = format!("--{name}");
+    let prefix = format!("--{name}=");
+    let mut arguments = arguments.iter().map(|argument| argument.to_string_lossy());
+    while let Some(argument) = arguments.next() {
+        if argument == flag { return arguments.next().map(|value| value.to_string()); }
+        if let Some(value) = argument.strip_prefix(&prefix) { return Some(value.to_string()); }
     }
-    // source path and destination path cannot be equal
-    if source_path.iter().any(|path| path.eq_ignore_ascii_case(destination.as_str())) {
-        return Err("[source-path] and [destination-path] cannot be the same.".into());
+    None
+}
+
+fn args_from_matches(matches: &ArgMatches) -> Result<Args, Box<dyn Error>> {
+    let (name, matches) = matches.subcommand().ok_or("Missing command.")?;
+    let command = CommandType::from_str(name)?;
+
+    // not all subcommands define all arguments, these are left with their default value
+    let string = |id: &str| matches.try_get_one::<String>(id).ok().flatten().cloned().unwrap_or_default();
+    let flag = |id: &str| matches.try_get_one::<bool>(id).ok().flatten().copied().unwrap_or_default();
+
+    let source_path: Vec<String> = matches.try_get_one::<Vec<String>>("source_path")
+        .ok().flatten().cloned().unwrap_or_default();
+
+    let destination = string("destination_path");
+    if !destination.is_empty() {
+        let destination_path = Path::new(destination.as_str());
+        if destination_path.is_file() {
+            return Err("Destination path needs to be a directory.".into());
+        } else if !destination_path.exists() &&
+            fs::create_dir_all(destination_path).is_err() {
+            return Err("Destination directory cannot be created, verify path and/or permissions.".into());
+        }
+        // source path and destination path cannot be equal
+        if source_path.iter().any(|path| path.eq_ignore_ascii_case(destination.as_str())) {
+            return Err("[source-path] and [destination-path] cannot be the same.".into());
+        }
     }
 
-    let report: &String = matches.get_one("report_path").expect("validated in args parser");
+    let report = string("report_path");
     if !report.is_empty() {
         let report_path = Path::new(report.as_str());
         if report_path.is_file() {
@@ -186,34 +792,82 @@ pub fn build_args() -> Result<Args, Box<dyn Error>> {
         } else if File::create(report_path).is_err() {
             return Err("Report file cannot be created, verify path and/or permissions.".into());
         } else {
-            fs::remove_file(report_path).unwrap();
+            fs::remove_file(report_path)?;
         }
     }
 
-    let ignore_not_working_chd: &bool = matches.get_one("ignore_not_working_chd").expect("validated in args parser");
+    let report_format = match matches.try_get_one::<ReportFormat>("report_format").ok().flatten() {
+        Some(format) => *format,
+        None if report.is_empty() => ReportFormat::default(),
+        None => ReportFormat::from_path(&report)
+            .ok_or("Report file should have one of the extensions .md, .json, .csv or .html, or set --report-format")?,
+    };
 
-    let simulation: &bool = matches.get_one("simulation").expect("validated in args parser");
+    let simulation = flag("simulation");
     // simulation implies report
-    if *simulation && report.is_empty() {
+    if simulation && report.is_empty() {
         return Err("Simulation mode requires a report file.".into());
     }
 
-    let subset_start: &String = matches.get_one("subset_start").expect("validated in args parser");
-    let subset_end: &String = matches.get_one("subset_end").expect("validated in args parser");
+    let retroarch_playlist_grouping = matches.try_get_one::<PlaylistGrouping>("retroarch_playlist_grouping")
+        .ok().flatten().copied().unwrap_or_default();
 
-    let progress: &bool = matches.get_one("progress").expect("validated in args parser");
+    let unknown_values = matches.try_get_one::<UnknownValuePolicy>("unknown_values")
+        .ok().flatten().copied().unwrap_or_default();
+
+    let feature_rules = matches.try_get_one::<FeatureRules>("feature_rules")
+        .ok().flatten().cloned().unwrap_or_default();
+
+    let scan_depth = matches.try_get_one::<usize>("scan_depth")
+        .ok().flatten().copied().unwrap_or_default();
+
+    let ignore = matches.try_get_one::<IgnorePatterns>("ignore")
+        .ok().flatten().cloned().unwrap_or_default();
+
+    let unknown_files = matches.try_get_one::<UnknownFilesAction>("unknown_files")
+        .ok().flatten().copied().unwrap_or_default();
+
+    let names: Vec<String> = matches.try_get_many::<String>("names").ok().flatten()
+        .map(|names| names.cloned().collect())
+        .unwrap_or_default();
 
     Ok(Args {
-        mame_xml_path: mame_xml_path.clone(),
-        catver_path: catver_path.clone(),
-        source_path: source_path.to_vec(),
-        destination_path: destination.clone(),
-        report_path: report.clone(),
-        ignore_not_working_chd: *ignore_not_working_chd,
-        simulation: *simulation,
-        subset_start: subset_start.clone(),
-        subset_end: subset_end.clone(),
-        progress: *progress,
+        command,
+        names,
+        mame_xml_path: string("mame_xml_path"),
+        catver_path: string("catver_path"),
+        unknown_values,
+        feature_rules,
+        source_path,
+        scan_depth,
+        ignore,
+        check_contents: flag("check_contents"),
+        destination_path: destination,
+        report_path: report,
+        report_format,
+        ignore_not_working_chd: flag("ignore_not_working_chd"),
+        simulation,
+        subset_start: string("subset_start"),
+        subset_end: string("subset_end"),
+        filter: string("filter"),
+        unknown_files,
+        rename_identified: flag("rename_identified"),
+        torrentzip: flag("torrentzip"),
+        gamelist: flag("gamelist"),
+        retroarch_playlist_path: string("retroarch_playlist_path"),
+        retroarch_core_path: string("retroarch_core_path"),
+        retroarch_core_name: string("retroarch_core_name"),
+        retroarch_playlist_grouping,
+        attract_mode_romlist_path: string("attract_mode_romlist_path"),
+        attract_mode_emulator: string("attract_mode_emulator"),
+        folder_ini_path: string("folder_ini_path"),
+        catalog_path: string("catalog_path"),
+        sqlite_path: string("sqlite_path"),
+        previous_mame_xml_path: string("previous_mame_xml_path"),
+        previous_catver_path: string("previous_catver_path"),
+        diff_path: string("diff_path"),
+        address: string("address"),
+        progress: flag("progress"),
     })
 }
 
@@ -267,10 +921,6 @@ fn validate_report_path(value: &str) -> Result<String, String> {
         return Ok(value.to_string());
     }
 
-    if !value.ends_with(".md") {
-        return Err("Report file should have the extension .md".into());
-    }
-
     let path = sanitize_path(value);
     let report = Path::new(path.as_str());
 
@@ -281,6 +931,11 @@ fn validate_report_path(value: &str) -> Result<String, String> {
     }
 }
 
+fn validate_report_format(value: &str) -> Result<ReportFormat, String> {
+    ReportFormat::from_str(value)
+        .map_err(|_| "Invalid value for report_format. (md|json|csv|html)".into())
+}
+
 fn validate_ignore_not_working_chd(value: &str) -> Result<bool, String> {
     if value.eq_ignore_ascii_case("true") {
         Ok(true)
@@ -305,6 +960,162 @@ fn validate_subset(value: &str) -> Result<String, String> {
     Ok(value.to_ascii_lowercase())
 }
 
+fn validate_filter(value: &str) -> Result<String, String> {
+    if value.trim().is_empty() {
+        return Ok(String::new());
+    }
+    Filter::from_str(value).map(|_| value.to_string())
+}
+
+fn validate_check_contents(value: &str) -> Result<bool, String> {
+    if value.eq_ignore_ascii_case("true") {
+        Ok(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Ok(false)
+    } else {
+        Err("Invalid value for check_contents. (true|false)".into())
+    }
+}
+
+fn validate_unknown_files(value: &str) -> Result<UnknownFilesAction, String> {
+    UnknownFilesAction::from_str(value)
+        .map_err(|_| "Invalid value for unknown_files. (keep|copy|move)".into())
+}
+
+fn validate_rename_identified(value: &str) -> Result<bool, String> {
+    if value.eq_ignore_ascii_case("true") {
+        Ok(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Ok(false)
+    } else {
+        Err("Invalid value for rename_identified. (true|false)".into())
+    }
+}
+
+fn validate_torrentzip(value: &str) -> Result<bool, String> {
+    if value.eq_ignore_ascii_case("true") {
+        Ok(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Ok(false)
+    } else {
+        Err("Invalid value for torrentzip. (true|false)".into())
+    }
+}
+
+fn validate_gamelist(value: &str) -> Result<bool, String> {
+    if value.eq_ignore_ascii_case("true") {
+        Ok(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Ok(false)
+    } else {
+        Err("Invalid value for gamelist. (true|false)".into())
+    }
+}
+
+fn validate_retroarch_playlist_path(value: &str) -> Result<String, String> {
+    let path = sanitize_path(value);
+
+    if Path::new(path.as_str()).is_file() {
+        Err("RetroArch playlist path needs to be a directory.".into())
+    } else {
+        Ok(path)
+    }
+}
+
+fn validate_scan_depth(value: &str) -> Result<usize, String> {
+    value.parse::<usize>().map_err(|_| "Invalid value for scan_depth, expected 0 or more.".into())
+}
+
+fn validate_ignore(value: &str) -> Result<IgnorePatterns, String> {
+    IgnorePatterns::from_str(value)
+}
+
+fn validate_feature_rules(value: &str) -> Result<FeatureRules, String> {
+    FeatureRules::from_str(value)
+}
+
+fn validate_unknown_values(value: &str) -> Result<UnknownValuePolicy, String> {
+    UnknownValuePolicy::from_str(value)
+        .map_err(|_| "Invalid value for unknown_values. (not-working|working|uncategorized|fail)".into())
+}
+
+fn validate_retroarch_playlist_grouping(value: &str) -> Result<PlaylistGrouping, String> {
+    PlaylistGrouping::from_str(value)
+        .map_err(|_| "Invalid value for retroarch_playlist_grouping. (folder|category)".into())
+}
+
+fn validate_attract_mode_romlist_path(value: &str) -> Result<String, String> {
+    let path = sanitize_path(value);
+
+    if Path::new(path.as_str()).is_file() {
+        Err("Attract-Mode romlist path needs to be a directory.".into())
+    } else {
+        Ok(path)
+    }
+}
+
+fn validate_folder_ini_path(value: &str) -> Result<String, String> {
+    let path = sanitize_path(value);
+
+    if Path::new(path.as_str()).is_file() {
+        Err("Folder INI path needs to be a directory.".into())
+    } else {
+        Ok(path)
+    }
+}
+
+fn validate_optional_catver_ini_file(path: &str) -> Result<String, String> {
+    if path.is_empty() { Ok(String::new()) } else { validate_catver_ini_file(path) }
+}
+
+fn validate_diff_path(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Ok(value.to_string());
+    }
+
+    let path = sanitize_path(value);
+    match ReportFormat::from_path(&path) {
+        Some(ReportFormat::Markdown) | Some(ReportFormat::Json) => Ok(path),
+        _ => Err("Diff file should have the extension .md or .json.".into()),
+    }
+}
+
+fn validate_catalog_path(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Ok(value.to_string());
+    }
+
+    let path = sanitize_path(value);
+    if path.ends_with(".json") || path.ends_with(".json.gz") {
+        Ok(path)
+    } else {
+        Err("Catalog file should have the extension .json or .json.gz.".into())
+    }
+}
+
+fn validate_sqlite_path(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Ok(value.to_string());
+    }
+
+    let path = sanitize_path(value);
+    if [".db", ".sqlite", ".sqlite3"].iter().any(|extension| path.ends_with(extension)) {
+        Ok(path)
+    } else {
+        Err("SQLite file should have the extension .db, .sqlite or .sqlite3.".into())
+    }
+}
+
+fn validate_address(value: &str) -> Result<String, String> {
+    let address = SocketAddr::from_str(value)
+        .map_err(|_| "Invalid address, for ex, 127.0.0.1:8080".to_string())?;
+    if address.ip().is_loopback() {
+        Ok(value.to_string())
+    } else {
+        Err("Only localhost addresses are allowed, for ex, 127.0.0.1:8080".into())
+    }
+}
+
 fn validate_progress(value: &str) -> Result<bool, String> {
     if value.eq_ignore_ascii_case("true") {
         Ok(true)
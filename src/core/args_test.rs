@@ -1,6 +1,59 @@
-use crate::core::args::command;
+use crate::core::args::{build_args_from, command, CommandType};
+use crate::core::identify::UnknownFilesAction;
 
 #[test]
 fn verify_command() {
     command().debug_assert();
 }
+
+#[test]
+fn should_build_args_for_each_subcommand() {
+    let mame = "--mame-xml-path=tests/resources/listxml_0244.xml";
+    let catver = "--catver-path=tests/resources/catver_0244.ini";
+
+    let args = build_args_from(["roms-curator", "stats", mame, catver]).unwrap();
+    assert_eq!(args.command, CommandType::Stats);
+    assert!(args.source_path.is_empty());
+    assert!(args.destination_path.is_empty());
+    assert!(args.progress);
+
+    let args = build_args_from(["roms-curator", "diff", mame, "--previous-mame-xml-path=tests/resources/listxml_0245.xml"]).unwrap();
+    assert_eq!(args.command, CommandType::Diff);
+    assert!(args.catver_path.is_empty());
+    assert_eq!(args.previous_mame_xml_path, "tests/resources/listxml_0245.xml");
+
+    let args = build_args_from(["roms-curator", "verify", mame, catver, "--source-path=tests/resources/merged_roms/", "--scan-depth=2", "--ignore=*.txt,.trash"]).unwrap();
+    assert_eq!(args.command, CommandType::Verify);
+    assert_eq!(args.source_path, vec!["tests/resources/merged_roms/"]);
+    assert_eq!(args.scan_options().max_depth, 2);
+    assert_eq!(args.ignore.patterns(), ["*.txt", ".trash"]);
+
+    let args = build_args_from(["roms-curator", "upgrade", mame, catver, "-s", "tests/resources/merged_roms/", "-d", "target/tests/args_upgrade"]).unwrap();
+    assert_eq!(args.command, CommandType::Upgrade);
+    assert_eq!(args.attract_mode_emulator, "mame");
+    assert_eq!(args.unknown_files, UnknownFilesAction::Keep);
+
+    let args = build_args_from(["roms-curator", "copy", mame, catver, "-s", "tests/resources/merged_roms/", "-d", "target/tests/args_copy",
+        "--unknown-files=move", "--rename-identified=true", "--torrentzip=true"]).unwrap();
+    let options = args.copy_options().unwrap();
+    assert_eq!(options.unknown_files, UnknownFilesAction::Move);
+    assert!(options.rename_identified);
+    assert!(options.torrentzip);
+
+    let args = build_args_from(["roms-curator", "serve", mame, catver]).unwrap();
+    assert_eq!(args.command, CommandType::Serve);
+    assert_eq!(args.address, "127.0.0.1:8080");
+}
+
+#[test]
+fn should_reject_arguments_of_other_subcommands() {
+    let mame = "--mame-xml-path=tests/resources/listxml_0244.xml";
+    let catver = "--catver-path=tests/resources/catver_0244.ini";
+
+    assert!(build_args_from(["roms-curator"]).is_err());
+    assert!(build_args_from(["roms-curator", "stats", mame, catver, "--source-path=tests/resources/merged_roms/"]).is_err());
+    assert!(build_args_from(["roms-curator", "categorize", mame]).is_err());
+    assert!(build_args_from(["roms-curator", "verify", mame, catver, "--source-path=tests/resources/merged_roms/", "--unknown-files=copy"]).is_err());
+    assert!(build_args_from(["roms-curator", "diff", mame]).is_err());
+    assert!(build_args_from(["roms-curator", "serve", mame, catver, "--address=0.0.0.0:8080"]).is_err());
+}
@@ -0,0 +1,149 @@
+//! Categorized rom collection saved as JSON, optionally gzip compressed, so that it
+//! can be loaded again, or by other tools, without `mame.xml` and `catver.ini`.
+//!
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "generator": "roms-curator 0.1.5",
+//!   "generated": "2024-05-01T10:00:00+01:00",
+//!   "roms": {
+//!     "robocop": { "data": { "description": "Robocop (World revision 4)", .. }, "category": "Working", .. }
+//!   }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{CuratorError, CuratorResult};
+use crate::models::roms::{Rom, Roms};
+
+/// Version of the catalog format written by this version, increased on incompatible changes.
+pub const CATALOG_FORMAT_VERSION: u32 = 1;
+
+/// First bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Compression of a catalog file, see [write_catalog_to].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogCompression {
+    #[default]
+    None,
+    Gzip,
+}
+
+impl CatalogCompression {
+    /// @return [Gzip](CatalogCompression::Gzip) if `path` ends with `.gz`, for ex, `roms.json.gz`.
+    pub fn from_path(path: &Path) -> CatalogCompression {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("gz") => CatalogCompression::Gzip,
+            _ => CatalogCompression::None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CatalogOut<'a> {
+    format_version: u32,
+    generator: String,
+    generated: String,
+    // sorted so that the same collection always gives the same file
+    roms: BTreeMap<&'a str, &'a Rom>,
+}
+
+#[derive(Deserialize)]
+struct CatalogIn {
+    format_version: u32,
+    // only deserialized once the version is known to be compatible
+    roms: serde_json::Value,
+}
+
+///
+/// Writes `roms` to `path`, gzip compressed if it ends with `.gz`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use roms_curator::core::catalog::{read_catalog, write_catalog};
+///
+/// let roms = roms_curator::load_roms("mame.xml", "catver.ini")?;
+/// write_catalog(&roms, "roms.json.gz")?;
+///
+/// let roms = read_catalog("roms.json.gz")?;
+/// # Ok::<(), roms_curator::error::CuratorError>(())
+/// ```
+///
+pub fn write_catalog(roms: &Roms, path: impl AsRef<Path>) -> CuratorResult<()> {
+    let path = path.as_ref();
+    let file = File::create(path)
+        .map_err(|err| CuratorError::path(path, &format!("Error creating catalog ({err})")))?;
+    write_catalog_to(roms, BufWriter::new(file), CatalogCompression::from_path(path))
+}
+
+///
+/// Writes `roms` to `writer`, see [module](self) documentation for the format.
+///
+pub fn write_catalog_to(roms: &Roms, writer: impl Write, compression: CatalogCompression) -> CuratorResult<()> {
+    let catalog = CatalogOut {
+        format_version: CATALOG_FORMAT_VERSION,
+        generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+        generated: chrono::offset::Local::now().to_rfc3339(),
+        roms: roms.iter().map(|(name, rom)| (name.as_str(), rom)).collect(),
+    };
+
+    match compression {
+        CatalogCompression::None => write_json(&catalog, writer),
+        CatalogCompression::Gzip => {
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            serde_json::to_writer(&mut encoder, &catalog)?;
+            encoder.finish()?.flush()?;
+            Ok(())
+        }
+    }
+}
+
+fn write_json(catalog: &CatalogOut, mut writer: impl Write) -> CuratorResult<()> {
+    serde_json::to_writer_pretty(&mut writer, catalog)?;
+    writer.flush()?;
+    Ok(())
+}
+
+///
+/// Reads a catalog written by [write_catalog], compressed or not.
+///
+pub fn read_catalog(path: impl AsRef<Path>) -> CuratorResult<Roms> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .map_err(|err| CuratorError::path(path, &format!("Error reading catalog ({err})")))?;
+    read_catalog_from(file)
+}
+
+///
+/// Reads a catalog from `reader`, gzip compression is detected from the first bytes.
+///
+/// @return A [Catalog](CuratorError::Catalog) error if the catalog was written by
+/// an incompatible version.
+///
+pub fn read_catalog_from(reader: impl Read) -> CuratorResult<Roms> {
+    let mut reader = BufReader::new(reader);
+    let catalog: CatalogIn = if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        serde_json::from_reader(BufReader::new(GzDecoder::new(reader)))?
+    } else {
+        serde_json::from_reader(reader)?
+    };
+
+    if catalog.format_version != CATALOG_FORMAT_VERSION {
+        return Err(CuratorError::Catalog(format!(
+            "format version {} is not supported, expected {CATALOG_FORMAT_VERSION}.", catalog.format_version
+        )));
+    }
+
+    Ok(serde_json::from_value(catalog.roms)?)
+}
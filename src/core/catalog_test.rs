@@ -0,0 +1,64 @@
+use crate::read_mame_xml;
+use crate::core::catalog::{CatalogCompression, read_catalog_from, write_catalog_to};
+use crate::core::roms_service::{parse, UnfilteredRomsExt};
+use crate::core::test_utils::build_roms;
+use crate::error::CuratorError;
+use crate::models::roms::{CategoryReason, Roms, Status};
+
+fn assert_same_roms(expected: &Roms, actual: &Roms) {
+    assert_eq!(expected.len(), actual.len());
+    expected.iter().for_each(|(name, rom)| {
+        let loaded = &actual[name];
+        assert_eq!(loaded.data, rom.data, "{name}");
+        assert_eq!(loaded.category, rom.category, "{name}");
+        assert_eq!(loaded.reason, rom.reason, "{name}");
+    });
+}
+
+#[test]
+fn should_write_and_read_catalog() {
+    let roms = build_roms();
+
+    let mut json: Vec<u8> = Vec::new();
+    write_catalog_to(&roms, &mut json, CatalogCompression::None).unwrap();
+    let loaded = read_catalog_from(json.as_slice()).unwrap();
+    assert_same_roms(&roms, &loaded);
+
+    let text = String::from_utf8(json.clone()).unwrap();
+    assert!(text.contains("\"format_version\": 1"));
+    assert!(text.contains("\"description\": \"Robocop (World revision 4)\""));
+    assert!(text.contains("\"driver\": \"Good\""));
+
+    let mut gzip: Vec<u8> = Vec::new();
+    write_catalog_to(&roms, &mut gzip, CatalogCompression::Gzip).unwrap();
+    assert!(gzip.len() < json.len());
+    let loaded = read_catalog_from(gzip.as_slice()).unwrap();
+    assert_same_roms(&roms, &loaded);
+}
+
+#[test]
+fn should_keep_unknown_values_and_reasons() {
+    let contents = r#"<mame><machine name="newgame"><description>New game</description><driver status="unemulated" emulation="good"/></machine></mame>"#;
+    let roms = parse(read_mame_xml(contents).unwrap(), Default::default()).unwrap().categorize_roms().unwrap();
+
+    let mut json: Vec<u8> = Vec::new();
+    write_catalog_to(&roms, &mut json, CatalogCompression::None).unwrap();
+    let loaded = read_catalog_from(json.as_slice()).unwrap();
+
+    let status = loaded["newgame"].data.status.as_ref().unwrap();
+    assert_eq!(status.driver, Status::Unknown("unemulated".to_string()));
+    assert_eq!(loaded["newgame"].reason, CategoryReason::UnknownValue("driver status 'unemulated'".to_string()));
+}
+
+#[test]
+fn should_reject_incompatible_catalog() {
+    let err = read_catalog_from(r#"{"format_version": 99, "roms": {}}"#.as_bytes()).unwrap_err();
+    assert!(matches!(err, CuratorError::Catalog(_)));
+
+    // the version is checked before the roms, whose format might have changed
+    let err = read_catalog_from(r#"{"format_version": 99, "roms": [{"name": "robocop", "status": 1}]}"#.as_bytes()).unwrap_err();
+    assert!(matches!(err, CuratorError::Catalog(_)));
+
+    let err = read_catalog_from("not json".as_bytes()).unwrap_err();
+    assert!(matches!(err, CuratorError::Json(_)));
+}
@@ -0,0 +1,147 @@
+//! Default argument values from a TOML configuration file and environment variables.
+//!
+//! ```toml
+//! mame_xml_path = "/mame/mame.xml"
+//! catver_path = "/mame/catver.ini"
+//! source_path = ["/roms", "/chd-roms"]
+//!
+//! [profile.cabinet]
+//! destination_path = "/cabinet/roms"
+//! filter = "status == working && !chd"
+//! ```
+//!
+//! Values are applied in this order, each one overriding the previous:
+//! configuration file, selected profile, `ROMS_CURATOR_*` environment variables
+//! and, last, command line arguments.
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use toml::{Table, Value};
+
+/// Configuration file name searched in the current directory.
+pub const CONFIG_FILE_NAME: &str = "roms-curator.toml";
+
+/// Prefix of environment variables overriding arguments, for ex, `ROMS_CURATOR_MAME_XML_PATH`.
+pub const ENV_PREFIX: &str = "ROMS_CURATOR_";
+
+/// Argument values by argument id, for ex, `mame_xml_path`.
+pub type ArgValues = HashMap<String, String>;
+
+/// Argument values from a configuration file, see [module](self) documentation.
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Values outside of any profile.
+    pub values: ArgValues,
+    /// Values of each `[profile.<name>]` table.
+    pub profiles: HashMap<String, ArgValues>,
+}
+
+impl FromStr for Config {
+    type Err = String;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let table: Table = contents.parse().map_err(|err| format!("Invalid configuration file: {err}"))?;
+
+        let mut config = Config::default();
+        for (key, value) in table {
+            if key == "profile" {
+                let Value::Table(profiles) = value else {
+                    return Err("[profile] should be a table of profiles, for ex, [profile.cabinet].".into());
+                };
+                for (name, profile) in profiles {
+                    let Value::Table(profile) = profile else {
+                        return Err(format!("Profile '{name}' should be a table, for ex, [profile.{name}]."));
+                    };
+                    config.profiles.insert(name, to_arg_values(profile)?);
+                }
+            } else {
+                let (key, value) = to_arg_value(&key, value)?;
+                config.values.insert(key, value);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+impl Config {
+    pub fn from_file(path: &Path) -> Result<Config, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Error reading configuration file {}: {err}", path.display()))?;
+        Ok(Config::from_str(&contents)?)
+    }
+
+    ///
+    /// Looks for [CONFIG_FILE_NAME] in the current directory, then for `roms-curator/config.toml`
+    /// in `$XDG_CONFIG_HOME` (or `~/.config`) and `%APPDATA%`.
+    ///
+    /// @return The first configuration file found, if any.
+    ///
+    pub fn discover() -> Option<PathBuf> {
+        let mut candidates = vec![PathBuf::from(CONFIG_FILE_NAME)];
+
+        let config_dirs = [
+            env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+            env::var_os("HOME").map(|home| Path::new(&home).join(".config")),
+            env::var_os("APPDATA").map(PathBuf::from),
+        ];
+        config_dirs.into_iter().flatten()
+            .for_each(|dir| candidates.push(dir.join("roms-curator").join("config.toml")));
+
+        candidates.into_iter().find(|path| path.is_file())
+    }
+
+    ///
+    /// @return Values outside of any profile, overridden by the values of `profile`, if any.
+    ///
+    pub fn values(&self, profile: Option<&str>) -> Result<ArgValues, String> {
+        let mut values = self.values.clone();
+        if let Some(profile) = profile {
+            let profile_values = self.profiles.get(profile)
+                .ok_or_else(|| format!("Profile '{profile}' not found in configuration file."))?;
+            values.extend(profile_values.clone());
+        }
+        Ok(values)
+    }
+}
+
+///
+/// @return Argument values from `ROMS_CURATOR_*` environment variables,
+/// for ex, `ROMS_CURATOR_SOURCE_PATH=/roms,/chd-roms`.
+///
+pub fn env_values(vars: impl Iterator<Item=(String, String)>) -> ArgValues {
+    vars.filter_map(|(key, value)| {
+        key.strip_prefix(ENV_PREFIX).map(|key| (key.to_ascii_lowercase(), value))
+    }).collect()
+}
+
+fn to_arg_values(table: Table) -> Result<ArgValues, String> {
+    table.into_iter().map(|(key, value)| to_arg_value(&key, value)).collect()
+}
+
+///
+/// Converts a TOML value to the same format as the command line argument,
+/// arrays are joined with a comma.
+///
+fn to_arg_value(key: &str, value: Value) -> Result<(String, String), String> {
+    let key = key.replace('-', "_");
+    let value = match value {
+        Value::String(value) => value,
+        Value::Boolean(value) => value.to_string(),
+        Value::Integer(value) => value.to_string(),
+        Value::Array(values) => values.into_iter()
+            .map(|value| match value {
+                Value::String(value) => Ok(value),
+                _ => Err(format!("Invalid value for {key}, arrays can only have strings.")),
+            })
+            .collect::<Result<Vec<String>, String>>()?
+            .join(","),
+        _ => return Err(format!("Invalid value for {key}, should be a string, boolean, number or array of strings.")),
+    };
+    Ok((key, value))
+}
@@ -0,0 +1,101 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::core::args::configured_command;
+use crate::core::config::{Config, env_values};
+
+static CONFIG: &str = r#"
+mame_xml_path = "tests/resources/listxml_0244.xml"
+catver-path = "tests/resources/catver_0244.ini"
+source_path = ["tests/resources/merged_roms/", "tests/resources/chds/"]
+progress = false
+
+[profile.cabinet]
+filter = "status == working && !chd"
+progress = true
+"#;
+
+fn write_config(name: &str, contents: &str) -> String {
+    let folder = Path::new("target/tests/config");
+    fs::create_dir_all(folder).unwrap();
+    let path = folder.join(name);
+    fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn arguments(arguments: &[&str]) -> Vec<OsString> {
+    arguments.iter().map(OsString::from).collect()
+}
+
+#[test]
+fn should_read_values_and_profiles() {
+    let config = Config::from_str(CONFIG).unwrap();
+
+    let values = config.values(None).unwrap();
+    assert_eq!(values["catver_path"], "tests/resources/catver_0244.ini");
+    assert_eq!(values["source_path"], "tests/resources/merged_roms/,tests/resources/chds/");
+    assert_eq!(values["progress"], "false");
+    assert!(!values.contains_key("filter"));
+
+    let values = config.values(Some("cabinet")).unwrap();
+    assert_eq!(values["filter"], "status == working && !chd");
+    assert_eq!(values["progress"], "true");
+    assert_eq!(values["mame_xml_path"], "tests/resources/listxml_0244.xml");
+
+    assert!(config.values(Some("archive")).is_err());
+}
+
+#[test]
+fn should_reject_invalid_config() {
+    assert!(Config::from_str("mame_xml_path = ").is_err());
+    assert!(Config::from_str("profile = \"cabinet\"").is_err());
+    assert!(Config::from_str("[profile]\ncabinet = 1").is_err());
+    assert!(Config::from_str("source_path = [1, 2]").is_err());
+    assert!(Config::from_str("[subset]\nstart = \"a\"").is_err());
+}
+
+#[test]
+fn should_read_env_values() {
+    let vars = vec![
+        ("ROMS_CURATOR_MAME_XML_PATH".to_string(), "mame.xml".to_string()),
+        ("HOME".to_string(), "/home".to_string()),
+    ];
+    let values = env_values(vars.into_iter());
+    assert_eq!(values.len(), 1);
+    assert_eq!(values["mame_xml_path"], "mame.xml");
+}
+
+#[test]
+fn command_line_should_override_env_and_config() {
+    let config = write_config("precedence.toml", CONFIG);
+
+    // from config and profile
+    let args = arguments(&["roms-curator", "stats", "--config", &config, "--profile=cabinet"]);
+    let matches = configured_command(&args, std::iter::empty()).unwrap().try_get_matches_from(args).unwrap();
+    let (_, matches) = matches.subcommand().unwrap();
+    assert_eq!(matches.get_one::<String>("catver_path").unwrap(), "tests/resources/catver_0244.ini");
+    assert_eq!(matches.get_one::<String>("filter").unwrap(), "status == working && !chd");
+    assert!(matches.get_one::<bool>("progress").unwrap());
+
+    // env overrides config, command line overrides env
+    let env = vec![
+        ("ROMS_CURATOR_PROGRESS".to_string(), "false".to_string()),
+        ("ROMS_CURATOR_FILTER".to_string(), "chd".to_string()),
+    ];
+    let args = arguments(&["roms-curator", "stats", "--config", &config, "--profile=cabinet", "--filter=clone"]);
+    let matches = configured_command(&args, env.into_iter()).unwrap().try_get_matches_from(args).unwrap();
+    let (_, matches) = matches.subcommand().unwrap();
+    assert!(!matches.get_one::<bool>("progress").unwrap());
+    assert_eq!(matches.get_one::<String>("filter").unwrap(), "clone");
+
+    // values are still validated
+    let config = write_config("invalid.toml", "progress = \"maybe\"");
+    let args = arguments(&["roms-curator", "stats", "--config", &config, "-m", "tests/resources/listxml_0244.xml", "-c", "tests/resources/catver_0244.ini"]);
+    assert!(configured_command(&args, std::iter::empty()).unwrap().try_get_matches_from(args).is_err());
+
+    let config = write_config("unknown.toml", "colour = \"red\"");
+    let args = arguments(&["roms-curator", "stats", "--config", &config]);
+    assert!(configured_command(&args, std::iter::empty()).is_err());
+}
@@ -0,0 +1,265 @@
+//! Rom containers of a source collection: zip and 7z archives, and plain (uncompressed) folders.
+//! Listing and reading works the same for all of them. Only zips are rebuilt when copied, see
+//! [torrentzip](crate::core::torrentzip), 7z archives and folders are always copied as they are.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZReader};
+use strum_macros::Display;
+
+use crate::core::roms_service::read_dir_at;
+use crate::error::{CuratorError, CuratorResult};
+use crate::models::roms::RomData;
+
+/// Kind of rom container, see [from_path](ContainerKind::from_path).
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerKind {
+    #[strum(serialize = "zip")]
+    Zip,
+    #[strum(serialize = "7z")]
+    SevenZip,
+    #[strum(serialize = "folder")]
+    Folder,
+}
+
+impl ContainerKind {
+    ///
+    /// @return Kind of container at `path`, from its extension, [Folder](ContainerKind::Folder)
+    /// if it's a directory, `None` if it's not a container, for ex, `robocop.txt`.
+    ///
+    pub fn from_path(path: &Path) -> Option<ContainerKind> {
+        if path.is_dir() { return Some(ContainerKind::Folder); }
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("zip") => Some(ContainerKind::Zip),
+            Some(extension) if extension.eq_ignore_ascii_case("7z") => Some(ContainerKind::SevenZip),
+            _ => None,
+        }
+    }
+}
+
+/// A file inside a rom container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerEntry {
+    pub name: String,
+    pub size: u64,
+    pub crc: u32,
+}
+
+impl ContainerEntry {
+    /// @return CRC32 as 8 lowercase hex digits, as in `mame.xml`.
+    pub fn crc_hex(&self) -> String {
+        format!("{:08x}", self.crc)
+    }
+}
+
+///
+/// Lists the files in the container at `path`, sorted by name. Archives are listed from their
+/// headers, folder files, and 7z files without a CRC32 in the headers, are read to compute it.
+///
+/// @return An [Archive](CuratorError::Archive) error if `path` is not a container or can't be read.
+///
+pub fn list_entries(path: &Path) -> CuratorResult<Vec<ContainerEntry>> {
+    let mut entries = match kind_of(path)? {
+        ContainerKind::Zip => {
+            let mut archive = open_zip(path)?;
+            let mut entries: Vec<ContainerEntry> = Vec::new();
+            for index in 0..archive.len() {
+                let file = archive.by_index_raw(index).map_err(|err| archive_error(path, err))?;
+                if file.is_dir() { continue; }
+                entries.push(ContainerEntry { name: file.name().to_string(), size: file.size(), crc: file.crc32() });
+            }
+            entries
+        }
+        ContainerKind::SevenZip => {
+            let reader = open_7z(path)?;
+            let files = &reader.archive().files;
+            if files.iter().all(|file| file.is_directory() || has_7z_crc(file)) {
+                files.iter()
+                    .filter(|file| !file.is_directory())
+                    .map(|file| ContainerEntry { name: file.name().to_string(), size: file.size(), crc: file.crc as u32 })
+                    .collect()
+            } else {
+                let mut entries: Vec<ContainerEntry> = Vec::new();
+                read_entries(path, |entry, _| {
+                    entries.push(entry.clone());
+                    Ok(())
+                })?;
+                entries
+            }
+        }
+        ContainerKind::Folder => {
+            folder_files(path)?.iter().map(|file_path| folder_entry(file_path)).collect::<CuratorResult<_>>()?
+        }
+    };
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+///
+/// Calls `visit` with each file in the container at `path` and a reader of its contents,
+/// in the order they are stored. 7z files without a CRC32 in the headers are read in memory
+/// first to compute it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use roms_curator::core::container::read_entries;
+///
+/// read_entries(Path::new("/roms/robocop.7z"), |entry, reader| {
+///     let mut contents: Vec<u8> = Vec::new();
+///     reader.read_to_end(&mut contents)?;
+///     println!("{} {} bytes", entry.name, contents.len());
+///     Ok(())
+/// })?;
+/// # Ok::<(), roms_curator::error::CuratorError>(())
+/// ```
+///
+pub fn read_entries(
+    path: &Path,
+    mut visit: impl FnMut(&ContainerEntry, &mut dyn Read) -> CuratorResult<()>,
+) -> CuratorResult<()> {
+    match kind_of(path)? {
+        ContainerKind::Zip => {
+            let mut archive = open_zip(path)?;
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index).map_err(|err| archive_error(path, err))?;
+                if file.is_dir() { continue; }
+                let entry = ContainerEntry { name: file.name().to_string(), size: file.size(), crc: file.crc32() };
+                visit(&entry, &mut file)?;
+            }
+            Ok(())
+        }
+        ContainerKind::SevenZip => {
+            let mut reader = open_7z(path)?;
+            // errors of `visit` can't go through the 7z reader, they are kept here
+            let mut visit_error: Option<CuratorError> = None;
+            reader.for_each_entries(|file, contents| {
+                if file.is_directory() { return Ok(true); }
+                let result = if has_7z_crc(file) {
+                    let entry = ContainerEntry { name: file.name().to_string(), size: file.size(), crc: file.crc as u32 };
+                    visit(&entry, contents)
+                } else {
+                    let mut buffer: Vec<u8> = Vec::new();
+                    contents.read_to_end(&mut buffer)?;
+                    let entry = ContainerEntry { name: file.name().to_string(), size: buffer.len() as u64, crc: crc32fast::hash(&buffer) };
+                    visit(&entry, &mut buffer.as_slice())
+                };
+                match result {
+                    Ok(()) => Ok(true),
+                    Err(err) => {
+                        visit_error = Some(err);
+                        Ok(false)
+                    }
+                }
+            }).map_err(|err| archive_error(path, err))?;
+            visit_error.map_or(Ok(()), Err)
+        }
+        ContainerKind::Folder => {
+            for file_path in folder_files(path)? {
+                let entry = folder_entry(&file_path)?;
+                visit(&entry, &mut BufReader::new(File::open(&file_path)?))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+///
+/// Compares the files of a container with the rom files of the machine it belongs to.
+/// Files are matched by CRC32 and size, so renamed files are still found. Roms shared with
+/// the parent or bios, and roms never dumped, are not expected.
+///
+/// @return Problems found, [is_ok](ContentCheck::is_ok) if there are none.
+///
+pub fn check_contents(data: &RomData, entries: &[ContainerEntry]) -> ContentCheck {
+    let mut check = ContentCheck::default();
+
+    data.rom_files.iter()
+        .filter(|file| file.merge.is_none())
+        .for_each(|file| {
+            let Some(crc) = &file.crc else { return; };
+            let found = entries.iter().any(|entry| entry.size == file.size && entry.crc_hex() == *crc);
+            if found { return; }
+
+            if entries.iter().any(|entry| entry.name.eq_ignore_ascii_case(&file.name)) {
+                check.bad.push(file.name.clone());
+            } else {
+                check.missing.push(file.name.clone());
+            }
+        });
+
+    check
+}
+
+/// Result of [check_contents].
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct ContentCheck {
+    /// Roms not in the container.
+    pub missing: Vec<String>,
+    /// Roms in the container with the wrong size or CRC32.
+    pub bad: Vec<String>,
+}
+
+impl ContentCheck {
+    /// @return true if nothing is missing or bad.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.bad.is_empty()
+    }
+}
+
+/// @return Files directly in the folder at `path`, sorted.
+fn folder_files(path: &Path) -> CuratorResult<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for entry in read_dir_at(path)? {
+        let path = entry?.path();
+        if path.is_file() { paths.push(path); }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// @return Entry of a file in a folder, reading it to compute its CRC32.
+fn folder_entry(path: &Path) -> CuratorResult<ContainerEntry> {
+    let name = path.file_name().and_then(|name| name.to_str())
+        .ok_or_else(|| CuratorError::Encoding(path.to_path_buf()))?;
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = crc32fast::Hasher::new();
+    let mut size: u64 = 0;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 { break; }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok(ContainerEntry { name: name.to_string(), size, crc: hasher.finalize() })
+}
+
+/// @return true if the CRC32 of the 7z `file` is in the headers, or it's empty and doesn't need one.
+fn has_7z_crc(file: &SevenZArchiveEntry) -> bool {
+    file.has_crc || !file.has_stream
+}
+
+fn kind_of(path: &Path) -> CuratorResult<ContainerKind> {
+    ContainerKind::from_path(path).ok_or_else(|| archive_error(path, "not a zip, 7z or folder"))
+}
+
+pub(crate) fn open_zip(path: &Path) -> CuratorResult<zip::ZipArchive<BufReader<File>>> {
+    let file = File::open(path)?;
+    zip::ZipArchive::new(BufReader::new(file)).map_err(|err| archive_error(path, err))
+}
+
+fn open_7z(path: &Path) -> CuratorResult<SevenZReader<File>> {
+    SevenZReader::open(path, Password::empty()).map_err(|err| archive_error(path, err))
+}
+
+pub(crate) fn archive_error(path: &Path, err: impl std::fmt::Display) -> CuratorError {
+    CuratorError::Archive(format!("{} ({err})", path.display()))
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use sevenz_rust::{SevenZArchiveEntry, SevenZWriter};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::core::container::{check_contents, ContainerEntry, ContainerKind, ContentCheck, list_entries, read_entries};
+use crate::core::roms_service::parse;
+use crate::core::test_utils::folder;
+use crate::error::CuratorError;
+use crate::read_mame_xml;
+
+static FILES: [(&str, &[u8]); 2] = [("b.bin", b"hello"), ("a.bin", b"abc")];
+
+/// Creates robocop.zip, robocop.7z and a robocop folder with the same [FILES].
+fn create_containers(folder: &Path) -> Vec<PathBuf> {
+    let zip_path = folder.join("robocop.zip");
+    let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+    for (name, contents) in FILES {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap();
+
+    let seven_zip_path = folder.join("robocop.7z");
+    let mut seven_zip = SevenZWriter::create(&seven_zip_path).unwrap();
+    for (name, contents) in FILES {
+        let mut entry = SevenZArchiveEntry::new();
+        entry.name = name.to_string();
+        entry.has_stream = true;
+        seven_zip.push_archive_entry(entry, Some(contents)).unwrap();
+    }
+    seven_zip.finish().unwrap();
+
+    let folder_path = folder.join("robocop");
+    fs::create_dir_all(&folder_path).unwrap();
+    for (name, contents) in FILES {
+        fs::write(folder_path.join(name), contents).unwrap();
+    }
+
+    vec![zip_path, seven_zip_path, folder_path]
+}
+
+/// Writes a 7z with a single file stored as it is, without any CRC32 in the headers, as some tools do.
+fn create_7z_without_crc(path: &Path, name: &str, contents: &[u8]) {
+    let mut file_name: Vec<u8> = name.encode_utf16().chain([0]).flat_map(|unit| unit.to_le_bytes()).collect();
+    file_name.insert(0, 0); // not external
+    let size = contents.len() as u8;
+
+    let mut header: Vec<u8> = vec![
+        0x01, 0x04, // header, main streams info
+        0x06, 0x00, 0x01, 0x09, size, 0x00, // pack info: position 0, 1 stream of `size`, no CRC32
+        0x07, 0x0B, 0x01, 0x00, 0x01, 0x01, 0x00, 0x0C, size, 0x00, // unpack info: 1 folder, copy coder, no CRC32
+        0x08, 0x00, // substreams info, no CRC32
+        0x00, // end of main streams info
+        0x05, 0x01, 0x11, file_name.len() as u8, // files info: 1 file, names
+    ];
+    header.extend(file_name);
+    header.extend([0x00, 0x00]); // end of files info, end of header
+
+    let mut start_header: Vec<u8> = Vec::new();
+    start_header.extend((contents.len() as u64).to_le_bytes());
+    start_header.extend((header.len() as u64).to_le_bytes());
+    start_header.extend(crc32fast::hash(&header).to_le_bytes());
+
+    let mut seven_zip: Vec<u8> = vec![b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C, 0x00, 0x04];
+    seven_zip.extend(crc32fast::hash(&start_header).to_le_bytes());
+    seven_zip.extend(start_header);
+    seven_zip.extend(contents);
+    seven_zip.extend(header);
+    fs::write(path, seven_zip).unwrap();
+}
+
+#[test]
+fn should_tell_container_kinds() {
+    let folder = folder("container", "kinds");
+    let paths = create_containers(&folder);
+
+    assert_eq!(ContainerKind::from_path(&paths[0]), Some(ContainerKind::Zip));
+    assert_eq!(ContainerKind::from_path(&paths[1]), Some(ContainerKind::SevenZip));
+    assert_eq!(ContainerKind::from_path(&paths[2]), Some(ContainerKind::Folder));
+    assert_eq!(ContainerKind::from_path(Path::new("ROBOCOP.7Z")), Some(ContainerKind::SevenZip));
+    assert_eq!(ContainerKind::from_path(Path::new("robocop.txt")), None);
+    assert_eq!(ContainerKind::SevenZip.to_string(), "7z");
+}
+
+#[test]
+fn should_list_and_read_all_containers_the_same() {
+    let folder = folder("container", "list");
+    let expected = vec![
+        ContainerEntry { name: "a.bin".to_string(), size: 3, crc: 0x352441c2 },
+        ContainerEntry { name: "b.bin".to_string(), size: 5, crc: 0x3610a686 },
+    ];
+
+    for path in create_containers(&folder) {
+        assert_eq!(list_entries(&path).unwrap(), expected, "{}", path.display());
+
+        let mut read: Vec<(ContainerEntry, Vec<u8>)> = Vec::new();
+        read_entries(&path, |entry, reader| {
+            let mut contents: Vec<u8> = Vec::new();
+            reader.read_to_end(&mut contents)?;
+            read.push((entry.clone(), contents));
+            Ok(())
+        }).unwrap();
+        read.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+
+        assert_eq!(read, vec![(expected[0].clone(), b"abc".to_vec()), (expected[1].clone(), b"hello".to_vec())]);
+    }
+}
+
+#[test]
+fn should_compute_the_crc_of_7z_files_without_one() {
+    let folder = folder("container", "no_crc");
+    let path = folder.join("robocop.7z");
+    create_7z_without_crc(&path, "b.bin", b"hello");
+    let expected = ContainerEntry { name: "b.bin".to_string(), size: 5, crc: 0x3610a686 };
+
+    assert_eq!(list_entries(&path).unwrap(), vec![expected.clone()]);
+
+    let mut read: Vec<(ContainerEntry, Vec<u8>)> = Vec::new();
+    read_entries(&path, |entry, reader| {
+        let mut contents: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        read.push((entry.clone(), contents));
+        Ok(())
+    }).unwrap();
+    assert_eq!(read, vec![(expected, b"hello".to_vec())]);
+}
+
+#[test]
+fn should_fail_on_unreadable_containers() {
+    let folder = folder("container", "unreadable");
+    let path = folder.join("robocop.7z");
+    fs::write(&path, "not a 7z").unwrap();
+
+    assert!(matches!(list_entries(&path), Err(CuratorError::Archive(_))));
+    assert!(matches!(list_entries(Path::new("robocop.txt")), Err(CuratorError::Archive(_))));
+}
+
+#[test]
+fn should_check_contents_by_crc_and_size() {
+    let contents = r#"<mame><machine name="robocop" romof="bios"><description>Robocop</description>
+        <rom name="a.bin" size="3" crc="352441c2"/>
+        <rom name="b.bin" size="5" crc="3610a686"/>
+        <rom name="bios.bin" merge="bios.bin" size="5" crc="0d7e864e"/>
+        <rom name="undumped.bin" size="5" status="nodump"/>
+    </machine></mame>"#;
+    let roms = parse(read_mame_xml(contents).unwrap(), Default::default()).unwrap();
+    let data = &roms["robocop"];
+
+    let renamed = vec![
+        ContainerEntry { name: "renamed.bin".to_string(), size: 3, crc: 0x352441c2 },
+        ContainerEntry { name: "b.bin".to_string(), size: 5, crc: 0x3610a686 },
+    ];
+    assert!(check_contents(data, &renamed).is_ok());
+
+    let bad = vec![ContainerEntry { name: "B.BIN".to_string(), size: 5, crc: 0x0d7e864e }];
+    assert_eq!(check_contents(data, &bad), ContentCheck { missing: vec!["a.bin".to_string()], bad: vec!["b.bin".to_string()] });
+}
@@ -0,0 +1,234 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::core::feature_rules::FeatureRules;
+use crate::core::filter::Filter;
+use crate::core::identify::UnknownFilesAction;
+use crate::core::progress::{NoProgress, Progress};
+use crate::core::roms_service::{parse, UnfilteredRomsExt};
+use crate::core::scanner::ScanOptions;
+use crate::error::{CuratorError, CuratorResult};
+use crate::models::destination_folders::DestinationFolders;
+use crate::models::roms::{Roms, UnknownValuePolicy};
+use crate::{parse_category_list, read_mame_xml, RomCategories};
+
+/// Where to read a MAME file from.
+enum Input {
+    Path(PathBuf),
+    Reader(Box<dyn Read>),
+}
+
+impl Input {
+    fn describe(&self) -> String {
+        match self {
+            Input::Path(path) => path.display().to_string(),
+            Input::Reader(_) => "reader".to_string(),
+        }
+    }
+
+    fn read_to_string(self) -> CuratorResult<String> {
+        match self {
+            Input::Path(path) => fs::read_to_string(&path)
+                .map_err(|err| CuratorError::path(path, &format!("Error reading file ({err})"))),
+            Input::Reader(mut reader) => {
+                let mut contents = String::new();
+                reader.read_to_string(&mut contents)?;
+                Ok(contents)
+            }
+        }
+    }
+}
+
+///
+/// Categorizes a MAME ROM database, built with [Curator::builder].
+///
+/// # Examples
+///
+/// ```no_run
+/// use roms_curator::core::curator::Curator;
+/// use roms_curator::core::progress::TerminalProgress;
+///
+/// let roms = Curator::builder()
+///     .mame_xml_path("/mame/mame.xml")
+///     .catver_path("/mame/catver.ini")
+///     .progress(TerminalProgress::default())
+///     .build()?
+///     .categorize()?;
+/// # Ok::<(), roms_curator::error::CuratorError>(())
+/// ```
+///
+pub struct Curator {
+    mame_xml: Input,
+    catver: Option<Input>,
+    options: CategorizeOptions,
+    progress: Box<dyn Progress>,
+}
+
+/// Builder of [Curator], only the MAME ROM database is mandatory.
+#[derive(Default)]
+pub struct CuratorBuilder {
+    mame_xml: Option<Input>,
+    catver: Option<Input>,
+    options: CategorizeOptions,
+    progress: Option<Box<dyn Progress>>,
+}
+
+impl CuratorBuilder {
+    /// MAME ROM database file, for ex, `mame.xml` from `mame -listxml`.
+    pub fn mame_xml_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.mame_xml = Some(Input::Path(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// MAME ROM database read from `reader` instead of a file.
+    pub fn mame_xml_reader(mut self, reader: impl Read + 'static) -> Self {
+        self.mame_xml = Some(Input::Reader(Box::new(reader)));
+        self
+    }
+
+    /// MAME support file, optional, without it roms have no category (genre).
+    pub fn catver_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.catver = Some(Input::Path(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// MAME support file read from `reader` instead of a file.
+    pub fn catver_reader(mut self, reader: impl Read + 'static) -> Self {
+        self.catver = Some(Input::Reader(Box::new(reader)));
+        self
+    }
+
+    /// How to categorize machines with values unknown to this version, [NotWorking](UnknownValuePolicy::NotWorking) if not set.
+    pub fn unknown_value_policy(mut self, policy: UnknownValuePolicy) -> Self {
+        self.options.unknown_value_policy = policy;
+        self
+    }
+
+    /// Which features make a machine not working, [FeatureRules::default] if not set.
+    pub fn feature_rules(mut self, rules: FeatureRules) -> Self {
+        self.options.feature_rules = rules;
+        self
+    }
+
+    /// Receives the categorization progress, [NoProgress] if not set.
+    pub fn progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    pub fn build(self) -> CuratorResult<Curator> {
+        let mame_xml = self.mame_xml.ok_or_else(|| CuratorError::path("mame.xml", "Missing MAME ROM database"))?;
+        Ok(Curator {
+            mame_xml,
+            catver: self.catver,
+            options: self.options,
+            progress: self.progress.unwrap_or_else(|| Box::new(NoProgress)),
+        })
+    }
+}
+
+impl Curator {
+    pub fn builder() -> CuratorBuilder {
+        CuratorBuilder::default()
+    }
+
+    ///
+    /// Reads the MAME ROM database and support file and categorizes all roms.
+    ///
+    /// @return A `HashMap<String, Rom>` collection with all ROMs categorized.
+    ///
+    pub fn categorize(self) -> CuratorResult<Roms> {
+        let progress = self.progress;
+        progress.start(3);
+
+        progress.message("* Reading mame database and copying files can last a few minutes, please be patient. *");
+
+        let rom_categories = match self.catver {
+            Some(catver) => {
+                progress.message(format!("Reading {} document...", catver.describe()).as_str());
+                parse_category_list(&catver.read_to_string()?)?
+            }
+            None => RomCategories::new(),
+        };
+        progress.inc();
+
+        progress.message(format!("Reading {} document...", self.mame_xml.describe()).as_str());
+        let contents = self.mame_xml.read_to_string()?;
+        let doc = read_mame_xml(&contents)?;
+        progress.inc();
+
+        progress.message("Categorizing roms...");
+        let unfiltered_roms = parse(doc, rom_categories)?;
+        let roms = unfiltered_roms.categorize_roms_with(&self.options)?;
+        progress.inc();
+
+        progress.finish();
+
+        Ok(roms)
+    }
+}
+
+///
+/// Options of [categorize_roms_with](crate::core::roms_service::UnfilteredRomsExt::categorize_roms_with).
+///
+#[derive(Default, Debug, Clone)]
+pub struct CategorizeOptions {
+    /// How to categorize machines with values unknown to this version.
+    pub unknown_value_policy: UnknownValuePolicy,
+    /// Which imperfect or unemulated features make a machine not working.
+    pub feature_rules: FeatureRules,
+}
+
+///
+/// Options of [copy_roms](crate::core::roms_service::RomsExt::copy_roms) and
+/// [upgrade_roms](crate::core::roms_service::RomsExt::upgrade_roms).
+///
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    /// Where the original ROM collection is. Can be more than one directory.
+    pub source_paths: Vec<PathBuf>,
+    /// Where to copy the roms, see [DestinationFolders].
+    pub destination_path: PathBuf,
+    /// If true, not-working CHD ROMs and Directories will not be copied.
+    pub ignore_not_working_chd: bool,
+    /// If true, no ROMs will be copied, but the report will still be generated as if they were.
+    pub simulation: bool,
+    /// If set, only roms which ascii name alphabetical order is higher than this will be copied.
+    pub subset_start: Option<String>,
+    /// If set, only roms which ascii name alphabetical order is lower than this will be copied.
+    pub subset_end: Option<String>,
+    /// If set, only roms matching this expression will be copied.
+    pub filter: Option<Filter>,
+    /// How the source paths are scanned for roms, for ex, how many levels of sub-directories.
+    pub scan: ScanOptions,
+    /// What to do with source files that don't match any machine, see [DestinationFolders::unknown].
+    pub unknown_files: UnknownFilesAction,
+    /// If true, unknown files identified as a machine are renamed after it in the `unknown` folder.
+    pub rename_identified: bool,
+    /// If true, copied zip files are rewritten in deterministic, TorrentZip-style form, see [torrentzip](crate::core::torrentzip).
+    pub torrentzip: bool,
+}
+
+impl CopyOptions {
+    pub fn new(source_paths: Vec<PathBuf>, destination_path: PathBuf) -> CopyOptions {
+        CopyOptions {
+            source_paths,
+            destination_path,
+            ignore_not_working_chd: false,
+            simulation: false,
+            subset_start: None,
+            subset_end: None,
+            filter: None,
+            scan: ScanOptions::default(),
+            unknown_files: UnknownFilesAction::Keep,
+            rename_identified: false,
+            torrentzip: false,
+        }
+    }
+
+    /// Creates, if needed, and returns the destination folders.
+    pub fn destination_folders(&self) -> CuratorResult<DestinationFolders> {
+        DestinationFolders::new(&self.destination_path)
+    }
+}
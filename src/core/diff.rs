@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::models::diff::{RomChange, RomsDiff};
+use crate::models::roms::{Rom, Roms};
+
+///
+/// Compares two categorized rom collections, usually built from two
+/// different MAME releases with [load_roms](crate::load_roms).
+///
+/// A machine removed from `old` and added to `new` with the same description
+/// is considered renamed, as long as no other machine shares that description.
+///
+/// # Examples
+///
+/// ```no_run
+/// use roms_curator::core::diff::diff_roms;
+///
+/// let old = roms_curator::load_roms("mame0244.xml", "catver0244.ini").unwrap();
+/// let new = roms_curator::load_roms("mame0245.xml", "catver0245.ini").unwrap();
+///
+/// let diff = diff_roms(&old, &new);
+/// diff.to_file("diff.md").unwrap();
+/// ```
+///
+/// @return A [RomsDiff] with all changes sorted by name.
+///
+pub fn diff_roms(old: &Roms, new: &Roms) -> RomsDiff {
+    let mut changed: Vec<RomChange> = new.iter()
+        .filter_map(|(name, new_rom)| {
+            let old_rom = old.get(name)?;
+            if old_rom.category == new_rom.category { return None; }
+            Some(RomChange {
+                name: name.clone(),
+                description: new_rom.data.description.clone(),
+                old_name: None,
+                from: Some(old_rom.category),
+                to: Some(new_rom.category),
+                reason: new_rom.reason.to_string(),
+            })
+        })
+        .collect();
+
+    let removed: Vec<(&String, &Rom)> = old.iter().filter(|(name, _)| !new.contains_key(*name)).collect();
+    let added: Vec<(&String, &Rom)> = new.iter().filter(|(name, _)| !old.contains_key(*name)).collect();
+
+    let removed_by_description = unique_by_description(&removed);
+    let added_by_description = unique_by_description(&added);
+
+    let mut renamed: Vec<RomChange> = Vec::new();
+    let mut renamed_names: Vec<&String> = Vec::new();
+    for (description, (old_name, old_rom)) in &removed_by_description {
+        if let Some((new_name, new_rom)) = added_by_description.get(description) {
+            renamed.push(RomChange {
+                name: new_name.to_string(),
+                description: new_rom.data.description.clone(),
+                old_name: Some(old_name.to_string()),
+                from: Some(old_rom.category),
+                to: Some(new_rom.category),
+                reason: new_rom.reason.to_string(),
+            });
+            renamed_names.push(old_name);
+            renamed_names.push(new_name);
+        }
+    }
+
+    let to_change = |(name, rom): &(&String, &Rom), is_added: bool| RomChange {
+        name: name.to_string(),
+        description: rom.data.description.clone(),
+        old_name: None,
+        from: if is_added { None } else { Some(rom.category) },
+        to: if is_added { Some(rom.category) } else { None },
+        reason: rom.reason.to_string(),
+    };
+
+    let mut added: Vec<RomChange> = added.iter()
+        .filter(|(name, _)| !renamed_names.contains(name))
+        .map(|entry| to_change(entry, true))
+        .collect();
+    let mut removed: Vec<RomChange> = removed.iter()
+        .filter(|(name, _)| !renamed_names.contains(name))
+        .map(|entry| to_change(entry, false))
+        .collect();
+
+    for changes in [&mut added, &mut removed, &mut renamed, &mut changed] {
+        changes.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    RomsDiff { added, removed, renamed, changed }
+}
+
+///
+/// @return Roms indexed by lowercase description, leaving out
+/// descriptions shared by more than one rom.
+///
+fn unique_by_description<'a>(roms: &[(&'a String, &'a Rom)]) -> HashMap<String, (&'a String, &'a Rom)> {
+    let mut by_description: HashMap<String, Option<(&String, &Rom)>> = HashMap::new();
+    roms.iter()
+        .filter(|(_, rom)| !rom.data.description.trim().is_empty())
+        .for_each(|(name, rom)| {
+            by_description.entry(rom.data.description.trim().to_lowercase())
+                .and_modify(|entry| *entry = None)
+                .or_insert(Some((name, rom)));
+        });
+
+    by_description.into_iter()
+        .filter_map(|(description, entry)| entry.map(|entry| (description, entry)))
+        .collect()
+}
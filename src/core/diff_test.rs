@@ -0,0 +1,46 @@
+use crate::core::diff::diff_roms;
+use crate::load_roms;
+use crate::models::diff::RomChange;
+use crate::models::roms::RomCategory;
+
+fn names<'a>(changes: &[&'a RomChange]) -> Vec<&'a str> {
+    changes.iter().map(|change| change.name.as_str()).collect()
+}
+
+#[test]
+fn should_diff_two_mame_releases() {
+    let old = load_roms("tests/resources/listxml_0244.xml", "tests/resources/catver_0244.ini").unwrap();
+    let new = load_roms("tests/resources/listxml_0245.xml", "tests/resources/catver_0244.ini").unwrap();
+
+    let diff = diff_roms(&old, &new);
+
+    assert_eq!(names(&diff.became_working()), vec!["100lions"]);
+    assert_eq!(names(&diff.regressed()), vec!["robocop"]);
+    assert_eq!(diff.regressed()[0].reason, "preliminary driver");
+
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].name, "005a");
+    assert_eq!(diff.added[0].from, None);
+    assert_eq!(diff.added[0].to, Some(RomCategory::Working));
+
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].name, "sv801");
+    assert_eq!(diff.removed[0].from, Some(RomCategory::System));
+
+    assert_eq!(diff.renamed.len(), 1);
+    assert_eq!(diff.renamed[0].name, "elevatorb");
+    assert_eq!(diff.renamed[0].old_name, Some("elevatora".to_string()));
+
+    assert_eq!(diff.transitions().len(), 2);
+}
+
+#[test]
+fn should_have_no_differences_for_same_release() {
+    let old = load_roms("tests/resources/listxml_0244.xml", "tests/resources/catver_0244.ini").unwrap();
+    let new = load_roms("tests/resources/listxml_0244.xml", "").unwrap();
+
+    let diff = diff_roms(&old, &new);
+
+    assert!(diff.is_empty());
+    assert!(diff.to_markdown().contains("- Added: 0"));
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::core::export::{copied_roms_by_folder, CopiedRom};
+use crate::error::CuratorResult;
+use crate::models::destination_folders::DestinationFolders;
+use crate::models::report::Report;
+use crate::models::roms::{Control, Roms};
+
+static ROMLIST_HEADER: &str = "#Name;Title;Emulator;CloneOf;Year;Manufacturer;Category;Players;Rotation;Control;Status;DisplayCount;DisplayType;AltRomname;AltTitle;Extra;Buttons";
+
+/// Options for [write_romlists].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttractModeOptions {
+    /// Directory where the romlists are written, usually Attract-Mode `romlists` directory.
+    pub romlist_dir: PathBuf,
+    /// Attract-Mode emulator name used to launch the games, also used as prefix
+    /// of each romlist file name, for ex, `mame` for `mame - working.txt`.
+    pub emulator: String,
+}
+
+impl AttractModeOptions {
+    pub fn new(romlist_dir: PathBuf) -> AttractModeOptions {
+        AttractModeOptions { romlist_dir, emulator: "mame".to_string() }
+    }
+}
+
+///
+/// Writes an Attract-Mode romlist for each destination folder with roms
+/// copied to it according to the [Report](Report).
+///
+/// @return Paths of all written romlists.
+///
+pub fn write_romlists(
+    roms: &Roms,
+    report: &Report,
+    destination_folders: &DestinationFolders,
+    options: &AttractModeOptions,
+) -> CuratorResult<Vec<PathBuf>> {
+    fs::create_dir_all(&options.romlist_dir)?;
+    let mut written = Vec::new();
+
+    for (folder, copied) in copied_roms_by_folder(roms, report, destination_folders) {
+        let folder_name = folder.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let path = options.romlist_dir.join(format!("{} - {}.txt", options.emulator, folder_name));
+        write_romlist(&path, &copied, &options.emulator)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+fn write_romlist(path: &Path, copied: &[CopiedRom], emulator: &str) -> CuratorResult<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writer.write_all(ROMLIST_HEADER.as_bytes())?;
+    writer.write_all(b"\n")?;
+
+    for entry in copied {
+        writer.write_all(romlist_line(entry, emulator).as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn romlist_line(entry: &CopiedRom, emulator: &str) -> String {
+    let data = &entry.rom.data;
+
+    let title = if data.description.is_empty() { &entry.name } else { &data.description };
+    let status = data.status.as_ref()
+        .map(|status| status.driver.to_string().to_ascii_lowercase())
+        .unwrap_or_default();
+    let control = data.input.as_ref()
+        .and_then(|input| input.controls.first())
+        .map(control_name)
+        .unwrap_or_default();
+    let display_type = data.display.first()
+        .map(|display| display.typ.clone())
+        .unwrap_or_default();
+    let buttons = if data.buttons() > 0 { data.buttons().to_string() } else { String::new() };
+
+    let fields = [
+        entry.name.as_str(),
+        title,
+        emulator,
+        data.clone_of.as_deref().unwrap_or_default(),
+        data.year.as_str(),
+        data.manufacturer.as_str(),
+        data.category.as_str(),
+        &data.players().to_string(),
+        &data.rotation().to_string(),
+        &control,
+        &status,
+        &data.display.len().to_string(),
+        &display_type,
+        "",
+        "",
+        "",
+        &buttons,
+    ];
+
+    fields.iter()
+        .map(|field| field.replace([';', '\n', '\r'], " "))
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+/// Attract-Mode control names as imported from MAME, for ex, `joystick (8-way)`.
+fn control_name(control: &Control) -> String {
+    match (control.typ.as_str(), &control.ways) {
+        ("joy", Some(ways)) => format!("joystick ({ways}-way)"),
+        ("joy", None) => "joystick".to_string(),
+        ("doublejoy", Some(ways)) => format!("double joystick ({ways}-way)"),
+        (typ, _) => typ.to_string(),
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use strum::IntoEnumIterator;
+
+use crate::error::CuratorResult;
+use crate::models::roms::{RomCategory, Roms};
+
+pub static FOLDER_INI_FILE_NAME: &str = "roms_curator.ini";
+pub static WORKING_FOLDER_INI_FILE_NAME: &str = "roms_curator_working.ini";
+
+///
+/// Writes the categorization as MAME / MAMEUI folder filter INI files
+/// (same format as the ones from progettosnaps) to `folder_ini_dir`:
+/// - `roms_curator.ini`: one sub-folder (section) per [RomCategory](RomCategory).
+/// - `roms_curator_working.ini`: only working roms.
+///
+/// This needs only the categorized roms, nothing has to be copied.
+///
+/// @return Paths of all written INI files.
+///
+pub fn write_folder_inis(roms: &Roms, folder_ini_dir: &Path) -> CuratorResult<Vec<PathBuf>> {
+    fs::create_dir_all(folder_ini_dir)?;
+
+    let path = folder_ini_dir.join(FOLDER_INI_FILE_NAME);
+    let mut writer = start_folder_ini(&path, "List of machines by roms-curator category")?;
+    for category in RomCategory::iter() {
+        let names = names_in_category(roms, &category);
+        if names.is_empty() { continue; }
+
+        write_line(&mut writer, &format!("[{category}]"))?;
+        for name in names {
+            write_line(&mut writer, name)?;
+        }
+        write_line(&mut writer, "")?;
+    }
+    writer.flush()?;
+
+    let working_path = folder_ini_dir.join(WORKING_FOLDER_INI_FILE_NAME);
+    let mut writer = start_folder_ini(&working_path, "List of working machines by roms-curator")?;
+    for name in names_in_category(roms, &RomCategory::Working) {
+        write_line(&mut writer, name)?;
+    }
+    writer.flush()?;
+
+    Ok(vec![path, working_path])
+}
+
+/// Writes the header up until, and including, `[ROOT_FOLDER]`.
+fn start_folder_ini(path: &Path, description: &str) -> CuratorResult<BufWriter<File>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let date = chrono::offset::Local::now().format("%d-%b-%y");
+
+    write_line(&mut writer, "[FOLDER_SETTINGS]")?;
+    write_line(&mut writer, "RootFolderIcon mame")?;
+    write_line(&mut writer, "SubFolderIcon folder")?;
+    write_line(&mut writer, "")?;
+    write_line(&mut writer, &format!(";; {file_name} / roms-curator {} / {date} ;;", env!("CARGO_PKG_VERSION")))?;
+    write_line(&mut writer, &format!(";; {description} ;;"))?;
+    write_line(&mut writer, "")?;
+    write_line(&mut writer, "[ROOT_FOLDER]")?;
+
+    Ok(writer)
+}
+
+fn names_in_category<'a>(roms: &'a Roms, category: &RomCategory) -> Vec<&'a String> {
+    let mut names: Vec<&String> = roms.iter()
+        .filter(|(_, rom)| &rom.category == category)
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names
+}
+
+/// Folder INI files use windows line endings.
+fn write_line(writer: &mut impl Write, line: &str) -> CuratorResult<()> {
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\r\n")?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::core::export::{copied_roms_by_folder, CopiedRom};
+use crate::error::CuratorResult;
+use crate::models::destination_folders::DestinationFolders;
+use crate::models::report::Report;
+use crate::models::roms::Roms;
+use crate::utils::escape_xml;
+
+pub static GAMELIST_FILE_NAME: &str = "gamelist.xml";
+
+///
+/// Writes an EmulationStation / ES-DE `gamelist.xml` to each destination folder
+/// with roms copied to it according to the [Report](Report).
+/// An existing `gamelist.xml` is replaced.
+///
+/// @return Paths of all written gamelists.
+///
+pub fn write_gamelists(
+    roms: &Roms,
+    report: &Report,
+    destination_folders: &DestinationFolders,
+) -> CuratorResult<Vec<PathBuf>> {
+    let mut written = Vec::new();
+
+    for (folder, copied) in copied_roms_by_folder(roms, report, destination_folders) {
+        let path = folder.join(GAMELIST_FILE_NAME);
+        write_gamelist(&path, &copied)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+fn write_gamelist(path: &Path, copied: &[CopiedRom]) -> CuratorResult<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writer.write_all(b"<?xml version=\"1.0\"?>\n<gameList>\n")?;
+
+    for entry in copied {
+        let data = &entry.rom.data;
+        let title = if data.description.is_empty() { &entry.name } else { &data.description };
+
+        writer.write_all(b"\t<game>\n")?;
+        write_element(&mut writer, "path", &format!("./{}", entry.file_name))?;
+        write_element(&mut writer, "name", title)?;
+        if data.year.len() == 4 && data.year.chars().all(|c| c.is_ascii_digit()) {
+            write_element(&mut writer, "releasedate", &format!("{}0101T000000", data.year))?;
+        }
+        write_element(&mut writer, "developer", &data.manufacturer)?;
+        write_element(&mut writer, "publisher", &data.manufacturer)?;
+        write_element(&mut writer, "genre", &data.category)?;
+        if data.players() > 0 {
+            write_element(&mut writer, "players", &data.players().to_string())?;
+        }
+        writer.write_all(b"\t</game>\n")?;
+    }
+
+    writer.write_all(b"</gameList>\n")?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn write_element(writer: &mut impl Write, tag: &str, value: &str) -> CuratorResult<()> {
+    if value.is_empty() { return Ok(()); }
+    writer.write_all(format!("\t\t<{tag}>{}</{tag}>\n", escape_xml(value)).as_bytes())?;
+    Ok(())
+}
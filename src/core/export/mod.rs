@@ -0,0 +1,101 @@
+//! Exporters that write frontend specific files (game lists, playlists, etc.)
+//! from a categorized [Roms](crate::models::roms::Roms) collection.
+
+use std::collections::BTreeMap;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+use crate::core::roms_service::RomsExt;
+use crate::core::scanner::rom_name;
+use crate::error::CuratorResult;
+use crate::models::destination_folders::DestinationFolders;
+use crate::models::report::{Report, ReportDetailEntry};
+use crate::models::roms::RomCategory;
+use crate::models::roms::{Rom, Roms};
+
+pub mod attract_mode;
+pub mod folder_ini;
+pub mod gamelist;
+pub mod retroarch;
+pub mod sqlite;
+
+/// A rom file successfully copied to one of the destination folders.
+pub struct CopiedRom<'a> {
+    /// Rom name, as in `mame.xml`.
+    pub name: String,
+    pub rom: &'a Rom,
+    /// File name in the destination folder, for ex, `robocop.zip`.
+    pub file_name: String,
+}
+
+///
+/// Groups all roms moved according to the [Report](Report) by the destination folder
+/// they were copied to. CHD directories are not included since they are not games
+/// by themselves, only the rom that depends on them. Roms stored as folders are.
+///
+/// @return Copied roms by destination folder, sorted by rom name.
+///
+pub fn copied_roms_by_folder<'a>(
+    roms: &'a Roms,
+    report: &Report,
+    destination_folders: &DestinationFolders,
+) -> BTreeMap<PathBuf, Vec<CopiedRom<'a>>> {
+    let mut by_folder: BTreeMap<PathBuf, Vec<CopiedRom>> = BTreeMap::new();
+
+    report.roms_working.iter()
+        .chain(report.roms_other.iter())
+        .filter(|entry| entry.moved)
+        .for_each(|entry| {
+            let Ok(name) = rom_name(Path::new(&entry.rom_name)) else { return; };
+
+            if let Some(rom) = roms.get(&name) {
+                let folder = Roms::get_destination_folder(rom, destination_folders);
+                if !rom.data.chd.is_empty() && folder.join(&entry.rom_name).is_dir() { return; }
+
+                by_folder.entry(folder).or_default().push(CopiedRom {
+                    name,
+                    rom,
+                    file_name: entry.rom_name.clone(),
+                });
+            }
+        });
+
+    by_folder.values_mut().for_each(|copied| copied.sort_by(|a, b| a.name.cmp(&b.name)));
+
+    by_folder
+}
+
+///
+/// Builds a [Report](Report) of the roms already in their destination folder, for ex,
+/// from a previous copy, so files can be exported without copying anything.
+/// Roms not in `mame.xml` or not in the folder of their category are left out.
+///
+pub fn destination_report(roms: &Roms, destination_folders: &DestinationFolders) -> CuratorResult<Report> {
+    let mut report = Report::new();
+
+    for folder in [&destination_folders.working, &destination_folders.other, &destination_folders.chd_working, &destination_folders.chd_other] {
+        for entry in read_dir(folder)? {
+            let path = entry?.path();
+            let Ok(name) = rom_name(&path) else { continue; };
+            let Some(rom) = roms.get(&name) else { continue; };
+            if Roms::get_destination_folder(rom, destination_folders) != *folder { continue; }
+
+            let entry = ReportDetailEntry {
+                rom_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                moved: true,
+                is_chd: !rom.data.chd.is_empty(),
+                source_path: path.to_string_lossy().to_string(),
+                destination_path: path.to_string_lossy().to_string(),
+                category: rom.category.to_string(),
+                reason: rom.reason.to_string(),
+                ..Default::default()
+            };
+            match rom.category {
+                RomCategory::Working => report.add_rom_working(entry),
+                _ => report.add_rom_other(entry),
+            };
+        }
+    }
+
+    Ok(report)
+}
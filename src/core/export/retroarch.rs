@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{absolute, PathBuf};
+
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+
+use crate::core::export::{copied_roms_by_folder, CopiedRom};
+use crate::error::CuratorResult;
+use crate::models::destination_folders::DestinationFolders;
+use crate::models::report::Report;
+use crate::models::roms::Roms;
+
+/// Core path/name value that lets RetroArch pick the core when the game is launched.
+pub static DETECT: &str = "DETECT";
+
+/// How copied roms are split into playlists.
+#[derive(Display, Debug, Default, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum PlaylistGrouping {
+    /// One playlist per destination folder (`working`, `other`, `chd_working`, `chd_other`).
+    #[default]
+    Folder,
+    /// One playlist per [RomCategory](crate::models::roms::RomCategory).
+    Category,
+}
+
+/// Options for [write_playlists].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetroArchOptions {
+    /// Directory where the playlists are written, usually RetroArch `playlists` directory.
+    pub playlist_dir: PathBuf,
+    /// Prefix of each playlist file name and database name used by RetroArch
+    /// to find thumbnails, for ex, `MAME` for `MAME - working.lpl`.
+    pub playlist_name: String,
+    /// Path of the core used to run the games, [DETECT] to choose on launch.
+    pub core_path: String,
+    /// Name of the core used to run the games, [DETECT] to choose on launch.
+    pub core_name: String,
+    pub grouping: PlaylistGrouping,
+}
+
+impl RetroArchOptions {
+    pub fn new(playlist_dir: PathBuf) -> RetroArchOptions {
+        RetroArchOptions {
+            playlist_dir,
+            playlist_name: "MAME".to_string(),
+            core_path: DETECT.to_string(),
+            core_name: DETECT.to_string(),
+            grouping: PlaylistGrouping::Folder,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Playlist<'a> {
+    version: &'a str,
+    default_core_path: &'a str,
+    default_core_name: &'a str,
+    label_display_mode: u8,
+    right_thumbnail_mode: u8,
+    left_thumbnail_mode: u8,
+    sort_mode: u8,
+    items: Vec<PlaylistItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct PlaylistItem<'a> {
+    path: String,
+    label: &'a str,
+    core_path: &'a str,
+    core_name: &'a str,
+    crc32: &'a str,
+    db_name: &'a str,
+}
+
+///
+/// Writes RetroArch JSON playlists (`.lpl`) for all roms copied
+/// according to the [Report](Report), grouped as set in [RetroArchOptions].
+///
+/// @return Paths of all written playlists.
+///
+pub fn write_playlists(
+    roms: &Roms,
+    report: &Report,
+    destination_folders: &DestinationFolders,
+    options: &RetroArchOptions,
+) -> CuratorResult<Vec<PathBuf>> {
+    let mut playlists: BTreeMap<String, Vec<(PathBuf, CopiedRom)>> = BTreeMap::new();
+
+    for (folder, copied) in copied_roms_by_folder(roms, report, destination_folders) {
+        let folder_name = folder.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        copied.into_iter().for_each(|entry| {
+            let group = match options.grouping {
+                PlaylistGrouping::Folder => folder_name.clone(),
+                PlaylistGrouping::Category => entry.rom.category.to_string(),
+            };
+            playlists.entry(group).or_default().push((folder.clone(), entry));
+        });
+    }
+
+    fs::create_dir_all(&options.playlist_dir)?;
+    let db_name = format!("{}.lpl", options.playlist_name);
+    let mut written = Vec::new();
+
+    for (group, mut entries) in playlists {
+        entries.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+
+        let mut items = Vec::new();
+        for (folder, entry) in &entries {
+            let data = &entry.rom.data;
+            items.push(PlaylistItem {
+                path: absolute(folder.join(&entry.file_name))?.to_string_lossy().to_string(),
+                label: if data.description.is_empty() { &entry.name } else { &data.description },
+                core_path: &options.core_path,
+                core_name: &options.core_name,
+                crc32: DETECT,
+                db_name: &db_name,
+            });
+        }
+
+        // RetroArch leaves the default core empty when it should be detected
+        let (default_core_path, default_core_name) = if options.core_path == DETECT {
+            ("", "")
+        } else {
+            (options.core_path.as_str(), options.core_name.as_str())
+        };
+
+        let playlist = Playlist {
+            version: "1.5",
+            default_core_path,
+            default_core_name,
+            label_display_mode: 0,
+            right_thumbnail_mode: 0,
+            left_thumbnail_mode: 0,
+            sort_mode: 0,
+            items,
+        };
+
+        let path = options.playlist_dir.join(format!("{} - {}.lpl", options.playlist_name, group));
+        serde_json::to_writer_pretty(BufWriter::new(File::create(&path)?), &playlist)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
@@ -0,0 +1,154 @@
+//! Categorized roms as a SQLite database, to be queried with SQL by other tools.
+
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{Connection, params};
+
+use crate::core::info::RomsInfoExt;
+use crate::error::CuratorResult;
+use crate::models::roms::Roms;
+
+/// Tables, indices and views created by [write_sqlite].
+static SCHEMA: &str = "
+CREATE TABLE genres (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE machines (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    year TEXT NOT NULL,
+    manufacturer TEXT NOT NULL,
+    source_file TEXT NOT NULL,
+    parent TEXT,
+    rom_of TEXT,
+    genre_id INTEGER REFERENCES genres(id),
+    category TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    driver_status TEXT,
+    emulation_status TEXT,
+    is_bios INTEGER NOT NULL,
+    is_system INTEGER NOT NULL,
+    is_mechanical INTEGER NOT NULL,
+    players INTEGER NOT NULL,
+    buttons INTEGER NOT NULL,
+    rotation INTEGER NOT NULL
+);
+CREATE TABLE features (
+    machine TEXT NOT NULL REFERENCES machines(name),
+    type TEXT NOT NULL,
+    status TEXT NOT NULL
+);
+CREATE TABLE disks (
+    machine TEXT NOT NULL REFERENCES machines(name),
+    name TEXT NOT NULL,
+    status TEXT NOT NULL
+);
+CREATE TABLE dependencies (
+    machine TEXT NOT NULL REFERENCES machines(name),
+    dependency TEXT NOT NULL,
+    depth INTEGER NOT NULL
+);
+CREATE INDEX idx_machines_parent ON machines(parent);
+CREATE INDEX idx_machines_category ON machines(category);
+CREATE INDEX idx_machines_genre ON machines(genre_id);
+CREATE INDEX idx_features_machine ON features(machine);
+CREATE INDEX idx_disks_machine ON disks(machine);
+CREATE INDEX idx_dependencies_machine ON dependencies(machine);
+CREATE INDEX idx_dependencies_dependency ON dependencies(dependency);
+CREATE VIEW machines_with_genre AS
+    SELECT machines.*, genres.name AS genre FROM machines LEFT JOIN genres ON genres.id = machines.genre_id;
+";
+
+///
+/// Writes all categorized roms to a new SQLite database at `path`, replacing it if it exists.
+///
+/// ## Tables
+/// - `machines`: one row per machine, with its category, reason, driver and emulation status.
+///   `name` is the primary key, `parent` and `category` are indexed.
+/// - `genres`: catver categories, referenced by `machines.genre_id`.
+/// - `features`: imperfect or unemulated features of each machine.
+/// - `disks`: CHDs of each machine.
+/// - `dependencies`: full `romof` chain of each machine, `depth` 1 being the direct one.
+///
+/// The `machines_with_genre` view joins machines with their genre name.
+///
+/// # Examples
+///
+/// ```sql
+/// SELECT genre, COUNT(*) FROM machines_with_genre WHERE category = 'Working' GROUP BY genre;
+/// ```
+///
+pub fn write_sqlite(roms: &Roms, path: &Path) -> CuratorResult<()> {
+    if path.exists() { fs::remove_file(path)?; }
+
+    let mut connection = Connection::open(path)?;
+    connection.execute_batch(SCHEMA)?;
+
+    let transaction = connection.transaction()?;
+    {
+        let mut insert_genre = transaction.prepare("INSERT OR IGNORE INTO genres (name) VALUES (?1)")?;
+        let mut select_genre = transaction.prepare("SELECT id FROM genres WHERE name = ?1")?;
+        let mut insert_machine = transaction.prepare(
+            "INSERT INTO machines (name, description, year, manufacturer, source_file, parent, rom_of, genre_id, \
+             category, reason, driver_status, emulation_status, is_bios, is_system, is_mechanical, players, buttons, rotation) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)"
+        )?;
+        let mut insert_feature = transaction.prepare("INSERT INTO features (machine, type, status) VALUES (?1, ?2, ?3)")?;
+        let mut insert_disk = transaction.prepare("INSERT INTO disks (machine, name, status) VALUES (?1, ?2, ?3)")?;
+        let mut insert_dependency = transaction.prepare("INSERT INTO dependencies (machine, dependency, depth) VALUES (?1, ?2, ?3)")?;
+
+        let mut names: Vec<&String> = roms.keys().collect();
+        names.sort();
+
+        for name in names {
+            let rom = &roms[name];
+            let data = &rom.data;
+
+            let genre_id: Option<i64> = if data.category.is_empty() {
+                None
+            } else {
+                insert_genre.execute(params![data.category])?;
+                Some(select_genre.query_row(params![data.category], |row| row.get(0))?)
+            };
+
+            insert_machine.execute(params![
+                name,
+                data.description,
+                data.year,
+                data.manufacturer,
+                data.source_file,
+                data.clone_of,
+                data.rom_of,
+                genre_id,
+                rom.category.to_string(),
+                rom.reason.to_string(),
+                data.status.as_ref().map(|status| status.driver.to_string()),
+                data.status.as_ref().map(|status| status.emulation.to_string()),
+                data.is_bios,
+                data.is_system,
+                data.is_mechanical,
+                data.players(),
+                data.buttons(),
+                data.rotation(),
+            ])?;
+
+            for feature in &data.features {
+                insert_feature.execute(params![name, feature.typ.to_string(), feature.status.to_string()])?;
+            }
+
+            for chd in &data.chd {
+                insert_disk.execute(params![name, chd.name, chd.status.to_string()])?;
+            }
+
+            let dependencies = roms.info(name).map(|info| info.dependencies).unwrap_or_default();
+            for (depth, (dependency, _)) in dependencies.iter().enumerate() {
+                insert_dependency.execute(params![name, dependency, depth as i64 + 1])?;
+            }
+        }
+    }
+    transaction.commit()?;
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use strum_macros::{Display, EnumString};
+
+use crate::models::roms::{CategoryReason, Feature, FeatureStatus, FeatureType};
+
+/// How many [Imperfect](FeatureEffect::Imperfect) features make a machine not working by default.
+pub const DEFAULT_IMPERFECT_LIMIT: usize = 2;
+
+/// What a feature type with a given status does to the categorization of a machine.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum FeatureEffect {
+    /// The machine is not working.
+    #[strum(serialize = "not-working")]
+    NotWorking,
+    /// Counts towards [imperfect_limit](FeatureRules::imperfect_limit).
+    #[strum(serialize = "imperfect")]
+    Imperfect,
+    /// The machine is still working.
+    #[strum(serialize = "ignore")]
+    Ignore,
+}
+
+///
+/// Per feature type and status rules deciding if imperfect or unemulated features
+/// make a machine not working. Features without a rule are ignored.
+///
+/// Rules are written as `type:status=effect`, separated by commas, and override the
+/// [default](FeatureRules::default) ones. The status can be left out to set both, for ex:
+///
+/// ```text
+/// protection:unemulated=not-working,sound:imperfect=ignore,controls=imperfect
+/// ```
+///
+/// ## Default rules
+/// - `graphics`, `sound`: both statuses are [Imperfect](FeatureEffect::Imperfect),
+///   so a machine with imperfect sound alone is still working.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureRules {
+    rules: HashMap<(FeatureType, FeatureStatus), FeatureEffect>,
+    /// How many [Imperfect](FeatureEffect::Imperfect) features make a machine not working.
+    pub imperfect_limit: usize,
+}
+
+impl Default for FeatureRules {
+    fn default() -> Self {
+        let mut rules = FeatureRules { rules: HashMap::new(), imperfect_limit: DEFAULT_IMPERFECT_LIMIT };
+        for typ in [FeatureType::Graphics, FeatureType::Sound] {
+            rules.set(typ.clone(), FeatureStatus::Imperfect, FeatureEffect::Imperfect);
+            rules.set(typ, FeatureStatus::Unemulated, FeatureEffect::Imperfect);
+        }
+        rules
+    }
+}
+
+impl FeatureRules {
+    /// Adds, or replaces, the rule of features of type `typ` with `status`.
+    pub fn set(&mut self, typ: FeatureType, status: FeatureStatus, effect: FeatureEffect) -> &mut FeatureRules {
+        self.rules.insert((typ, status), effect);
+        self
+    }
+
+    /// @return Effect of a feature of type `typ` with `status`, [Ignore](FeatureEffect::Ignore) if there is no rule.
+    pub fn effect(&self, typ: &FeatureType, status: &FeatureStatus) -> FeatureEffect {
+        self.rules.get(&(typ.clone(), status.clone())).copied().unwrap_or(FeatureEffect::Ignore)
+    }
+
+    ///
+    /// @return Why `features` make a machine not working, if they do.
+    ///
+    pub fn not_working_reason(&self, features: &[Feature]) -> Option<CategoryReason> {
+        let mut imperfect = 0;
+        for feature in features {
+            match self.effect(&feature.typ, &feature.status) {
+                FeatureEffect::NotWorking => {
+                    let status = feature.status.to_string().to_lowercase();
+                    return Some(CategoryReason::Feature(format!("{status} {}", feature.typ)));
+                }
+                FeatureEffect::Imperfect => imperfect += 1,
+                FeatureEffect::Ignore => (),
+            }
+        }
+
+        if imperfect >= self.imperfect_limit { Some(CategoryReason::Features) } else { None }
+    }
+}
+
+impl FromStr for FeatureRules {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut rules = FeatureRules::default();
+
+        for rule in value.split(',').map(str::trim).filter(|rule| !rule.is_empty()) {
+            let (feature, effect) = rule.split_once('=')
+                .ok_or_else(|| format!("Invalid feature rule '{rule}', expected type:status=effect."))?;
+            let effect = FeatureEffect::from_str(effect.trim())
+                .map_err(|_| format!("Invalid effect in feature rule '{rule}'. (not-working|imperfect|ignore)"))?;
+
+            let (typ, status) = match feature.split_once(':') {
+                Some((typ, status)) => (typ.trim(), Some(status.trim())),
+                None => (feature.trim(), None),
+            };
+            let typ = FeatureType::from_str(typ).map_err(|err| err.to_string())?;
+            if let FeatureType::Unknown(typ) = typ {
+                return Err(format!("Unknown feature type '{typ}' in feature rule '{rule}'."));
+            }
+
+            let statuses = match status {
+                None => vec![FeatureStatus::Imperfect, FeatureStatus::Unemulated],
+                Some(status) => match FeatureStatus::from_str(status) {
+                    Ok(FeatureStatus::Unknown(_)) | Err(_) => {
+                        return Err(format!("Unknown feature status '{status}' in feature rule '{rule}'. (imperfect|unemulated)"));
+                    }
+                    Ok(status) => vec![status],
+                },
+            };
+            statuses.into_iter().for_each(|status| { rules.set(typ.clone(), status, effect); });
+        }
+
+        Ok(rules)
+    }
+}
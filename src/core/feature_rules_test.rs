@@ -0,0 +1,89 @@
+use std::str::FromStr;
+
+use crate::core::curator::CategorizeOptions;
+use crate::core::feature_rules::{FeatureEffect, FeatureRules};
+use crate::core::roms_service::{parse, UnfilteredRomsExt};
+use crate::models::roms::{CategoryReason, Feature, FeatureStatus, FeatureType};
+use crate::models::roms::RomCategory::{NotWorking, Working};
+use crate::read_mame_xml;
+
+fn feature(typ: FeatureType, status: FeatureStatus) -> Feature {
+    Feature { typ, status }
+}
+
+#[test]
+fn should_apply_default_rules() {
+    let rules = FeatureRules::default();
+
+    // imperfect sound alone is still working
+    assert_eq!(rules.not_working_reason(&[feature(FeatureType::Sound, FeatureStatus::Imperfect)]), None);
+    // unknown types to the rules are ignored
+    assert_eq!(rules.not_working_reason(&[
+        feature(FeatureType::Palette, FeatureStatus::Imperfect),
+        feature(FeatureType::Sound, FeatureStatus::Unemulated),
+    ]), None);
+
+    assert_eq!(rules.not_working_reason(&[
+        feature(FeatureType::Sound, FeatureStatus::Imperfect),
+        feature(FeatureType::Graphics, FeatureStatus::Imperfect),
+    ]), Some(CategoryReason::Features));
+
+    // same as before feature rules, only sound and graphics are checked
+    assert_eq!(rules.not_working_reason(&[feature(FeatureType::Protection, FeatureStatus::Unemulated)]), None);
+}
+
+#[test]
+fn should_parse_rules_over_the_default_ones() {
+    let rules = FeatureRules::from_str("sound:imperfect=ignore, controls=not-working,timing:imperfect=imperfect,protection:unemulated=not-working").unwrap();
+
+    assert_eq!(rules.effect(&FeatureType::Sound, &FeatureStatus::Imperfect), FeatureEffect::Ignore);
+    assert_eq!(rules.effect(&FeatureType::Sound, &FeatureStatus::Unemulated), FeatureEffect::Imperfect);
+    assert_eq!(rules.effect(&FeatureType::Controls, &FeatureStatus::Imperfect), FeatureEffect::NotWorking);
+    assert_eq!(rules.effect(&FeatureType::Controls, &FeatureStatus::Unemulated), FeatureEffect::NotWorking);
+    assert_eq!(rules.effect(&FeatureType::Timing, &FeatureStatus::Imperfect), FeatureEffect::Imperfect);
+    assert_eq!(rules.effect(&FeatureType::Protection, &FeatureStatus::Unemulated), FeatureEffect::NotWorking);
+
+    assert_eq!(rules.not_working_reason(&[
+        feature(FeatureType::Sound, FeatureStatus::Imperfect),
+        feature(FeatureType::Graphics, FeatureStatus::Imperfect),
+    ]), None);
+
+    let reason = rules.not_working_reason(&[feature(FeatureType::Protection, FeatureStatus::Unemulated)]);
+    assert_eq!(reason, Some(CategoryReason::Feature("unemulated protection".to_string())));
+    assert_eq!(reason.unwrap().to_string(), "unemulated protection");
+    assert_eq!(rules.not_working_reason(&[feature(FeatureType::Protection, FeatureStatus::Imperfect)]), None);
+
+    assert_eq!(FeatureRules::from_str("").unwrap(), FeatureRules::default());
+}
+
+#[test]
+fn should_reject_invalid_rules() {
+    assert!(FeatureRules::from_str("sound").unwrap_err().contains("expected type:status=effect"));
+    assert!(FeatureRules::from_str("sound=broken").unwrap_err().contains("Invalid effect"));
+    assert!(FeatureRules::from_str("smell=ignore").unwrap_err().contains("Unknown feature type 'smell'"));
+    assert!(FeatureRules::from_str("sound:partial=ignore").unwrap_err().contains("Unknown feature status 'partial'"));
+}
+
+#[test]
+fn should_categorize_with_feature_rules() {
+    let contents = r#"<mame>
+        <machine name="protected"><description>Protected</description><driver status="good" emulation="good"/><feature type="protection" status="unemulated"/></machine>
+        <machine name="noisy"><description>Noisy</description><driver status="good" emulation="good"/><feature type="sound" status="imperfect"/><feature type="graphics" status="imperfect"/></machine>
+    </mame>"#;
+    let categorize = |options: &CategorizeOptions| parse(read_mame_xml(contents).unwrap(), Default::default()).unwrap()
+        .categorize_roms_with(options).unwrap();
+
+    let roms = categorize(&CategorizeOptions::default());
+    assert!(matches!(roms["protected"].category, Working));
+    assert!(matches!(roms["noisy"].category, NotWorking));
+    assert_eq!(roms["noisy"].reason, CategoryReason::Features);
+
+    let options = CategorizeOptions {
+        feature_rules: FeatureRules::from_str("protection:unemulated=not-working,graphics:imperfect=ignore").unwrap(),
+        ..CategorizeOptions::default()
+    };
+    let roms = categorize(&options);
+    assert!(matches!(roms["protected"].category, NotWorking));
+    assert_eq!(roms["protected"].reason, CategoryReason::Feature("unemulated protection".to_string()));
+    assert!(matches!(roms["noisy"].category, Working));
+}
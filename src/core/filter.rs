@@ -0,0 +1,397 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::roms::Rom;
+
+///
+/// A compiled filter expression used to select which machines are copied.
+///
+/// Expressions are made of comparisons (`field op value`) and boolean fields,
+/// combined with `&&`, `||`, `!` and parentheses, for example:
+///
+/// ```text
+/// status == working && driver != preliminary && category ~ "Shooter" && !chd
+/// ```
+///
+/// ## Operators
+/// - `==`, `!=`: case-insensitive equality.
+/// - `<`, `<=`, `>`, `>=`: numeric comparison when both sides are numbers,
+///   otherwise case-insensitive alphabetical comparison.
+/// - `~`: case-insensitive "contains".
+///
+/// ## Fields
+/// - `name`: machine name.
+/// - `status`: rom category (`working`, `not_working`, `bios`, `system`, `uncategorized`).
+/// - `driver`, `emulation`: driver status (`good`, `imperfect`, `preliminary`).
+/// - `category`: catver category, for ex, `Platform / Shooter`.
+/// - `description`, `year`, `manufacturer`, `sourcefile`: listxml machine information.
+/// - `players`, `buttons`, `rotate`: numeric input and display information.
+/// - `display`: display type, for ex, `raster`, `vector`.
+/// - `control`: matches any control type, for ex, `control == lightgun`.
+/// - `parent`: machine this one is a clone of.
+/// - `romof`: machine this one takes roms from.
+/// - `feature`: matches any feature type, for ex, `feature ~ sound`.
+/// - `chd`, `clone`, `bios`, `system`, `mechanical`: boolean fields.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    /// @return true if the machine `name` / `rom` satisfies the expression.
+    pub fn matches(&self, name: &str, rom: &Rom) -> bool {
+        self.expr.eval(name, rom)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(value)?;
+        if tokens.is_empty() { return Err("Filter expression is empty.".into()); }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected '{token}' in filter expression."));
+        }
+
+        Ok(Filter { expr })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Status,
+    Driver,
+    Emulation,
+    Category,
+    Description,
+    Year,
+    Manufacturer,
+    SourceFile,
+    Players,
+    Buttons,
+    Rotate,
+    Display,
+    Control,
+    Parent,
+    RomOf,
+    Feature,
+    Chd,
+    Clone,
+    Bios,
+    System,
+    Mechanical,
+}
+
+impl Field {
+    fn is_bool(&self) -> bool {
+        matches!(self, Field::Chd | Field::Clone | Field::Bios | Field::System | Field::Mechanical)
+    }
+
+    /// Enum backed fields are compared ignoring `_`, `-` and spaces,
+    /// so that `not_working` matches `NotWorking`.
+    fn is_enum(&self) -> bool {
+        matches!(self, Field::Status | Field::Driver | Field::Emulation)
+    }
+
+    fn values(&self, name: &str, rom: &Rom) -> Vec<String> {
+        let data = &rom.data;
+        match self {
+            Field::Name => vec![name.to_string()],
+            Field::Status => vec![rom.category.to_string()],
+            Field::Driver => data.status.iter().map(|s| s.driver.to_string()).collect(),
+            Field::Emulation => data.status.iter().map(|s| s.emulation.to_string()).collect(),
+            Field::Category => vec![data.category.clone()],
+            Field::Description => vec![data.description.clone()],
+            Field::Year => vec![data.year.clone()],
+            Field::Manufacturer => vec![data.manufacturer.clone()],
+            Field::SourceFile => vec![data.source_file.clone()],
+            Field::Players => vec![data.players().to_string()],
+            Field::Buttons => vec![data.buttons().to_string()],
+            Field::Rotate => vec![data.rotation().to_string()],
+            Field::Display => data.display.iter().map(|d| d.typ.clone()).collect(),
+            Field::Control => data.control_types().iter().map(|c| c.to_string()).collect(),
+            Field::Parent => data.clone_of.iter().cloned().collect(),
+            Field::RomOf => data.rom_of.iter().cloned().collect(),
+            Field::Feature => data.features.iter().map(|f| f.typ.to_string()).collect(),
+            Field::Chd |
+            Field::Clone |
+            Field::Bios |
+            Field::System |
+            Field::Mechanical => vec![self.flag(rom).to_string()],
+        }
+    }
+
+    fn flag(&self, rom: &Rom) -> bool {
+        let data = &rom.data;
+        match self {
+            Field::Chd => !data.chd.is_empty(),
+            Field::Clone => data.clone_of.is_some(),
+            Field::Bios => data.is_bios,
+            Field::System => data.is_system,
+            Field::Mechanical => data.is_mechanical,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "name" => Ok(Field::Name),
+            "status" => Ok(Field::Status),
+            "driver" => Ok(Field::Driver),
+            "emulation" => Ok(Field::Emulation),
+            "category" | "genre" => Ok(Field::Category),
+            "description" | "title" => Ok(Field::Description),
+            "year" => Ok(Field::Year),
+            "manufacturer" => Ok(Field::Manufacturer),
+            "sourcefile" => Ok(Field::SourceFile),
+            "players" => Ok(Field::Players),
+            "buttons" => Ok(Field::Buttons),
+            "rotate" | "rotation" => Ok(Field::Rotate),
+            "display" => Ok(Field::Display),
+            "control" => Ok(Field::Control),
+            "parent" | "cloneof" => Ok(Field::Parent),
+            "romof" => Ok(Field::RomOf),
+            "feature" => Ok(Field::Feature),
+            "chd" => Ok(Field::Chd),
+            "clone" => Ok(Field::Clone),
+            "bios" => Ok(Field::Bios),
+            "system" => Ok(Field::System),
+            "mechanical" => Ok(Field::Mechanical),
+            _ => Err(format!("Unknown filter field '{value}'.")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Flag(Field),
+    Compare(Field, Op, String),
+}
+
+impl Expr {
+    fn eval(&self, name: &str, rom: &Rom) -> bool {
+        match self {
+            Expr::And(left, right) => left.eval(name, rom) && right.eval(name, rom),
+            Expr::Or(left, right) => left.eval(name, rom) || right.eval(name, rom),
+            Expr::Not(expr) => !expr.eval(name, rom),
+            Expr::Flag(field) => field.flag(rom),
+            Expr::Compare(field, op, expected) => {
+                let values: Vec<String> = field.values(name, rom).into_iter()
+                    .filter(|value| !value.is_empty())
+                    .collect();
+                // a missing value never matches, except for `!=`
+                if values.is_empty() { return matches!(op, Op::Ne); }
+                if matches!(op, Op::Ne) {
+                    return !values.iter().any(|value| compare(*field, Op::Eq, value, expected));
+                }
+                values.iter().any(|value| compare(*field, *op, value, expected))
+            }
+        }
+    }
+}
+
+fn compare(field: Field, op: Op, value: &str, expected: &str) -> bool {
+    let normalize = |s: &str| {
+        if field.is_enum() {
+            s.chars().filter(|c| !matches!(c, '_' | '-' | ' ')).collect::<String>().to_ascii_lowercase()
+        } else {
+            s.to_lowercase()
+        }
+    };
+    let (value, expected) = (normalize(value), normalize(expected));
+
+    let ordering = match (value.parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b),
+        _ => Some(value.cmp(&expected)),
+    };
+
+    match op {
+        Op::Eq => value == expected,
+        Op::Ne => value != expected,
+        Op::Contains => value.contains(&expected),
+        Op::Lt => ordering.is_some_and(|o| o.is_lt()),
+        Op::Le => ordering.is_some_and(|o| o.is_le()),
+        Op::Gt => ordering.is_some_and(|o| o.is_gt()),
+        Op::Ge => ordering.is_some_and(|o| o.is_ge()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Op(Op),
+    Word(String),
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
+            Token::Not => write!(f, "!"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Op(op) => write!(f, "{}", match op {
+                Op::Eq => "==",
+                Op::Ne => "!=",
+                Op::Lt => "<",
+                Op::Le => "<=",
+                Op::Gt => ">",
+                Op::Ge => ">=",
+                Op::Contains => "~",
+            }),
+            Token::Word(word) => write!(f, "{word}"),
+            Token::Quoted(word) => write!(f, "\"{word}\""),
+        }
+    }
+}
+
+fn tokenize(value: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '~' => Token::Op(Op::Contains),
+            '&' | '|' => {
+                if chars.next_if_eq(&c).is_none() {
+                    return Err(format!("Expected '{c}{c}' in filter expression."));
+                }
+                if c == '&' { Token::And } else { Token::Or }
+            }
+            '=' => {
+                if chars.next_if_eq(&'=').is_none() {
+                    return Err("Expected '==' in filter expression.".into());
+                }
+                Token::Op(Op::Eq)
+            }
+            '!' => if chars.next_if_eq(&'=').is_some() { Token::Op(Op::Ne) } else { Token::Not },
+            '<' => if chars.next_if_eq(&'=').is_some() { Token::Op(Op::Le) } else { Token::Op(Op::Lt) },
+            '>' => if chars.next_if_eq(&'=').is_some() { Token::Op(Op::Ge) } else { Token::Op(Op::Gt) },
+            '"' | '\'' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => word.push(next),
+                        None => return Err("Unterminated string in filter expression.".into()),
+                    }
+                }
+                Token::Quoted(word)
+            }
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some(next) = chars.next_if(|n| is_word_char(*n)) {
+                    word.push(next);
+                }
+                Token::Word(word)
+            }
+            _ => return Err(format!("Unexpected '{c}' in filter expression.")),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '?' | '/' | '*')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Missing ')' in filter expression.".into()),
+                }
+            }
+            Some(Token::Word(word)) => {
+                let field = Field::from_str(&word)?;
+                match self.peek() {
+                    Some(Token::Op(op)) => {
+                        let op = *op;
+                        self.next();
+                        match self.next() {
+                            Some(Token::Word(value)) | Some(Token::Quoted(value)) => Ok(Expr::Compare(field, op, value)),
+                            _ => Err(format!("Missing value after '{word}' in filter expression.")),
+                        }
+                    }
+                    _ if field.is_bool() => Ok(Expr::Flag(field)),
+                    _ => Err(format!("Field '{word}' needs to be compared with a value.")),
+                }
+            }
+            Some(token) => Err(format!("Unexpected '{token}' in filter expression.")),
+            None => Err("Unexpected end of filter expression.".into()),
+        }
+    }
+}
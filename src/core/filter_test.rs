@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use crate::core::filter::Filter;
+use crate::core::test_utils::build_roms;
+use crate::models::roms::Roms;
+
+fn matching(roms: &Roms, expression: &str) -> Vec<String> {
+    let filter = Filter::from_str(expression).unwrap();
+    let mut names: Vec<String> = roms.iter()
+        .filter(|(name, rom)| filter.matches(name, rom))
+        .map(|(name, _)| name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn should_filter_by_status_and_flags() {
+    let roms = build_roms();
+
+    assert_eq!(matching(&roms, "status == working && !chd"), vec!["005", "elevatora", "robocop"]);
+    assert_eq!(matching(&roms, "status == working && chd"), vec!["area51"]);
+    assert_eq!(matching(&roms, "status == not_working && !system"), vec!["100lions", "99bottles", "as_acp"]);
+    assert_eq!(matching(&roms, "status == Bios"), vec!["3dobios", "aristmk6"]);
+    assert_eq!(matching(&roms, "clone || mechanical"), vec!["99bottles", "as_acp", "elevatora"]);
+}
+
+#[test]
+fn should_filter_by_text_fields() {
+    let roms = build_roms();
+
+    assert_eq!(matching(&roms, "category ~ \"shooter\""), vec!["005", "elevatora", "robocop"]);
+    assert_eq!(matching(&roms, "category ~ 'Slot Machine' && !(name == as_acp)"), vec!["100lions"]);
+    assert_eq!(matching(&roms, "parent == elevator"), vec!["elevatora"]);
+    assert_eq!(matching(&roms, "driver == imperfect || emulation != good"), vec!["005", "100lions", "3dobios", "99bottles", "a24play", "aristmk6", "as_acp", "robocop", "sv801"]);
+    assert_eq!(matching(&roms, "name >= r && name < s"), vec!["robocop"]);
+}
+
+#[test]
+fn should_filter_by_machine_information() {
+    let roms = build_roms();
+
+    assert_eq!(matching(&roms, "status == working && year >= 1988 && !chd"), vec!["robocop"]);
+    assert_eq!(matching(&roms, "manufacturer ~ \"Data East\" || manufacturer == sega"), vec!["005", "robocop"]);
+    assert_eq!(matching(&roms, "control == lightgun"), vec!["area51"]);
+    assert_eq!(matching(&roms, "players > 2"), vec!["a24play"]);
+    assert_eq!(matching(&roms, "rotate == 270 || buttons > 100"), vec!["005", "99bottles"]);
+    assert_eq!(matching(&roms, "description ~ elevator && sourcefile == taitosj.cpp"), vec!["elevatora"]);
+    assert_eq!(matching(&roms, "year < 1990 && display == raster"), vec!["005", "elevatora", "robocop"]);
+}
+
+#[test]
+fn should_reject_invalid_expressions() {
+    assert!(Filter::from_str("").is_err());
+    assert!(Filter::from_str("colour == red").is_err());
+    assert!(Filter::from_str("status working").is_err());
+    assert!(Filter::from_str("category").is_err());
+    assert!(Filter::from_str("status == working &").is_err());
+    assert!(Filter::from_str("(status == working").is_err());
+    assert!(Filter::from_str("name == \"robocop").is_err());
+    assert!(Filter::from_str("status == working)").is_err());
+}
@@ -0,0 +1,140 @@
+//! Identifies files that don't match any machine name, for ex, a renamed `robocop (world).zip`,
+//! by the checksums of the roms inside them, see [RomIndex].
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::Path;
+
+use sha1::{Digest, Sha1};
+use strum_macros::{Display, EnumString};
+
+use crate::core::container::read_entries;
+use crate::error::CuratorResult;
+use crate::models::roms::{RomFile, Roms};
+
+/// What to do with source files that don't match any machine, see [CopyOptions](crate::core::curator::CopyOptions).
+#[derive(Display, Debug, Default, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum UnknownFilesAction {
+    /// Left where they are and reported as ignored.
+    #[default]
+    #[strum(serialize = "keep")]
+    Keep,
+    /// Copied to the `unknown` folder of the destination and identified.
+    #[strum(serialize = "copy")]
+    Copy,
+    /// Moved to the `unknown` folder of the destination and identified.
+    #[strum(serialize = "move")]
+    Move,
+}
+
+/// Machine a file was identified as, see [RomIndex::identify].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identification {
+    pub machine: String,
+    /// Roms of the machine found in the file.
+    pub matched: usize,
+    /// Dumped roms of the machine, without those merged from its parent.
+    pub total: usize,
+}
+
+impl Identification {
+    /// @return true if all dumped roms of the machine were found.
+    pub fn is_complete(&self) -> bool {
+        self.matched == self.total
+    }
+}
+
+///
+/// Rom files of all machines in `mame.xml`, by CRC32 and size, to identify containers
+/// whatever their name is.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use roms_curator::core::identify::RomIndex;
+///
+/// let roms = roms_curator::load_roms("mame.xml", "catver.ini")?;
+/// let index = RomIndex::new(&roms);
+/// if let Some(identification) = index.identify(Path::new("/roms/robocop (world).zip"))? {
+///     println!("{} ({} of {} roms)", identification.machine, identification.matched, identification.total);
+/// }
+/// # Ok::<(), roms_curator::error::CuratorError>(())
+/// ```
+///
+pub struct RomIndex<'a> {
+    files: HashMap<(u32, u64), Vec<(&'a str, &'a RomFile)>>,
+    totals: HashMap<&'a str, usize>,
+}
+
+impl<'a> RomIndex<'a> {
+    ///
+    /// Indexes the dumped rom files of all machines in `roms`. Roms merged from the parent
+    /// are left out, they belong to the parent, so a clone stored split can be found complete.
+    ///
+    pub fn new(roms: &'a Roms) -> RomIndex<'a> {
+        let mut files: HashMap<(u32, u64), Vec<(&str, &RomFile)>> = HashMap::new();
+        let mut totals: HashMap<&str, usize> = HashMap::new();
+
+        for (name, rom) in roms {
+            for file in rom.data.rom_files.iter().filter(|file| file.merge.is_none()) {
+                let Some(crc) = file.crc.as_ref().and_then(|crc| u32::from_str_radix(crc, 16).ok()) else { continue; };
+                files.entry((crc, file.size)).or_default().push((name.as_str(), file));
+                *totals.entry(name.as_str()).or_default() += 1;
+            }
+        }
+
+        RomIndex { files, totals }
+    }
+
+    ///
+    /// Reads the container at `path` and looks up its files by CRC32 and size, and by SHA1
+    /// when `mame.xml` has it. The machine with most roms found wins, then the one with
+    /// fewest roms, so that a clone is not taken for its parent.
+    ///
+    /// @return `None` if no file belongs to any machine.
+    /// An [Archive](crate::error::CuratorError::Archive) error if `path` is not a container or can't be read.
+    ///
+    pub fn identify(&self, path: &Path) -> CuratorResult<Option<Identification>> {
+        // rom names found for each machine, a rom can be in a container more than once
+        let mut found: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+        read_entries(path, |entry, reader| {
+            // read even if not indexed, 7z entries must be read in order
+            let sha1 = sha1_hex(reader)?;
+            let Some(candidates) = self.files.get(&(entry.crc, entry.size)) else { return Ok(()); };
+
+            candidates.iter()
+                .filter(|(_, file)| file.sha1.as_ref().is_none_or(|expected| expected.eq_ignore_ascii_case(&sha1)))
+                .for_each(|(machine, file)| {
+                    let names = found.entry(machine).or_default();
+                    if !names.contains(&file.name.as_str()) { names.push(&file.name) }
+                });
+            Ok(())
+        })?;
+
+        let best = found.into_iter()
+            .map(|(machine, names)| Identification {
+                machine: machine.to_string(),
+                matched: names.len(),
+                total: self.totals[machine],
+            })
+            .max_by(|a, b| a.matched.cmp(&b.matched).then(b.total.cmp(&a.total)).then(b.machine.cmp(&a.machine)));
+
+        Ok(best)
+    }
+}
+
+/// @return SHA1 of all that's left in `reader`, as 40 lowercase hex digits.
+fn sha1_hex(reader: &mut dyn Read) -> CuratorResult<String> {
+    let mut hasher = Sha1::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 { break; }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
@@ -0,0 +1,109 @@
+use std::fs;
+
+use sevenz_rust::{SevenZArchiveEntry, SevenZWriter};
+use zip::write::SimpleFileOptions;
+
+use crate::core::identify::{Identification, RomIndex, UnknownFilesAction};
+use crate::core::roms_service::{parse, UnfilteredRomsExt};
+use crate::core::test_utils::{create_zip, folder};
+use crate::models::roms::Roms;
+use crate::read_mame_xml;
+
+fn build_roms() -> Roms {
+    let contents = r#"<mame>
+        <machine name="robocop"><description>Robocop</description>
+            <rom name="a.bin" size="3" crc="352441c2" sha1="a9993e364706816aba3e25717850c26c9cd0d89d"/>
+            <rom name="b.bin" size="5" crc="3610a686" sha1="aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"/>
+        </machine>
+        <machine name="robocopu" cloneof="robocop" romof="robocop"><description>Robocop (US)</description>
+            <rom name="a.bin" merge="a.bin" size="3" crc="352441c2" sha1="a9993e364706816aba3e25717850c26c9cd0d89d"/>
+            <rom name="c.bin" size="5" crc="0d7e864e"/>
+        </machine>
+        <machine name="robocopb"><description>Robocop (bootleg)</description>
+            <rom name="c.bin" size="5" crc="0d7e864e"/>
+            <rom name="z.bin" size="3" crc="77379134"/>
+        </machine>
+        <machine name="hello"><description>Hello</description>
+            <rom name="hello.bin" size="5" crc="3610a686" sha1="aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"/>
+        </machine>
+        <machine name="collision"><description>Same CRC32, different SHA1</description>
+            <rom name="x.bin" size="5" crc="3610a686" sha1="0000000000000000000000000000000000000000"/>
+        </machine>
+        <machine name="nodump"><description>Never dumped</description>
+            <rom name="n.bin" size="5" status="nodump"/>
+        </machine>
+    </mame>"#;
+    parse(read_mame_xml(contents).unwrap(), Default::default()).unwrap().categorize_roms().unwrap()
+}
+
+fn identification(machine: &str, matched: usize, total: usize) -> Option<Identification> {
+    Some(Identification { machine: machine.to_string(), matched, total })
+}
+
+#[test]
+fn should_identify_renamed_containers_by_checksum() {
+    let roms = build_roms();
+    let index = RomIndex::new(&roms);
+    let folder = folder("identify", "renamed");
+
+    let path = folder.join("robocop (world).zip");
+    create_zip(&path, &[("1.bin", b"abc"), ("2.bin", b"hello")], SimpleFileOptions::default());
+    assert_eq!(index.identify(&path).unwrap(), identification("robocop", 2, 2));
+    assert!(index.identify(&path).unwrap().unwrap().is_complete());
+
+    // the clone, not the parent it shares a.bin with, merged roms are the parent's
+    let path = folder.join("mystery.zip");
+    create_zip(&path, &[("c.bin", b"hellO"), ("a.bin", b"abc")], SimpleFileOptions::default());
+    assert_eq!(index.identify(&path).unwrap(), identification("robocopu", 1, 1));
+
+    // split clone, without its parent's roms, is complete
+    let path = folder.join("split.zip");
+    create_zip(&path, &[("c.bin", b"hellO")], SimpleFileOptions::default());
+    assert_eq!(index.identify(&path).unwrap(), identification("robocopu", 1, 1));
+    assert!(index.identify(&path).unwrap().unwrap().is_complete());
+
+    // same roms found, the machine with fewest roms wins, SHA1 rules out the collision
+    let path = folder.join("hello.zip");
+    create_zip(&path, &[("hello.bin", b"hello")], SimpleFileOptions::default());
+    assert_eq!(index.identify(&path).unwrap(), identification("hello", 1, 1));
+
+    let path = folder.join("nothing.zip");
+    create_zip(&path, &[("readme.txt", b"nothing to see")], SimpleFileOptions::default());
+    assert_eq!(index.identify(&path).unwrap(), None);
+}
+
+#[test]
+fn should_identify_7z_and_folder_containers() {
+    let roms = build_roms();
+    let index = RomIndex::new(&roms);
+    let folder = folder("identify", "kinds");
+
+    // unknown entry first, it must be read for the next ones to be
+    let seven_zip_path = folder.join("robocop-partial.7z");
+    let mut seven_zip = SevenZWriter::create(&seven_zip_path).unwrap();
+    for (name, contents) in [("0.txt", b"unknown".as_slice()), ("a.bin", b"abc")] {
+        let mut entry = SevenZArchiveEntry::new();
+        entry.name = name.to_string();
+        entry.has_stream = true;
+        seven_zip.push_archive_entry(entry, Some(contents)).unwrap();
+    }
+    seven_zip.finish().unwrap();
+    assert_eq!(index.identify(&seven_zip_path).unwrap(), identification("robocop", 1, 2));
+
+    let folder_path = folder.join("Robo");
+    fs::create_dir_all(&folder_path).unwrap();
+    fs::write(folder_path.join("a.bin"), b"abc").unwrap();
+    fs::write(folder_path.join("b.bin"), b"hello").unwrap();
+    assert_eq!(index.identify(&folder_path).unwrap(), identification("robocop", 2, 2));
+
+    assert!(index.identify(&folder.join("robocop.txt")).is_err());
+}
+
+#[test]
+fn should_parse_unknown_files_actions() {
+    assert_eq!("keep".parse::<UnknownFilesAction>().unwrap(), UnknownFilesAction::Keep);
+    assert_eq!("Copy".parse::<UnknownFilesAction>().unwrap(), UnknownFilesAction::Copy);
+    assert_eq!("MOVE".parse::<UnknownFilesAction>().unwrap(), UnknownFilesAction::Move);
+    assert!("delete".parse::<UnknownFilesAction>().is_err());
+    assert_eq!(UnknownFilesAction::default().to_string(), "keep");
+}
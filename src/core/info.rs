@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::core::scanner::{rom_name, scan_source, ScanOptions};
+use crate::error::CuratorResult;
+use crate::models::roms::{ChdStatus, Rom, RomCategory, Roms};
+
+/// Everything known about a single machine, see [RomsInfoExt::info].
+#[derive(Debug)]
+pub struct RomInfo<'a> {
+    pub name: String,
+    pub rom: &'a Rom,
+    /// Machines this one depends on through `romof`, closest first, for ex, parent then bios.
+    pub dependencies: Vec<(String, Option<RomCategory>)>,
+    /// Files and directories with this machine's name in the source paths,
+    /// `None` if the source paths were not checked.
+    pub found_in: Option<Vec<PathBuf>>,
+}
+
+/// Files and directories in the source paths by machine name, see [RomsInfoExt::scan_sources].
+pub type SourceFiles = HashMap<String, Vec<PathBuf>>;
+
+pub trait RomsInfoExt {
+    ///
+    /// Looks up a single machine.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use roms_curator::core::info::RomsInfoExt;
+    ///
+    /// let roms = roms_curator::load_roms("mame.xml", "catver.ini").unwrap();
+    /// if let Some(info) = roms.info("robocop") {
+    ///     println!("{info}");
+    /// }
+    /// ```
+    ///
+    /// @return [RomInfo] of the machine, `None` if it's not in `mame.xml`.
+    ///
+    fn info(&self, name: &str) -> Option<RomInfo<'_>>;
+
+    ///
+    /// Scans `source_paths` the same way as when copying, see [scan_source], once for any
+    /// number of machines looked up with [info_with_sources](RomsInfoExt::info_with_sources).
+    ///
+    /// @return Files and directories found, by machine name, each sorted.
+    ///
+    fn scan_sources(&self, source_paths: &[impl AsRef<Path>], options: &ScanOptions) -> CuratorResult<SourceFiles>;
+
+    ///
+    /// Same as [info](RomsInfoExt::info), also looking for the machine files in `sources`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use roms_curator::core::info::RomsInfoExt;
+    /// use roms_curator::core::scanner::ScanOptions;
+    ///
+    /// let roms = roms_curator::load_roms("mame.xml", "catver.ini").unwrap();
+    /// let sources = roms.scan_sources(&["/roms"], &ScanOptions::default()).unwrap();
+    /// for name in ["robocop", "robocop2"] {
+    ///     if let Some(info) = roms.info_with_sources(name, &sources) {
+    ///         println!("{info}");
+    ///     }
+    /// }
+    /// ```
+    ///
+    fn info_with_sources(&self, name: &str, sources: &SourceFiles) -> Option<RomInfo<'_>>;
+}
+
+impl RomsInfoExt for Roms {
+    fn info(&self, name: &str) -> Option<RomInfo<'_>> {
+        let name = name.to_ascii_lowercase();
+        let rom = self.get(&name)?;
+
+        let mut dependencies: Vec<(String, Option<RomCategory>)> = Vec::new();
+        let mut rom_of = rom.data.rom_of.clone();
+        while let Some(dependency) = rom_of {
+            // guard against broken files with circular dependencies
+            if dependency == name || dependencies.iter().any(|(name, _)| *name == dependency) { break; }
+            let found = self.get(&dependency);
+            rom_of = found.and_then(|rom| rom.data.rom_of.clone());
+            dependencies.push((dependency, found.map(|rom| rom.category)));
+        }
+
+        Some(RomInfo { name, rom, dependencies, found_in: None })
+    }
+
+    fn scan_sources(&self, source_paths: &[impl AsRef<Path>], options: &ScanOptions) -> CuratorResult<SourceFiles> {
+        let mut sources = SourceFiles::new();
+        for source_path in source_paths {
+            for path in scan_source(self, source_path.as_ref(), options)? {
+                if let Ok(name) = rom_name(&path) { sources.entry(name).or_default().push(path); }
+            }
+        }
+        sources.values_mut().for_each(|paths| paths.sort());
+
+        Ok(sources)
+    }
+
+    fn info_with_sources(&self, name: &str, sources: &SourceFiles) -> Option<RomInfo<'_>> {
+        let mut info = self.info(name)?;
+        info.found_in = Some(sources.get(&info.name).cloned().unwrap_or_default());
+        Some(info)
+    }
+}
+
+impl fmt::Display for RomInfo<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = &self.rom.data;
+        let or_unknown = |value: &str| if value.is_empty() { "unknown".to_string() } else { value.to_string() };
+
+        writeln!(f, "{}: {}", self.name, or_unknown(&data.description))?;
+        writeln!(f, "  Year: {}", or_unknown(&data.year))?;
+        writeln!(f, "  Manufacturer: {}", or_unknown(&data.manufacturer))?;
+        writeln!(f, "  Source file: {}", or_unknown(&data.source_file))?;
+        writeln!(f, "  Genre: {}", or_unknown(&data.category))?;
+        writeln!(f, "  Category: {} ({})", self.rom.category, self.rom.reason)?;
+
+        match &data.status {
+            Some(status) => writeln!(f, "  Driver: {}, emulation: {}", status.driver, status.emulation)?,
+            None => writeln!(f, "  Driver: unknown")?,
+        }
+
+        let mut flags: Vec<&str> = Vec::new();
+        if data.is_bios { flags.push("bios"); }
+        if data.is_system { flags.push("system"); }
+        if data.is_mechanical { flags.push("mechanical"); }
+        if !flags.is_empty() { writeln!(f, "  Type: {}", flags.join(", "))?; }
+
+        if !data.features.is_empty() {
+            let features: Vec<String> = data.features.iter()
+                .map(|feature| format!("{} {}", feature.typ, feature.status.to_string().to_lowercase()))
+                .collect();
+            writeln!(f, "  Features: {}", features.join(", "))?;
+        }
+
+        if let Some(parent) = &data.clone_of {
+            writeln!(f, "  Parent: {parent}")?;
+        }
+
+        if !self.dependencies.is_empty() {
+            let dependencies: Vec<String> = self.dependencies.iter()
+                .map(|(name, category)| match category {
+                    Some(category) => format!("{name} ({category})"),
+                    None => format!("{name} (not found)"),
+                })
+                .collect();
+            writeln!(f, "  Depends on: {}", dependencies.join(" -> "))?;
+        }
+
+        if !data.chd.is_empty() {
+            let chds: Vec<String> = data.chd.iter()
+                .map(|chd| match &chd.status {
+                    ChdStatus::NoStatus => chd.name.clone(),
+                    ChdStatus::NoDump => format!("{} (no dump)", chd.name),
+                    ChdStatus::BadDump => format!("{} (bad dump)", chd.name),
+                    ChdStatus::Unknown(status) => format!("{} (unknown status {status})", chd.name),
+                })
+                .collect();
+            writeln!(f, "  CHDs: {}", chds.join(", "))?;
+        }
+
+        if let Some(found_in) = &self.found_in {
+            if found_in.is_empty() {
+                writeln!(f, "  Found in sources: no")?;
+            } else {
+                let paths: Vec<String> = found_in.iter().map(|path| path.display().to_string()).collect();
+                writeln!(f, "  Found in sources: {}", paths.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
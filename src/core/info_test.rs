@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+use crate::core::info::RomsInfoExt;
+use crate::core::scanner::ScanOptions;
+use crate::load_roms;
+use crate::models::roms::RomCategory;
+
+#[test]
+fn should_show_machine_info() {
+    let roms = load_roms("tests/resources/listxml_0244.xml", "tests/resources/catver_0244.ini").unwrap();
+
+    let info = roms.info("100Lions").unwrap();
+    assert_eq!(info.name, "100lions");
+    assert_eq!(info.rom.category, RomCategory::NotWorking);
+    assert_eq!(info.dependencies, vec![("aristmk6".to_string(), Some(RomCategory::Bios))]);
+    assert!(info.found_in.is_none());
+
+    let text = info.to_string();
+    assert!(text.starts_with("100lions: 100 Lions (10219211, NSW/ACT)\n"));
+    assert!(text.contains("  Genre: Slot Machine / Video Slot\n"));
+    assert!(text.contains("  Category: NotWorking (preliminary driver)\n"));
+    assert!(text.contains("  Driver: Preliminary, emulation: Preliminary\n"));
+    assert!(text.contains("  Features: sound unemulated\n"));
+    assert!(text.contains("  Depends on: aristmk6 (Bios)\n"));
+
+    let sources = roms.scan_sources(&["tests/resources/merged_roms/", "tests/resources/chds/"], &ScanOptions::default()).unwrap();
+    let info = roms.info_with_sources("99bottles", &sources).unwrap();
+    let text = info.to_string();
+    assert!(text.contains("  Parent: gammagic\n"));
+    assert!(text.contains("  Depends on: gammagic (not found)\n"));
+    assert!(text.contains("(bad dump)"));
+    assert!(text.contains("  Found in sources: tests/resources/merged_roms/99bottles.zip\n"));
+
+    let info = roms.info_with_sources("area51", &sources).unwrap();
+    assert_eq!(info.found_in.unwrap(), vec![Path::new("tests/resources/chds/Area51"), Path::new("tests/resources/merged_roms/area51.zip")]);
+    assert!(roms.info_with_sources("unknown", &sources).is_none());
+
+    // sub-directories, scanned as when copying
+    let options = ScanOptions { max_depth: 1, ..ScanOptions::default() };
+    let sources = roms.scan_sources(&["tests/resources/"], &ScanOptions::default()).unwrap();
+    let info = roms.info_with_sources("robocop", &sources).unwrap();
+    assert_eq!(info.found_in.unwrap(), Vec::<PathBuf>::new());
+    let sources = roms.scan_sources(&["tests/resources/"], &options).unwrap();
+    let info = roms.info_with_sources("robocop", &sources).unwrap();
+    assert_eq!(info.found_in.unwrap(), vec![Path::new("tests/resources/merged_roms/robocop.zip")]);
+
+    assert!(roms.info("unknown").is_none());
+}
@@ -1,7 +1,47 @@
 pub mod roms_service;
 pub mod args;
+pub mod catalog;
+pub mod config;
+pub mod container;
+pub mod curator;
+pub mod feature_rules;
+pub mod filter;
+pub mod identify;
+pub mod info;
+pub mod progress;
+pub mod scanner;
+pub mod export;
+pub mod diff;
+pub mod serve;
+pub mod stats;
+pub mod torrentzip;
+pub mod verify;
 
 #[cfg(test)]
 mod roms_service_test;
 #[cfg(test)]
-mod args_test;
\ No newline at end of file
+mod args_test;
+#[cfg(test)]
+mod filter_test;
+#[cfg(test)]
+mod diff_test;
+#[cfg(test)]
+mod config_test;
+#[cfg(test)]
+mod info_test;
+#[cfg(test)]
+mod serve_test;
+#[cfg(test)]
+mod feature_rules_test;
+#[cfg(test)]
+mod catalog_test;
+#[cfg(test)]
+mod scanner_test;
+#[cfg(test)]
+mod container_test;
+#[cfg(test)]
+mod identify_test;
+#[cfg(test)]
+mod torrentzip_test;
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,80 @@
+use std::sync::Mutex;
+
+use indicatif::ProgressBar;
+use log::info;
+
+use crate::utils::build_progress_bar;
+
+///
+/// Receives progress of long running operations, like categorizing or copying roms,
+/// so that embedders can show it their own way.
+///
+pub trait Progress {
+    /// A new step with `length` units of work starts.
+    fn start(&self, length: u64);
+    /// One more unit of work is done.
+    fn inc(&self);
+    /// The current step is done.
+    fn finish(&self);
+    /// Informative message, for ex, which source path is being copied.
+    fn message(&self, message: &str);
+}
+
+impl<P: Progress + ?Sized> Progress for Box<P> {
+    fn start(&self, length: u64) { (**self).start(length) }
+    fn inc(&self) { (**self).inc() }
+    fn finish(&self) { (**self).finish() }
+    fn message(&self, message: &str) { (**self).message(message) }
+}
+
+///
+/// Only logs messages, see [Progress].
+///
+#[derive(Default, Debug, Clone, Copy)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _length: u64) {}
+    fn inc(&self) {}
+    fn finish(&self) {}
+    fn message(&self, message: &str) {
+        info!("{}", message);
+    }
+}
+
+///
+/// Shows a terminal progress bar for each step, see [build_progress_bar].
+///
+#[derive(Default, Debug)]
+pub struct TerminalProgress {
+    progress_bar: Mutex<Option<ProgressBar>>,
+}
+
+impl Progress for TerminalProgress {
+    fn start(&self, length: u64) {
+        let progress_bar = build_progress_bar();
+        progress_bar.set_length(length);
+        if let Some(previous) = self.progress_bar.lock().unwrap().replace(progress_bar) {
+            previous.finish();
+        }
+    }
+
+    fn inc(&self) {
+        if let Some(progress_bar) = self.progress_bar.lock().unwrap().as_ref() {
+            progress_bar.inc(1);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(progress_bar) = self.progress_bar.lock().unwrap().take() {
+            progress_bar.finish();
+        }
+    }
+
+    fn message(&self, message: &str) {
+        match self.progress_bar.lock().unwrap().as_ref() {
+            Some(progress_bar) => progress_bar.println(message),
+            None => info!("{}", message),
+        }
+    }
+}
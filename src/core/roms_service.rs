@@ -1,20 +1,29 @@
-use std::error::Error;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::fs::read_dir;
+use std::fs::{read_dir, ReadDir};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Instant;
 
-use log::error;
+use log::{error, warn};
 use roxmltree::{Document, Node};
 
-use crate::core::args::Args;
+use crate::core::container::ContainerKind;
+use crate::core::curator::{CategorizeOptions, CopyOptions};
+use crate::core::feature_rules::FeatureRules;
+use crate::core::identify::{RomIndex, UnknownFilesAction};
+use crate::core::export::gamelist::GAMELIST_FILE_NAME;
+use crate::core::progress::Progress;
+use crate::core::scanner::{is_hidden_or_system, is_rom_dir, rom_name, scan_source};
+use crate::core::torrentzip::torrentzip;
+use crate::error::{CuratorError, CuratorResult};
 use crate::models::destination_folders::DestinationFolders;
-use crate::models::report::{Report, ReportDetailEntry};
-use crate::models::roms::{Chd, ChdStatus, EXCLUDED_CATEGORIES, Feature, FeatureStatus, Rom, RomCategory, RomData, RomDataExt, Roms, RomStatus, SPECIAL_CASES_DEMOTE, SPECIAL_CASES_PROMOTE, Status, UnfilteredRoms};
+use crate::models::report::{Report, ReportDetailEntry, UnknownFile, UnrecognizedValue};
+use crate::models::roms::{CategoryReason, Chd, ChdStatus, Control, Display, EXCLUDED_CATEGORIES, Feature, Input, Rom, RomCategory, RomFile, RomData, RomDataExt, Roms, RomStatus, SPECIAL_CASES_DEMOTE, SPECIAL_CASES_PROMOTE, Sound, Status, UnfilteredRoms, UnknownValuePolicy};
 use crate::RomCategories;
-use crate::utils::{build_progress_bar, copy_dir_recursive, ProgressBarEx};
+use crate::utils::{copy_dir_recursive, move_path, path_size};
 
-pub fn parse(doc: Document, categories: RomCategories) -> Result<UnfilteredRoms, Box<dyn Error>> {
+pub fn parse(doc: Document, categories: RomCategories) -> CuratorResult<UnfilteredRoms> {
     let mut roms = UnfilteredRoms::new();
 
     // go through mame xml doc to build categorized collection with all roms
@@ -23,7 +32,7 @@ pub fn parse(doc: Document, categories: RomCategories) -> Result<UnfilteredRoms,
 
             let name = match node.attribute("name") {
                 Some(value) => value.to_string(),
-                None => return Err("Machine with no name!!! Probably something wrong with file, aborting...".into())
+                None => return Err(CuratorError::Xml("machine with no name, probably something wrong with the file.".to_string()))
             };
 
             let is_bios = is_bios(node);
@@ -40,17 +49,23 @@ pub fn parse(doc: Document, categories: RomCategories) -> Result<UnfilteredRoms,
             let rom_of = node.attribute("romof")
                 .map(|value| value.to_string());
 
-            let status = extract_status(node);
+            let status = extract_status(node, &name)?;
 
-            let features = extract_features(node);
+            let features = extract_features(node, &name)?;
 
             let chd = extract_chd(node);
 
+            let rom_files = extract_rom_files(node);
+
             let category = match categories.get(&name) {
                 Some(v) => v.to_string(),
                 _ => "".to_string()
             };
 
+            let source_file = node.attribute("sourcefile")
+                .unwrap_or_default()
+                .to_string();
+
             roms.insert(
                 name,
                 RomData {
@@ -62,7 +77,15 @@ pub fn parse(doc: Document, categories: RomCategories) -> Result<UnfilteredRoms,
                     clone_of,
                     rom_of,
                     chd,
+                    rom_files,
                     category,
+                    description: extract_text(node, "description"),
+                    year: extract_text(node, "year"),
+                    manufacturer: extract_text(node, "manufacturer"),
+                    source_file,
+                    display: extract_display(node),
+                    sound: extract_sound(node),
+                    input: extract_input(node),
                 },
             );
         }
@@ -72,53 +95,88 @@ pub fn parse(doc: Document, categories: RomCategories) -> Result<UnfilteredRoms,
 }
 
 pub trait UnfilteredRomsExt {
-    fn categorize_roms(self) -> Result<Roms, Box<dyn Error>>;
+    /// Same as [categorize_roms_with](UnfilteredRomsExt::categorize_roms_with) with the default [CategorizeOptions].
+    fn categorize_roms(self) -> CuratorResult<Roms>;
+    ///
+    /// Categorizes all roms, features are checked with [CategorizeOptions::feature_rules] and machines
+    /// with values unknown to this version, see [unknown_values](RomData::unknown_values), are categorized
+    /// according to [CategorizeOptions::unknown_value_policy].
+    ///
+    /// @return An [UnknownValue](CuratorError::UnknownValue) error if the policy is [Fail](UnknownValuePolicy::Fail)
+    /// and there are unknown values.
+    ///
+    fn categorize_roms_with(self, options: &CategorizeOptions) -> CuratorResult<Roms>;
 }
 
 impl UnfilteredRomsExt for UnfilteredRoms {
-    fn categorize_roms(self) -> Result<Roms, Box<dyn Error>> {
+    fn categorize_roms(self) -> CuratorResult<Roms> {
+        self.categorize_roms_with(&CategorizeOptions::default())
+    }
+
+    fn categorize_roms_with(self, options: &CategorizeOptions) -> CuratorResult<Roms> {
+        let policy = options.unknown_value_policy;
         let mut roms = Roms::new();
+        let mut unknown_count = 0;
 
         // First split roms into categories
-        self.into_iter().for_each(|(name, data)| {
+        for (name, data) in self {
+            let unknown = data.unknown_values().first()
+                .map(|(field, value)| (*field, value.to_string()));
+            if unknown.is_some() { unknown_count += 1; }
+
             let categorized_rom =
 
                 if data.is_bios {
                     (name, data.to_bios_rom())
                 } else if data.is_system {
                     (name, data.to_system_rom())
-                } else {
-                    let status = data.status.clone().unwrap();
-
-                    if data.is_mechanical
-                        || matches!(status.driver, Status::Preliminary)
-                        || SPECIAL_CASES_DEMOTE.contains(&name)
-                        || check_features_status(&name, &data.features) {
-                        (name, data.to_not_working_rom())
+                } else if let Some((field, value)) = unknown.filter(|_| policy != UnknownValuePolicy::Working) {
+                    let reason = CategoryReason::UnknownValue(format!("{field} '{value}'"));
+                    match policy {
+                        UnknownValuePolicy::Fail => return Err(CuratorError::UnknownValue { field, value, machine: name }),
+                        UnknownValuePolicy::UnCategorized => (name, data.to_uncategorized_rom(reason)),
+                        _ => (name, data.to_not_working_rom(reason)),
+                    }
+                } else if let Some(status) = data.status.clone() {
+                    if data.is_mechanical {
+                        (name, data.to_not_working_rom(CategoryReason::Mechanical))
+                    } else if matches!(status.driver, Status::Preliminary) {
+                        (name, data.to_not_working_rom(CategoryReason::PreliminaryDriver))
+                    } else if SPECIAL_CASES_DEMOTE.contains(&name) {
+                        (name, data.to_not_working_rom(CategoryReason::SpecialCase))
+                    } else if let Some(reason) = check_features_status(&name, &data.features, &options.feature_rules) {
+                        (name, data.to_not_working_rom(reason))
                     } else {
+                        let reason = CategoryReason::Emulation(status.emulation.clone());
                         match status.emulation {
                             Status::Imperfect |
-                            Status::Good => (name, data.to_working_rom()),
-                            _ => (name, data.to_not_working_rom())
+                            Status::Good |
+                            // only with UnknownValuePolicy::Working
+                            Status::Unknown(_) => (name, data.to_working_rom(reason)),
+                            _ => (name, data.to_not_working_rom(reason))
                         }
                     }
+                } else {
+                    (name, data.to_not_working_rom(CategoryReason::NoDriverStatus))
                 };
 
             roms.insert(categorized_rom.0.clone(), categorized_rom.1);
-        });
+        }
+
+        if unknown_count > 0 {
+            warn!("{unknown_count} machines have values unknown to this version, categorized as {policy}.");
+        }
 
         // Re-assign categories based on rom dependencies
         let to_demote = check_roms_dependency(&roms);
 
-        to_demote.iter().for_each(|name| {
-            roms.remove(name)
-                .and_then(|rom| {
-                    if matches!(rom.category, RomCategory::Working) {
-                        roms.insert(name.clone(), Rom { data: rom.data, category: RomCategory::NotWorking })
-                    } else {
-                        None
-                    }
-                });
+        to_demote.into_iter().for_each(|(name, reason)| {
+            if let Some(rom) = roms.get_mut(&name) {
+                if matches!(rom.category, RomCategory::Working) {
+                    rom.category = RomCategory::NotWorking;
+                    rom.reason = reason;
+                }
+            }
         });
 
         Ok(roms)
@@ -127,123 +185,149 @@ impl UnfilteredRomsExt for UnfilteredRoms {
 
 pub trait RomsExt {
     ///
-    /// Copies all roms in [Roms](Roms) to the specified destination directory in
-    /// [Config](Config).
+    /// Copies all roms in [Roms](Roms) from the source paths to the destination
+    /// directory set in [CopyOptions].
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// # use std::process;
-    /// # use log::error;
-    /// use roms_curator::core::args::build_args;
+    /// use std::path::PathBuf;
+    /// use roms_curator::core::curator::{CopyOptions, Curator};
+    /// use roms_curator::core::progress::NoProgress;
     /// use roms_curator::core::roms_service::RomsExt;
-    /// use roms_curator::utils::build_progress_bar;
     ///
-    /// let args = build_args().unwrap_or_else(|err| {
-    ///     error!("Application error: {err}");
-    ///     process::exit(1);
-    /// });
+    /// let roms = Curator::builder()
+    ///     .mame_xml_path("/mame/mame.xml")
+    ///     .catver_path("/mame/catver.ini")
+    ///     .build()?
+    ///     .categorize()?;
     ///
-    /// let roms = roms_curator::run(&args).unwrap_or_else(|err| {
-    ///     error!("Needs a proper Config");
-    ///     process::exit(1);
-    /// });
-    ///
-    /// let report = roms.copy_roms(&args).unwrap_or_else(|err| {
-    ///     error!("Application error: {err}");
-    ///     process::exit(1);
-    /// });
+    /// let options = CopyOptions {
+    ///     ignore_not_working_chd: true,
+    ///     ..CopyOptions::new(vec![PathBuf::from("/roms")], PathBuf::from("/roms-new"))
+    /// };
+    /// let report = roms.copy_roms(&options, &NoProgress)?;
+    /// # Ok::<(), roms_curator::error::CuratorError>(())
     /// ```
     ///
     /// @return A [Report](Report) of all that was and/or was not copied.
     ///
-    fn copy_roms(self, args: &Args) -> Result<Report, Box<dyn Error>>;
-    fn check_paths(args: &Args) -> Result<bool, &'static str>;
+    fn copy_roms(&self, options: &CopyOptions, progress: &dyn Progress) -> CuratorResult<Report>;
+    fn upgrade_roms(&self, options: &CopyOptions, progress: &dyn Progress) -> CuratorResult<Report>;
+    fn check_paths(options: &CopyOptions) -> CuratorResult<bool>;
     fn get_destination_folder(rom: &Rom, destination_folders: &DestinationFolders) -> PathBuf;
-    fn should_move(rom: &Rom, args: &Args) -> bool;
-    fn copy_rom(path: &Path, destination: &Path, args: &Args) -> bool;
-    fn is_excluded(args: &Args, file_prefix: &str) -> bool;
+    fn should_move(rom: &Rom, options: &CopyOptions) -> bool;
+    fn copy_rom(path: &Path, destination: &Path, options: &CopyOptions) -> CuratorResult<u64>;
+    fn move_rom(path: &Path, destination: &Path, options: &CopyOptions) -> CuratorResult<u64>;
+    fn is_excluded(options: &CopyOptions, rom_name: &str) -> bool;
 }
 
 impl RomsExt for Roms {
-    fn copy_roms(self, args: &Args) -> Result<Report, Box<dyn Error>> {
-        Self::check_paths(args)?;
-
-        let destination_paths = args.build_destination_folders_path();
+    fn copy_roms(&self, options: &CopyOptions, progress: &dyn Progress) -> CuratorResult<Report> {
+        Self::check_paths(options)?;
 
-        let mut total_working = 0;
-        let mut total_other = 0;
-        let mut something_failed = false;
         let mut report = Report::new();
+        let all_ok = copy_from_sources(self, options, progress, &HashSet::new(), &mut report)?;
+
+        report
+            .source_dir(join_paths(&options.source_paths))
+            .destination_dir(options.destination_path.to_string_lossy().to_string())
+            .unrecognized_values(unrecognized_values(self))
+            .all_ok(all_ok)
+            .build();
 
-        for source_path in args.source_path.clone() {
-            let progress_bar = if args.progress { Some(build_progress_bar()) } else { None };
+        Ok(report)
+    }
 
-            progress_bar.println(format!("Copying from source: {}", &source_path).as_str());
+    fn upgrade_roms(&self, options: &CopyOptions, progress: &dyn Progress) -> CuratorResult<Report> {
+        Self::check_paths(options)?;
 
-            let nr_of_files = read_dir(&source_path)?.count();
-            if let Some(pb) = progress_bar.as_ref() {
-                pb.set_length(nr_of_files as u64);
-            }
+        let destination_paths = options.destination_folders()?;
+
+        let mut report = Report::new();
+        let mut something_failed = false;
 
-            for entry in read_dir(source_path)? {
+        // collect first, roms are moved between the folders being read
+        let mut existing_paths: Vec<PathBuf> = Vec::new();
+        for folder in [&destination_paths.working, &destination_paths.other, &destination_paths.chd_working, &destination_paths.chd_other] {
+            for entry in read_dir_at(folder)? {
                 let path = entry?.path();
-                let (file_prefix, file_name) = (
-                    path.file_stem().unwrap().to_str().unwrap(),
-                    path.file_name().unwrap().to_str().unwrap()
-                );
-
-                progress_bar.inc();
-
-                if Self::is_excluded(args, file_prefix) { continue; }
-
-                if let Some(rom) = self.get(&file_prefix.to_ascii_lowercase()) {
-                    if Self::should_move(rom, args) {
-                        let destination =
-                            Self::get_destination_folder(rom, &destination_paths)
-                                .join(file_name);
-
-                        let moved = Self::copy_rom(&path, &destination, args);
-                        if !moved { something_failed = true };
-
-                        let report_detail_entry = ReportDetailEntry { rom_name: file_name.to_string(), moved, is_chd: !rom.data.chd.is_empty() };
-
-                        match &rom.category {
-                            RomCategory::Working => {
-                                total_working += 1;
-                                report.add_rom_working(report_detail_entry)
-                            }
-                            _ => {
-                                total_other += 1;
-                                report.add_rom_other(report_detail_entry)
-                            }
-                        };
-                    }
-                } else {
-                    let report_detail_entry = ReportDetailEntry {
-                        rom_name: file_name.to_string(),
-                        moved: false, // doesn't matter here
-                        is_chd: false, // doesn't matter here
-                    };
-                    report.add_ignored_rom(report_detail_entry);
+                let Some(name) = path.file_name() else { continue; };
+                if name == GAMELIST_FILE_NAME || is_hidden_or_system(name) { continue; }
+                existing_paths.push(path);
+            }
+        }
+
+        let mut existing: HashSet<String> = HashSet::new();
+        for path in existing_paths {
+            let (rom_name, file_name) = match file_names(&path) {
+                Ok(names) => names,
+                Err(err) => {
+                    report.add_ignored_rom(ignored_entry(&path, Some(err)));
+                    continue;
                 }
             };
+            existing.insert(file_name.to_ascii_lowercase());
 
-            if let Some(pb) = progress_bar { pb.finish(); }
-        };
+            let Some(rom) = self.get(&rom_name) else {
+                // not in this mame version anymore, left where it is
+                report.add_ignored_rom(ignored_entry(&path, None));
+                continue;
+            };
+
+            let destination = Self::get_destination_folder(rom, &destination_paths).join(file_name);
+            if destination == path { continue; }
+
+            let started = Instant::now();
+            let (moved, bytes, error) = match Self::move_rom(&path, &destination, options) {
+                Ok(bytes) => (true, bytes, rewrite_zip(&destination, options)),
+                Err(err) => {
+                    error!("Error moving {:?}: {}", path, err);
+                    (false, 0, Some(err.to_string()))
+                }
+            };
+            if error.is_some() { something_failed = true; }
+
+            let report_detail_entry = ReportDetailEntry {
+                rom_name: file_name.to_string(),
+                moved,
+                is_chd: !rom.data.chd.is_empty(),
+                source_path: path.to_string_lossy().to_string(),
+                destination_path: destination.to_string_lossy().to_string(),
+                category: rom.category.to_string(),
+                reason: rom.reason.to_string(),
+                bytes,
+                duration_ms: started.elapsed().as_millis() as u64,
+                error,
+            };
+
+            match &rom.category {
+                RomCategory::Working => {
+                    report.total_working += 1;
+                    report.add_rom_working(report_detail_entry)
+                }
+                _ => {
+                    report.total_other += 1;
+                    report.add_rom_other(report_detail_entry)
+                }
+            };
+        }
+
+        let all_ok = copy_from_sources(self, options, progress, &existing, &mut report)?;
 
         report
-            .total_working(total_working)
-            .total_other(total_other)
-            .all_ok(!something_failed)
+            .source_dir(join_paths(&options.source_paths))
+            .destination_dir(options.destination_path.to_string_lossy().to_string())
+            .unrecognized_values(unrecognized_values(self))
+            .all_ok(all_ok && !something_failed)
             .build();
 
         Ok(report)
     }
 
-    fn check_paths(args: &Args) -> Result<bool, &'static str> {
-        if args.source_path.is_empty() { return Err("Missing roms source path."); }
-        if args.destination_path.is_empty() { return Err("Missing roms destination path."); }
+    fn check_paths(options: &CopyOptions) -> CuratorResult<bool> {
+        if options.source_paths.is_empty() { return Err(CuratorError::path("", "Missing roms source path")); }
+        if options.destination_path.as_os_str().is_empty() { return Err(CuratorError::path("", "Missing roms destination path")); }
         Ok(true)
     }
 
@@ -271,8 +355,8 @@ impl RomsExt for Roms {
         }
     }
 
-    fn should_move(rom: &Rom, args: &Args) -> bool {
-        if !args.ignore_not_working_chd { return true; }
+    fn should_move(rom: &Rom, options: &CopyOptions) -> bool {
+        if !options.ignore_not_working_chd { return true; }
 
         let is_chd = !rom.data.chd.is_empty();
 
@@ -283,49 +367,317 @@ impl RomsExt for Roms {
         true
     }
 
-    fn copy_rom(path: &Path, destination: &Path, args: &Args) -> bool {
-        if args.simulation { return true; };
+    ///
+    /// @return Number of bytes copied, or that would be copied in simulation mode.
+    ///
+    fn copy_rom(path: &Path, destination: &Path, options: &CopyOptions) -> CuratorResult<u64> {
+        if options.simulation { return Ok(path_size(path)?); };
 
         if path.is_dir() {
-            if let Some(err) = copy_dir_recursive(path, destination).err() {
-                error!("Error copying {:?}: {}", path, err);
-                return false;
+            Ok(copy_dir_recursive(path, destination)?)
+        } else {
+            Ok(fs::copy(path, destination)?)
+        }
+    }
+
+    ///
+    /// @return Number of bytes moved, or that would be moved in simulation mode.
+    ///
+    fn move_rom(path: &Path, destination: &Path, options: &CopyOptions) -> CuratorResult<u64> {
+        // never replace a rom already there, for ex, the same set left in both working and other
+        if destination.exists() {
+            return Err(CuratorError::path(destination, "Error moving, destination already exists"));
+        }
+        let bytes = path_size(path)?;
+        if !options.simulation { move_path(path, destination)?; }
+        Ok(bytes)
+    }
+
+    fn is_excluded(options: &CopyOptions, rom_name: &str) -> bool {
+        let rom_name = rom_name.to_ascii_lowercase();
+        options.subset_start.as_ref().is_some_and(|start| rom_name < start.to_ascii_lowercase()) ||
+            options.subset_end.as_ref().is_some_and(|end| rom_name > end.to_ascii_lowercase())
+    }
+}
+
+///
+/// Copies roms from all source paths, scanned with [scan_source], to their destination folder,
+/// skipping files whose (lowercase) name is in `skip`. Files that don't match any machine are
+/// reported as ignored, or copied to the `unknown` folder, see [copy_unknown_file]. Directories
+/// not recognised as rom sets are always reported as ignored.
+///
+/// @return false if any copy failed.
+///
+fn copy_from_sources(
+    roms: &Roms,
+    options: &CopyOptions,
+    progress: &dyn Progress,
+    skip: &HashSet<String>,
+    report: &mut Report,
+) -> CuratorResult<bool> {
+    let destination_paths = options.destination_folders()?;
+    let index = (options.unknown_files != UnknownFilesAction::Keep).then(|| RomIndex::new(roms));
+
+    let mut something_failed = false;
+
+    for source_path in &options.source_paths {
+        let paths = scan_source(roms, source_path, &options.scan)?;
+        progress.start(paths.len() as u64);
+
+        progress.message(format!("Copying from source: {}", source_path.display()).as_str());
+
+        for path in paths {
+            progress.inc();
+
+            let (rom_name, file_name) = match file_names(&path) {
+                Ok(names) => names,
+                Err(err) => {
+                    report.add_ignored_rom(ignored_entry(&path, Some(err)));
+                    continue;
+                }
+            };
+
+            if Roms::is_excluded(options, &rom_name) { continue; }
+
+            if skip.contains(&file_name.to_ascii_lowercase()) { continue; }
+
+            // only zip, 7z and folders are roms, for ex, robocop.txt is not
+            let rom = roms.get(&rom_name).filter(|_| ContainerKind::from_path(&path).is_some());
+
+            if let Some(rom) = rom {
+                if options.filter.as_ref().is_some_and(|filter| !filter.matches(&rom_name, rom)) { continue; }
+
+                if Roms::should_move(rom, options) {
+                    let destination =
+                        Roms::get_destination_folder(rom, &destination_paths)
+                            .join(file_name);
+
+                    let started = Instant::now();
+                    let (moved, bytes, error) = match Roms::copy_rom(&path, &destination, options) {
+                        Ok(bytes) => (true, bytes, rewrite_zip(&destination, options)),
+                        Err(err) => {
+                            error!("Error copying {:?}: {}", path, err);
+                            (false, 0, Some(err.to_string()))
+                        }
+                    };
+                    if error.is_some() { something_failed = true; }
+
+                    let report_detail_entry = ReportDetailEntry {
+                        rom_name: file_name.to_string(),
+                        moved,
+                        is_chd: !rom.data.chd.is_empty(),
+                        source_path: path.to_string_lossy().to_string(),
+                        destination_path: destination.to_string_lossy().to_string(),
+                        category: rom.category.to_string(),
+                        reason: rom.reason.to_string(),
+                        bytes,
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        error,
+                    };
+
+                    match &rom.category {
+                        RomCategory::Working => {
+                            report.total_working += 1;
+                            report.add_rom_working(report_detail_entry)
+                        }
+                        _ => {
+                            report.total_other += 1;
+                            report.add_rom_other(report_detail_entry)
+                        }
+                    };
+                }
+            } else {
+                // organisational directories, for ex, `mame/`, are left where they are, with the roms in them
+                match &index {
+                    Some(index) if !path.is_dir() || is_rom_dir(roms, &path)? => {
+                        let unknown_file = copy_unknown_file(&path, file_name, options, &destination_paths, index)?;
+                        if !unknown_file.entry.moved { something_failed = true; }
+                        report.add_unknown_file(unknown_file);
+                    }
+                    _ => {
+                        report.add_ignored_rom(ignored_entry(&path, None));
+                    }
+                }
             }
-        } else if let Some(err) = fs::copy(path, destination).err() {
-            error!("Error copying {:?}: {}", path, err);
-            return false;
+        };
+
+        progress.finish();
+    };
+
+    Ok(!something_failed)
+}
+
+///
+/// Rewrites the zip copied to `destination` in deterministic form, if [CopyOptions::torrentzip].
+///
+/// @return Why the rewrite failed, the zip is then left as it was copied.
+///
+fn rewrite_zip(destination: &Path, options: &CopyOptions) -> Option<String> {
+    if !options.torrentzip || options.simulation { return None; }
+    if ContainerKind::from_path(destination) != Some(ContainerKind::Zip) { return None; }
+
+    torrentzip(destination).err().map(|err| {
+        error!("Error rewriting {:?} in deterministic form: {}", destination, err);
+        err.to_string()
+    })
+}
+
+///
+/// Copies or moves, according to [CopyOptions::unknown_files], a source file that doesn't match any
+/// machine to the `unknown` folder. Containers are identified with `index` and, if
+/// [CopyOptions::rename_identified], renamed after the machine they were identified as.
+///
+fn copy_unknown_file(
+    path: &Path,
+    file_name: &str,
+    options: &CopyOptions,
+    destination_paths: &DestinationFolders,
+    index: &RomIndex,
+) -> CuratorResult<UnknownFile> {
+    let identification = match ContainerKind::from_path(path) {
+        Some(_) => index.identify(path).unwrap_or_else(|err| {
+            warn!("Error identifying {:?}: {}", path, err);
+            None
+        }),
+        None => None,
+    };
+
+    let file_name = match &identification {
+        Some(identification) if options.rename_identified => match Path::new(file_name).extension() {
+            Some(extension) if !path.is_dir() => format!("{}.{}", identification.machine, extension.to_string_lossy()),
+            _ => identification.machine.clone(),
+        },
+        _ => file_name.to_string(),
+    };
+    // a simulation never touches the destination
+    let unknown_folder = if options.simulation {
+        destination_paths.unknown.as_path()
+    } else {
+        destination_paths.create_unknown()?
+    };
+    // never replace a file already there, it might have been moved from another source
+    let destination = unused_destination(unknown_folder, &file_name, path.is_dir());
+    let file_name = destination.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    let started = Instant::now();
+    let result = match options.unknown_files {
+        UnknownFilesAction::Move => Roms::move_rom(path, &destination, options),
+        _ => Roms::copy_rom(path, &destination, options),
+    };
+    let (moved, bytes, error) = match result {
+        Ok(bytes) => (true, bytes, None),
+        Err(err) => {
+            error!("Error copying unknown file {:?}: {}", path, err);
+            (false, 0, Some(err.to_string()))
         }
+    };
 
-        true
+    Ok(UnknownFile {
+        entry: ReportDetailEntry {
+            rom_name: file_name,
+            moved,
+            source_path: path.to_string_lossy().to_string(),
+            destination_path: destination.to_string_lossy().to_string(),
+            bytes,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error,
+            ..Default::default()
+        },
+        matched_roms: identification.as_ref().map_or(0, |identification| identification.matched),
+        total_roms: identification.as_ref().map_or(0, |identification| identification.total),
+        identified_as: identification.map(|identification| identification.machine),
+    })
+}
+
+///
+/// @return `folder/file_name`, or `folder/name (n).ext` with the lowest `n` not taken if it exists.
+///
+fn unused_destination(folder: &Path, file_name: &str, is_dir: bool) -> PathBuf {
+    let destination = folder.join(file_name);
+    if !destination.exists() { return destination; }
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && !is_dir => (stem, format!(".{extension}")),
+        _ => (file_name, String::new()),
+    };
+    let mut number = 1;
+    loop {
+        let destination = folder.join(format!("{stem} ({number}){extension}"));
+        if !destination.exists() { return destination; }
+        number += 1;
     }
+}
+
+pub(crate) fn read_dir_at(path: &Path) -> CuratorResult<ReadDir> {
+    read_dir(path).map_err(|err| CuratorError::path(path, &format!("Error reading directory ({err})")))
+}
+
+///
+/// @return [Rom name](rom_name) and file name of `path`,
+/// an [Encoding](CuratorError::Encoding) error if they are not valid UTF-8.
+///
+fn file_names(path: &Path) -> CuratorResult<(String, &str)> {
+    let rom_name = rom_name(path)?;
+    let file_name = path.file_name().and_then(|name| name.to_str())
+        .ok_or_else(|| CuratorError::Encoding(path.to_path_buf()))?;
+    Ok((rom_name, file_name))
+}
 
-    fn is_excluded(args: &Args, file_prefix: &str) -> bool {
-        (!args.subset_start.is_empty() && file_prefix < args.subset_start.as_str()) ||
-            (!args.subset_end.is_empty() && file_prefix > args.subset_end.as_str())
+fn ignored_entry(path: &Path, error: Option<CuratorError>) -> ReportDetailEntry {
+    ReportDetailEntry {
+        rom_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        source_path: path.to_string_lossy().to_string(),
+        error: error.map(|err| err.to_string()),
+        ..Default::default()
     }
 }
 
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths.iter().map(|path| path.to_string_lossy()).collect::<Vec<_>>().join(",")
+}
+
+///
+/// @return Values unknown to this version and the machines that have them, sorted by field and value.
+///
+pub fn unrecognized_values(roms: &Roms) -> Vec<UnrecognizedValue> {
+    let mut values: BTreeMap<(&str, &str), Vec<String>> = BTreeMap::new();
+    roms.iter().for_each(|(name, rom)| {
+        rom.data.unknown_values().into_iter().for_each(|key| {
+            let machines = values.entry(key).or_default();
+            if !machines.contains(name) { machines.push(name.clone()) }
+        })
+    });
+
+    values.into_iter()
+        .map(|((field, value), mut machines)| {
+            machines.sort();
+            UnrecognizedValue { field: field.to_string(), value: value.to_string(), machines }
+        })
+        .collect()
+}
+
 ///
 /// A good rom might dependent on a bad rom or chd file, in this case
 /// we need to re-classify the good rom as a bad rom
 ///
-fn check_roms_dependency(roms: &Roms) -> Vec<String> {
-    let mut demote_working: Vec<String> = Vec::new();
+fn check_roms_dependency(roms: &Roms) -> Vec<(String, CategoryReason)> {
+    let mut demote_working: Vec<(String, CategoryReason)> = Vec::new();
 
     roms.iter()
         .filter(|(_, rom)| matches!(rom.category, RomCategory::Working))
         .for_each(|(name, rom)| {
             if rom.data.rom_of.is_some() && !(rom.data.clone_of.is_some() &&
                 rom.data.rom_of.eq(&rom.data.clone_of)) {
-                let demote = should_demote_rom(rom.data.rom_of.clone().unwrap(), roms);
+                let rom_of = rom.data.rom_of.clone().unwrap();
+                let demote = should_demote_rom(rom_of.clone(), roms);
                 if demote {
-                    demote_working.push(name.clone());
+                    demote_working.push((name.clone(), CategoryReason::DependsOn(rom_of)));
                 }
             } else if !rom.data.chd.is_empty() {
                 rom.data.chd.iter().for_each(|chd| {
                     match chd.status {
-                        ChdStatus::BadDump { .. } => { demote_working.push(name.clone()) }
-                        ChdStatus::NoDump { .. } => { demote_working.push(name.clone()) }
+                        ChdStatus::BadDump |
+                        ChdStatus::NoDump => { demote_working.push((name.clone(), CategoryReason::BadChd(chd.name.clone()))) }
                         _ => ()
                     }
                 })
@@ -339,7 +691,7 @@ fn should_demote_rom(rom_of: String, roms: &Roms) -> bool {
     let found = roms.get(rom_of.as_str());
     if let Some(rom) = found {
         match rom.category {
-            RomCategory::Working => if rom.data.rom_of.is_some() {
+            RomCategory::Working if rom.data.rom_of.is_some() => {
                 should_demote_rom(rom.data.rom_of.clone().unwrap(), roms);
             }
             RomCategory::System |
@@ -361,22 +713,15 @@ fn should_demote_rom(rom_of: String, roms: &Roms) -> bool {
 }
 
 ///
-/// @return true if contains bad feature status, false otherwise.
+/// @return Why the features make the machine not working, if they do.
 ///
-fn check_features_status(name: &String, features: &[Feature]) -> bool {
-    if SPECIAL_CASES_PROMOTE.contains(name) { return false; }
+fn check_features_status(name: &String, features: &[Feature], rules: &FeatureRules) -> Option<CategoryReason> {
+    if SPECIAL_CASES_PROMOTE.contains(name) { return None; }
 
-    let mut invalid: u32 = 0;
-    features.iter().for_each(|feature| {
-        if feature.typ == "sound" || feature.typ == "graphics" {
-            invalid += 1;
-        }
-    });
-
-    invalid > 1
+    rules.not_working_reason(features)
 }
 
-fn extract_status(node: Node) -> Option<RomStatus> {
+fn extract_status(node: Node, name: &str) -> CuratorResult<Option<RomStatus>> {
     let mut driver_status: &str = "";
     let mut emulation_status: &str = "";
 
@@ -392,17 +737,17 @@ fn extract_status(node: Node) -> Option<RomStatus> {
     }
 
     if driver_status.is_empty() || emulation_status.is_empty() {
-        None
+        Ok(None)
     } else {
         let rom_status = RomStatus {
-            driver: Status::from_str(driver_status).unwrap(),
-            emulation: Status::from_str(emulation_status).unwrap(),
+            driver: parse_value(driver_status, "driver status", name)?,
+            emulation: parse_value(emulation_status, "emulation status", name)?,
         };
-        Some(rom_status)
+        Ok(Some(rom_status))
     }
 }
 
-fn extract_features(node: Node) -> Vec<Feature> {
+fn extract_features(node: Node, name: &str) -> CuratorResult<Vec<Feature>> {
     let mut feature_type: &str = "";
     let mut feature_status: &str = "";
     let mut features: Vec<Feature> = Vec::new();
@@ -418,14 +763,22 @@ fn extract_features(node: Node) -> Vec<Feature> {
                 feature_status = overall
             }
             let feature = Feature {
-                typ: feature_type.to_string(),
-                status: FeatureStatus::from_str(feature_status).unwrap(),
+                typ: parse_value(feature_type, "feature type", name)?,
+                status: parse_value(feature_status, "feature status", name)?,
             };
             features.push(feature)
         }
     }
 
-    features
+    Ok(features)
+}
+
+fn parse_value<T: FromStr>(value: &str, field: &'static str, machine: &str) -> CuratorResult<T> {
+    T::from_str(value).map_err(|_| CuratorError::UnknownValue {
+        field,
+        value: value.to_string(),
+        machine: machine.to_string(),
+    })
 }
 
 fn extract_chd(node: Node) -> Vec<Chd> {
@@ -443,7 +796,10 @@ fn extract_chd(node: Node) -> Vec<Chd> {
             }
             let chd = Chd {
                 name: chd_name.to_string(),
-                status: ChdStatus::from_str(chd_status).unwrap_or(ChdStatus::NoStatus),
+                status: match chd_status {
+                    "" => ChdStatus::NoStatus,
+                    status => ChdStatus::from_str(status).unwrap_or(ChdStatus::NoStatus),
+                },
             };
             chd_vec.push(chd);
         }
@@ -452,26 +808,87 @@ fn extract_chd(node: Node) -> Vec<Chd> {
     chd_vec
 }
 
+fn extract_rom_files(node: Node) -> Vec<RomFile> {
+    node.children()
+        .filter(|machine_node| machine_node.tag_name().name() == "rom")
+        .map(|machine_node| RomFile {
+            name: machine_node.attribute("name").unwrap_or_default().to_string(),
+            size: parse_attribute(machine_node, "size").unwrap_or(0),
+            crc: machine_node.attribute("crc").map(|crc| crc.to_ascii_lowercase()),
+            sha1: machine_node.attribute("sha1").map(|sha1| sha1.to_ascii_lowercase()),
+            merge: machine_node.attribute("merge").map(|merge| merge.to_string()),
+        })
+        .collect()
+}
+
+fn extract_text(node: Node, tag_name: &str) -> String {
+    node.children()
+        .find(|machine_node| machine_node.tag_name().name() == tag_name)
+        .and_then(|machine_node| machine_node.text())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn extract_display(node: Node) -> Vec<Display> {
+    node.children()
+        .filter(|machine_node| machine_node.tag_name().name() == "display")
+        .map(|machine_node| Display {
+            typ: machine_node.attribute("type").unwrap_or("unknown").to_string(),
+            rotate: parse_attribute(machine_node, "rotate").unwrap_or(0),
+            width: parse_attribute(machine_node, "width"),
+            height: parse_attribute(machine_node, "height"),
+        })
+        .collect()
+}
+
+fn extract_sound(node: Node) -> Option<Sound> {
+    node.children()
+        .find(|machine_node| machine_node.tag_name().name() == "sound")
+        .map(|machine_node| Sound {
+            channels: parse_attribute(machine_node, "channels").unwrap_or(0),
+        })
+}
+
+fn extract_input(node: Node) -> Option<Input> {
+    node.children()
+        .find(|machine_node| machine_node.tag_name().name() == "input")
+        .map(|machine_node| Input {
+            players: parse_attribute(machine_node, "players").unwrap_or(0),
+            coins: parse_attribute(machine_node, "coins"),
+            controls: machine_node.children()
+                .filter(|input_node| input_node.tag_name().name() == "control")
+                .map(|input_node| Control {
+                    typ: input_node.attribute("type").unwrap_or_default().to_string(),
+                    player: parse_attribute(input_node, "player"),
+                    buttons: parse_attribute(input_node, "buttons"),
+                    ways: input_node.attribute("ways").map(|value| value.to_string()),
+                })
+                .collect(),
+        })
+}
+
+fn parse_attribute<T: FromStr>(node: Node, name: &str) -> Option<T> {
+    node.attribute(name).and_then(|value| value.parse().ok())
+}
+
 fn is_system(node: Node, name: &str, categories: &RomCategories) -> bool {
     if is_device(node) { return true; };
 
     let mut has_device = false;
     let mut requires_chd = false;
 
-    return {
-        match categories.get(name) {
-            Some(v) => EXCLUDED_CATEGORIES.iter()
-                .any(|cat| v.contains(cat)),
-            _ => {
-                // couldn't match category, determine by having device (void if entry is chd)
-                for machine_node in node.children() {
-                    if machine_node.tag_name().name() == "disk" { requires_chd = true; }
-                    if machine_node.tag_name().name() == "device" { has_device = true; }
-                }
-                has_device && !requires_chd
+    match categories.get(name) {
+        Some(v) => EXCLUDED_CATEGORIES.iter()
+            .any(|cat| v.contains(cat)),
+        _ => {
+            // couldn't match category, determine by having device (void if entry is chd)
+            for machine_node in node.children() {
+                if machine_node.tag_name().name() == "disk" { requires_chd = true; }
+                if machine_node.tag_name().name() == "device" { has_device = true; }
             }
+            has_device && !requires_chd
         }
-    };
+    }
 }
 
 fn is_bios(node: Node) -> bool {
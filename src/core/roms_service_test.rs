@@ -1,15 +1,19 @@
 use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
 
-use crate::{build_category_list, read_mame_xml};
-use crate::core::args::Args;
-use crate::core::roms_service::{parse, RomsExt, UnfilteredRomsExt};
-use crate::models::roms::{Chd, Feature, FeatureStatus, RomData, Roms, RomStatus, Status};
+use crate::{parse_category_list, read_mame_xml};
+use crate::core::curator::{CategorizeOptions, CopyOptions};
+use crate::core::roms_service::{parse, RomsExt, unrecognized_values, UnfilteredRomsExt};
+use crate::error::CuratorError;
+use crate::models::report::UnrecognizedValue;
+use crate::models::roms::{CategoryReason, Chd, ChdStatus, Control, Display, Feature, FeatureStatus, FeatureType, Input, RomData, RomFile, Roms, RomStatus, Sound, Status, UnknownValuePolicy};
 use crate::models::roms::ChdStatus::{BadDump, NoStatus};
-use crate::models::roms::RomCategory::{Bios, NotWorking, System, Working};
+use crate::models::roms::RomCategory::{Bios, NotWorking, System, UnCategorized, Working};
 
 #[test]
 fn should_properly_classify_roms() {
-    let rom_categories = build_category_list("tests/resources/catver_0244.ini".to_string()).unwrap();
+    let rom_categories = parse_category_list(&fs::read_to_string("tests/resources/catver_0244.ini").unwrap()).unwrap();
 
     let contents = fs::read_to_string("tests/resources/listxml_0244.xml").unwrap();
 
@@ -31,11 +35,26 @@ fn should_properly_classify_roms() {
         is_bios: false,
         is_system: false,
         is_mechanical: false,
-        features: vec! {Feature { typ: "sound".to_string(), status: FeatureStatus::Imperfect }},
+        features: vec! {Feature { typ: FeatureType::Sound, status: FeatureStatus::Imperfect }},
         clone_of: None,
         rom_of: None,
         chd: Vec::new(),
+        rom_files: working["005"].data.rom_files.clone(),
         category: "Maze / Shooter Small".to_string(),
+        description: "005".to_string(),
+        year: "1981".to_string(),
+        manufacturer: "Sega".to_string(),
+        source_file: "segag80r.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 270, width: Some(256), height: Some(224) }},
+        sound: Some(Sound { channels: 1 }),
+        input: Some(Input {
+            players: 2,
+            coins: Some(2),
+            controls: vec! {
+                Control { typ: "joy".to_string(), player: Some(1), buttons: Some(1), ways: Some("4".to_string()) },
+                Control { typ: "joy".to_string(), player: Some(2), buttons: Some(1), ways: Some("4".to_string()) },
+            },
+        }),
     };
     assert_eq!(working["005"].data, rom_005);
     assert!(matches!(working["005"].category, Working));
@@ -49,7 +68,22 @@ fn should_properly_classify_roms() {
         clone_of: Some("elevator".to_string()),
         rom_of: Some("elevator".to_string()),
         chd: Vec::new(),
+        rom_files: working["elevatora"].data.rom_files.clone(),
         category: "Platform / Shooter".to_string(),
+        description: "Elevator Action (EA, 5 PCB version, 1.1)".to_string(),
+        year: "1983".to_string(),
+        manufacturer: "Taito Corporation".to_string(),
+        source_file: "taitosj.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 0, width: Some(256), height: Some(224) }},
+        sound: Some(Sound { channels: 1 }),
+        input: Some(Input {
+            players: 2,
+            coins: Some(3),
+            controls: vec! {
+                Control { typ: "joy".to_string(), player: Some(1), buttons: Some(2), ways: Some("8".to_string()) },
+                Control { typ: "joy".to_string(), player: Some(2), buttons: Some(2), ways: Some("8".to_string()) },
+            },
+        }),
     };
     assert_eq!(working["elevatora"].data, rom_elevatora);
     assert!(matches!(working["elevatora"].category, Working ));
@@ -59,11 +93,26 @@ fn should_properly_classify_roms() {
         is_bios: false,
         is_system: false,
         is_mechanical: false,
-        features: vec! {Feature { typ: "sound".to_string(), status: FeatureStatus::Imperfect }},
+        features: vec! {Feature { typ: FeatureType::Sound, status: FeatureStatus::Imperfect }},
         clone_of: None,
         rom_of: None,
         chd: Vec::new(),
+        rom_files: working["robocop"].data.rom_files.clone(),
         category: "Platform / Shooter Scrolling".to_string(),
+        description: "Robocop (World revision 4)".to_string(),
+        year: "1988".to_string(),
+        manufacturer: "Data East Corporation".to_string(),
+        source_file: "dec0.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 0, width: Some(256), height: Some(240) }},
+        sound: Some(Sound { channels: 1 }),
+        input: Some(Input {
+            players: 2,
+            coins: Some(2),
+            controls: vec! {
+                Control { typ: "joy".to_string(), player: Some(1), buttons: Some(2), ways: Some("8".to_string()) },
+                Control { typ: "joy".to_string(), player: Some(2), buttons: Some(2), ways: Some("8".to_string()) },
+            },
+        }),
     };
     assert_eq!(working["robocop"].data, rom_robocop);
     assert!(matches!(working["robocop"].category, Working ));
@@ -77,7 +126,22 @@ fn should_properly_classify_roms() {
         clone_of: None,
         rom_of: None,
         chd: vec! {Chd { name: "area51".to_string(), status: NoStatus }},
+        rom_files: working["area51"].data.rom_files.clone(),
         category: "".to_string(),
+        description: "Area 51 (R3000)".to_string(),
+        year: "1996".to_string(),
+        manufacturer: "Atari Games".to_string(),
+        source_file: "jaguar.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 0, width: Some(360), height: Some(240) }},
+        sound: Some(Sound { channels: 2 }),
+        input: Some(Input {
+            players: 2,
+            coins: Some(4),
+            controls: vec! {
+                Control { typ: "lightgun".to_string(), player: Some(1), buttons: Some(1), ways: None },
+                Control { typ: "lightgun".to_string(), player: Some(2), buttons: Some(1), ways: None },
+            },
+        }),
     };
     assert_eq!(working["area51"].data, rom_area51);
     assert!(matches!(working["area51"].category, Working ));
@@ -89,11 +153,23 @@ fn should_properly_classify_roms() {
         is_bios: false,
         is_system: false,
         is_mechanical: false,
-        features: vec! {Feature { typ: "sound".to_string(), status: FeatureStatus::Unemulated }},
+        features: vec! {Feature { typ: FeatureType::Sound, status: FeatureStatus::Unemulated }},
         clone_of: None,
         rom_of: Some("aristmk6".to_string()),
         chd: Vec::new(),
+        rom_files: not_working["100lions"].data.rom_files.clone(),
         category: "Slot Machine / Video Slot".to_string(),
+        description: "100 Lions (10219211, NSW/ACT)".to_string(),
+        year: "2006".to_string(),
+        manufacturer: "Aristocrat".to_string(),
+        source_file: "aristmk6.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 0, width: Some(640), height: Some(480) }},
+        sound: Some(Sound { channels: 0 }),
+        input: Some(Input {
+            players: 0,
+            coins: None,
+            controls: Vec::new(),
+        }),
     };
     assert_eq!(not_working["100lions"].data, rom_100lions);
     assert!(matches!(not_working["100lions"].category, NotWorking ));
@@ -103,11 +179,23 @@ fn should_properly_classify_roms() {
         is_bios: true,
         is_system: true,
         is_mechanical: false,
-        features: vec! {Feature { typ: "sound".to_string(), status: FeatureStatus::Unemulated }},
+        features: vec! {Feature { typ: FeatureType::Sound, status: FeatureStatus::Unemulated }},
         clone_of: None,
         rom_of: None,
         chd: Vec::new(),
+        rom_files: not_working["aristmk6"].data.rom_files.clone(),
         category: "System / BIOS".to_string(),
+        description: "MK6 System Software/Setchips".to_string(),
+        year: "2000".to_string(),
+        manufacturer: "Aristocrat".to_string(),
+        source_file: "aristmk6.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 0, width: Some(640), height: Some(480) }},
+        sound: Some(Sound { channels: 0 }),
+        input: Some(Input {
+            players: 0,
+            coins: None,
+            controls: Vec::new(),
+        }),
     };
     assert_eq!(not_working["aristmk6"].data, rom_aristmk6);
     assert!(matches!(not_working["aristmk6"].category, Bios ));
@@ -121,7 +209,23 @@ fn should_properly_classify_roms() {
         clone_of: None,
         rom_of: None,
         chd: Vec::new(),
+        rom_files: not_working["a24play"].data.rom_files.clone(),
         category: "System / Device".to_string(),
+        description: "4play Joystick Card (rev. B)".to_string(),
+        year: "".to_string(),
+        manufacturer: "".to_string(),
+        source_file: "src/devices/bus/a2bus/4play.cpp".to_string(),
+        display: Vec::new(),
+        sound: None,
+        input: Some(Input {
+            players: 3,
+            coins: None,
+            controls: vec! {
+                Control { typ: "joy".to_string(), player: Some(1), buttons: Some(3), ways: Some("8".to_string()) },
+                Control { typ: "joy".to_string(), player: Some(2), buttons: Some(3), ways: Some("8".to_string()) },
+                Control { typ: "joy".to_string(), player: Some(3), buttons: Some(3), ways: Some("8".to_string()) },
+            },
+        }),
     };
     assert_eq!(not_working["a24play"].data, rom_a24play);
     assert!(matches!(not_working["a24play"].category, System ));
@@ -131,11 +235,25 @@ fn should_properly_classify_roms() {
         is_bios: true,
         is_system: true,
         is_mechanical: false,
-        features: vec! {Feature { typ: "sound".to_string(), status: FeatureStatus::Unemulated }},
+        features: vec! {Feature { typ: FeatureType::Sound, status: FeatureStatus::Unemulated }},
         clone_of: None,
         rom_of: None,
         chd: Vec::new(),
+        rom_files: not_working["3dobios"].data.rom_files.clone(),
         category: "System / BIOS".to_string(),
+        description: "3DO Bios".to_string(),
+        year: "1993".to_string(),
+        manufacturer: "The 3DO Company".to_string(),
+        source_file: "3do.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 0, width: Some(1280), height: Some(240) }},
+        sound: Some(Sound { channels: 0 }),
+        input: Some(Input {
+            players: 1,
+            coins: None,
+            controls: vec! {
+                Control { typ: "joy".to_string(), player: None, buttons: Some(3), ways: Some("8".to_string()) },
+            },
+        }),
     };
     assert_eq!(not_working["3dobios"].data, rom_3dobios);
     assert!(matches!(not_working["3dobios"].category, Bios ));
@@ -149,7 +267,15 @@ fn should_properly_classify_roms() {
         clone_of: None,
         rom_of: None,
         chd: Vec::new(),
+        rom_files: not_working["sv801"].data.rom_files.clone(),
         category: "System / Device".to_string(),
+        description: "SV-801 Disk Controller".to_string(),
+        year: "".to_string(),
+        manufacturer: "".to_string(),
+        source_file: "src/devices/bus/svi3x8/slot/sv801.cpp".to_string(),
+        display: Vec::new(),
+        sound: None,
+        input: None,
     };
     assert_eq!(not_working["sv801"].data, rom_sv801);
     assert!(matches!(not_working["sv801"].category, System ));
@@ -159,11 +285,25 @@ fn should_properly_classify_roms() {
         is_bios: false,
         is_system: false,
         is_mechanical: false,
-        features: vec! {Feature { typ: "sound".to_string(), status: FeatureStatus::Unemulated }},
+        features: vec! {Feature { typ: FeatureType::Sound, status: FeatureStatus::Unemulated }},
         clone_of: Some("gammagic".to_string()),
         rom_of: Some("gammagic".to_string()),
         chd: vec! {Chd { name: "99bottles".to_string(), status: BadDump }},
+        rom_files: not_working["99bottles"].data.rom_files.clone(),
         category: "MultiGame / Compilation".to_string(),
+        description: "99 Bottles of Beer".to_string(),
+        year: "1999".to_string(),
+        manufacturer: "Bally Gaming Co.".to_string(),
+        source_file: "gammagic.cpp".to_string(),
+        display: vec! {Display { typ: "raster".to_string(), rotate: 0, width: Some(640), height: Some(480) }},
+        sound: Some(Sound { channels: 0 }),
+        input: Some(Input {
+            players: 1,
+            coins: None,
+            controls: vec! {
+                Control { typ: "keyboard".to_string(), player: None, buttons: Some(103), ways: None },
+            },
+        }),
     };
     assert_eq!(not_working["99bottles"].data, rom_99bottles);
     assert!(matches!(not_working["99bottles"].category, NotWorking ));
@@ -173,39 +313,170 @@ fn should_properly_classify_roms() {
         is_bios: false,
         is_system: false,
         is_mechanical: true,
-        features: vec! {Feature { typ: "sound".to_string(), status: FeatureStatus::Unemulated }},
+        features: vec! {Feature { typ: FeatureType::Sound, status: FeatureStatus::Unemulated }},
         clone_of: None,
         rom_of: None,
         chd: Vec::new(),
+        rom_files: not_working["as_acp"].data.rom_files.clone(),
         category: "Slot Machine / Reels".to_string(),
+        description: "unknown Astra 'ACP' (Astra, V403)".to_string(),
+        year: "200?".to_string(),
+        manufacturer: "Astra".to_string(),
+        source_file: "astrafr.cpp".to_string(),
+        display: Vec::new(),
+        sound: Some(Sound { channels: 0 }),
+        input: Some(Input {
+            players: 0,
+            coins: None,
+            controls: Vec::new(),
+        }),
     };
     assert_eq!(not_working["as_acp"].data, rom_as_acp);
     assert!(matches!(not_working["as_acp"].category, NotWorking ));
 }
 
+#[test]
+fn should_extract_machine_information() {
+    let rom_categories = parse_category_list(&fs::read_to_string("tests/resources/catver_0244.ini").unwrap()).unwrap();
+    let contents = fs::read_to_string("tests/resources/listxml_0244.xml").unwrap();
+    let doc = read_mame_xml(&contents).unwrap();
+
+    let roms = parse(doc, rom_categories).unwrap();
+
+    let robocop = &roms["robocop"];
+    assert_eq!(robocop.players(), 2);
+    assert_eq!(robocop.buttons(), 2);
+    assert_eq!(robocop.rotation(), 0);
+    assert_eq!(robocop.control_types(), vec!["joy"]);
+    assert_eq!(robocop.display[0].resolution(), Some("256x240".to_string()));
+
+    let rom_005 = &roms["005"];
+    assert_eq!(rom_005.rotation(), 270);
+    assert_eq!(rom_005.rom_files[0], RomFile {
+        name: "1346b.cpu-u25".to_string(),
+        size: 2048,
+        crc: Some("8e68533e".to_string()),
+        sha1: Some("a257c556d31691068ed5c991f1fb2b51da4826db".to_string()),
+        merge: None,
+    });
+
+    let v8000 = roms["99bottles"].rom_files.iter().find(|file| file.name == "v8000.bin").unwrap();
+    assert_eq!(v8000.crc, None);
+    let bios = roms["100lions"].rom_files.iter().find(|file| file.name == "24013001_right.u83").unwrap();
+    assert_eq!(bios.merge, Some("24013001_right.u83".to_string()));
+
+    let as_acp = &roms["as_acp"];
+    assert_eq!(as_acp.players(), 0);
+    assert_eq!(as_acp.buttons(), 0);
+    assert!(as_acp.display.is_empty());
+    assert!(as_acp.control_types().is_empty());
+}
+
 #[test]
 fn should_exclude_subsets() {
     // subset_start only
-    let mut args = Args::new();
-    args.subset_start = "r".to_string();
+    let mut options = CopyOptions::new(Vec::new(), PathBuf::new());
+    options.subset_start = Some("r".to_string());
 
-    assert!(Roms::is_excluded(&args, "area51"));
-    assert!(!Roms::is_excluded(&args, "robocop"));
+    assert!(Roms::is_excluded(&options, "area51"));
+    assert!(!Roms::is_excluded(&options, "robocop"));
 
     // subset_end only
-    let mut args = Args::new();
-    args.subset_end = "sv0".to_string();
+    let mut options = CopyOptions::new(Vec::new(), PathBuf::new());
+    options.subset_end = Some("sv0".to_string());
 
-    assert!(!Roms::is_excluded(&args, "robocop"));
-    assert!(Roms::is_excluded(&args, "sv801"));
+    assert!(!Roms::is_excluded(&options, "robocop"));
+    assert!(Roms::is_excluded(&options, "sv801"));
 
     // subset_start and subset_end
-    let mut args = Args::new();
-    args.subset_start = "az".to_string();
-    args.subset_end = "sv0".to_string();
-
-    assert!(Roms::is_excluded(&args, "as_acp"));
-    assert!(!Roms::is_excluded(&args, "elevatora"));
-    assert!(!Roms::is_excluded(&args, "robocop"));
-    assert!(Roms::is_excluded(&args, "sv801"));
+    let mut options = CopyOptions::new(Vec::new(), PathBuf::new());
+    options.subset_start = Some("az".to_string());
+    options.subset_end = Some("sv0".to_string());
+
+    assert!(Roms::is_excluded(&options, "as_acp"));
+    assert!(!Roms::is_excluded(&options, "elevatora"));
+    assert!(!Roms::is_excluded(&options, "robocop"));
+    assert!(Roms::is_excluded(&options, "sv801"));
+
+    // names and bounds are compared ignoring case
+    let mut options = CopyOptions::new(Vec::new(), PathBuf::new());
+    options.subset_start = Some("R".to_string());
+
+    assert!(!Roms::is_excluded(&options, "RoboCop"));
+    assert!(Roms::is_excluded(&options, "Area51"));
+}
+
+#[test]
+fn should_fail_on_malformed_input_instead_of_panicking() {
+    // unknown driver status
+    let contents = r#"<mame><machine name="newgame"><description>New game</description><driver status="experimental" emulation="good"/></machine></mame>"#;
+    let doc = read_mame_xml(contents).unwrap();
+
+    let err = parse(doc, Default::default()).unwrap().categorize_roms_with(&policy(UnknownValuePolicy::Fail)).unwrap_err();
+    assert!(matches!(err, CuratorError::UnknownValue { field: "driver status", ref value, ref machine } if value == "experimental" && machine == "newgame"));
+
+    // missing driver status
+    let contents = r#"<mame><machine name="nodriver"><description>No driver</description></machine></mame>"#;
+    let doc = read_mame_xml(contents).unwrap();
+
+    let roms = parse(doc, Default::default()).unwrap().categorize_roms().unwrap();
+    assert!(matches!(roms["nodriver"].category, NotWorking));
+    assert!(matches!(roms["nodriver"].reason, CategoryReason::NoDriverStatus));
+
+    // not a xml document
+    assert!(matches!(read_mame_xml("<mame><machine"), Err(CuratorError::Xml(_))));
+
+    // not a catver.ini
+    assert!(matches!(parse_category_list("just some text"), Err(CuratorError::Ini(_))));
+}
+
+#[test]
+fn should_categorize_unknown_values_by_policy() {
+    let contents = r#"<mame>
+        <machine name="newdriver"><description>New driver</description><driver status="unemulated" emulation="good"/></machine>
+        <machine name="newemulation"><description>New emulation</description><driver status="good" emulation="partial"/></machine>
+        <machine name="newdisk"><description>New disk</description><disk name="newdisk" status="verified"/><driver status="good" emulation="good"/></machine>
+        <machine name="newfeature"><description>New feature</description><driver status="good" emulation="good"/><feature type="camera" overall="partial"/></machine>
+        <machine name="known"><description>Known</description><driver status="good" emulation="good"/></machine>
+    </mame>"#;
+    let categorize = |unknown_value_policy| parse(read_mame_xml(contents).unwrap(), Default::default()).unwrap()
+        .categorize_roms_with(&policy(unknown_value_policy)).unwrap();
+
+    // not working by default
+    let roms = parse(read_mame_xml(contents).unwrap(), Default::default()).unwrap().categorize_roms().unwrap();
+    assert!(matches!(roms["newdriver"].category, NotWorking));
+    assert_eq!(roms["newdriver"].reason.to_string(), "unknown driver status 'unemulated'");
+    assert_eq!(roms["newemulation"].reason.to_string(), "unknown emulation status 'partial'");
+    assert_eq!(roms["newdisk"].reason.to_string(), "unknown disk status 'verified'");
+    assert_eq!(roms["newfeature"].reason.to_string(), "unknown feature status 'partial'");
+    assert!(matches!(roms["known"].category, Working));
+
+    let roms = categorize(UnknownValuePolicy::UnCategorized);
+    assert!(matches!(roms["newdriver"].category, UnCategorized));
+    assert!(matches!(roms["newdisk"].category, UnCategorized));
+    assert!(matches!(roms["known"].category, Working));
+
+    let roms = categorize(UnknownValuePolicy::Working);
+    assert!(matches!(roms["newdriver"].category, Working));
+    assert!(matches!(roms["newemulation"].category, Working));
+    assert_eq!(roms["newemulation"].reason.to_string(), "partial emulation");
+    assert!(matches!(roms["newdisk"].category, Working));
+    assert_eq!(roms["newdisk"].data.chd[0].status, ChdStatus::Unknown("verified".to_string()));
+
+    let unrecognized = unrecognized_values(&roms);
+    assert_eq!(unrecognized.len(), 4);
+    assert_eq!(unrecognized[0], UnrecognizedValue {
+        field: "disk status".to_string(),
+        value: "verified".to_string(),
+        machines: vec!["newdisk".to_string()],
+    });
+    assert_eq!(unrecognized[1].field, "driver status");
+    assert_eq!(unrecognized[1].value, "unemulated");
+
+    assert_eq!(UnknownValuePolicy::from_str("uncategorized"), Ok(UnknownValuePolicy::UnCategorized));
+    assert_eq!(UnknownValuePolicy::from_str("not-working"), Ok(UnknownValuePolicy::NotWorking));
+}
+
+fn policy(unknown_value_policy: UnknownValuePolicy) -> CategorizeOptions {
+    CategorizeOptions { unknown_value_policy, ..CategorizeOptions::default() }
 }
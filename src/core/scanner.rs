@@ -0,0 +1,193 @@
+//! Finds the roms in a source path whose collection is organised in sub-directories,
+//! for ex, `roms/A-M/robocop.zip` and `chds/area51/area51.chd`.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::core::roms_service::read_dir_at;
+use crate::error::{CuratorError, CuratorResult};
+use crate::models::roms::Roms;
+
+/// Files created by operating systems and file managers, never roms.
+const SYSTEM_FILE_NAMES: [&str; 4] = ["thumbs.db", "ehthumbs.db", "desktop.ini", "icon\r"];
+
+///
+/// @return true if `name` is hidden, starting with `.` as `.DS_Store` or macOS `._robocop.zip`
+/// files, or an operating system file like `Thumbs.db`. These are never roms.
+///
+pub fn is_hidden_or_system(name: &OsStr) -> bool {
+    let name = name.to_string_lossy();
+    name.starts_with('.') || SYSTEM_FILE_NAMES.contains(&name.to_ascii_lowercase().as_str())
+}
+
+///
+/// @return Lowercase machine name of the rom at `path`: the name of a directory, or the
+/// file name without its last extension, for ex, `robocop.v2` for `RoboCop.v2.zip`.
+/// An [Encoding](CuratorError::Encoding) error if the name is not valid UTF-8.
+///
+pub fn rom_name(path: &Path) -> CuratorResult<String> {
+    let name = path.file_name().and_then(|name| name.to_str())
+        .ok_or_else(|| CuratorError::Encoding(path.to_path_buf()))?;
+
+    let name = match name.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() && !path.is_dir() => stem,
+        _ => name,
+    };
+    Ok(name.to_ascii_lowercase())
+}
+
+///
+/// Glob patterns of files and directories left out when scanning source paths, for ex,
+/// `*.txt` or `BIOS/*`. Patterns are matched, ignoring case, against the name and the path
+/// relative to the source path, and written separated by commas:
+///
+/// ```text
+/// *.txt,*.nfo,.trash
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct IgnorePatterns {
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl Default for IgnorePatterns {
+    fn default() -> Self {
+        IgnorePatterns { patterns: Vec::new(), set: GlobSet::empty() }
+    }
+}
+
+impl PartialEq for IgnorePatterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.patterns == other.patterns
+    }
+}
+
+impl Eq for IgnorePatterns {}
+
+impl IgnorePatterns {
+    /// @return The patterns as they were written.
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// @return true if `relative_path`, relative to the source path, or its name matches any pattern.
+    pub fn is_ignored(&self, relative_path: &Path) -> bool {
+        if self.patterns.is_empty() { return false; }
+        self.set.is_match(relative_path) ||
+            relative_path.file_name().is_some_and(|name| self.set.is_match(name))
+    }
+}
+
+impl FromStr for IgnorePatterns {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let patterns: Vec<String> = value.split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &patterns {
+            let glob = GlobBuilder::new(pattern).case_insensitive(true).build()
+                .map_err(|err| format!("Invalid ignore pattern '{pattern}': {err}"))?;
+            builder.add(glob);
+        }
+        let set = builder.build().map_err(|err| err.to_string())?;
+
+        Ok(IgnorePatterns { patterns, set })
+    }
+}
+
+///
+/// Options of [scan_source].
+///
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// How many levels of sub-directories are scanned, 0 for only the source path itself.
+    pub max_depth: usize,
+    /// Files and directories left out of the scan.
+    pub ignore: IgnorePatterns,
+}
+
+///
+/// Lists the rom files and rom directories in `source_path`, sorted. Directories are
+/// told apart by matching their name against `roms`:
+/// - named after a machine with CHDs: a CHD directory, for ex, `area51/`.
+/// - named after another machine and without sub-directories: a rom directory.
+/// - anything else: an organisational directory, for ex, `A-M/`, scanned up to
+///   [max_depth](ScanOptions::max_depth) and listed as it is past it.
+///
+/// Ignored, [hidden and system](is_hidden_or_system) files and directories are left out.
+/// Names that are not valid UTF-8 are listed, so they can be reported.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use roms_curator::core::scanner::{scan_source, ScanOptions};
+///
+/// let roms = roms_curator::load_roms("mame.xml", "catver.ini")?;
+/// let options = ScanOptions { max_depth: 2, ignore: "*.txt".parse().unwrap() };
+/// let paths = scan_source(&roms, Path::new("/roms"), &options)?;
+/// # Ok::<(), roms_curator::error::CuratorError>(())
+/// ```
+///
+pub fn scan_source(roms: &Roms, source_path: &Path, options: &ScanOptions) -> CuratorResult<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    scan_dir(roms, source_path, source_path, 0, options, &mut paths)?;
+    Ok(paths)
+}
+
+fn scan_dir(
+    roms: &Roms,
+    source_path: &Path,
+    dir: &Path,
+    depth: usize,
+    options: &ScanOptions,
+    paths: &mut Vec<PathBuf>,
+) -> CuratorResult<()> {
+    let mut entries: Vec<PathBuf> = Vec::new();
+    for entry in read_dir_at(dir)? {
+        entries.push(entry?.path());
+    }
+    entries.sort();
+
+    for path in entries {
+        if path.file_name().is_some_and(is_hidden_or_system) { continue; }
+
+        let relative_path = path.strip_prefix(source_path).unwrap_or(&path);
+        if options.ignore.is_ignored(relative_path) { continue; }
+
+        if depth < options.max_depth && path.is_dir() && !is_rom_dir(roms, &path)? {
+            scan_dir(roms, source_path, &path, depth + 1, options, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+///
+/// @return true if the directory at `path` is a CHD or rom directory, see [scan_source].
+///
+pub(crate) fn is_rom_dir(roms: &Roms, path: &Path) -> CuratorResult<bool> {
+    let Ok(name) = rom_name(path) else { return Ok(true); };
+
+    match roms.get(&name) {
+        None => Ok(false),
+        Some(rom) if !rom.data.chd.is_empty() => Ok(true),
+        Some(_) => {
+            for entry in read_dir_at(path)? {
+                if entry?.path().is_dir() { return Ok(false); }
+            }
+            Ok(true)
+        }
+    }
+}
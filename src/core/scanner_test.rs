@@ -0,0 +1,115 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::core::scanner::{IgnorePatterns, is_hidden_or_system, rom_name, scan_source, ScanOptions};
+use crate::core::test_utils::build_roms;
+use crate::error::CuratorError;
+
+/// Creates `files`, relative to `folder`, with their parent directories.
+fn create_source(folder: &Path, files: &[&str]) {
+    if folder.exists() { fs::remove_dir_all(folder).unwrap(); }
+    for file in files {
+        let path = folder.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "rom").unwrap();
+    }
+}
+
+fn relative(source: &Path, paths: Vec<PathBuf>) -> Vec<String> {
+    paths.iter()
+        .map(|path| path.strip_prefix(source).unwrap().to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+#[test]
+fn should_scan_nested_source_folders() {
+    let roms = build_roms();
+    let source = Path::new("target/tests/scanner/nested");
+    create_source(source, &[
+        "roms/A-M/005.zip",
+        "roms/A-M/elevatora.zip",
+        "roms/N-Z/robocop.zip",
+        "roms/readme.txt",
+        "chds/Area51/area51.chd",
+        "chds/Area51/area51t.chd",
+        "a24play/a24play.bin",
+        ".trash/sv801.zip",
+    ]);
+
+    let paths = scan_source(&roms, source, &ScanOptions::default()).unwrap();
+    assert_eq!(relative(source, paths), vec!["a24play", "chds", "roms"]);
+
+    let options = ScanOptions { max_depth: 2, ignore: IgnorePatterns::from_str("*.TXT").unwrap() };
+    let paths = scan_source(&roms, source, &options).unwrap();
+    assert_eq!(relative(source, paths), vec![
+        "a24play",
+        "chds/Area51",
+        "roms/A-M/005.zip",
+        "roms/A-M/elevatora.zip",
+        "roms/N-Z/robocop.zip",
+    ]);
+
+    // organisational folders past the depth are listed as they are
+    let options = ScanOptions { max_depth: 1, ..options };
+    let paths = scan_source(&roms, source, &options).unwrap();
+    assert_eq!(relative(source, paths), vec!["a24play", "chds/Area51", "roms/A-M", "roms/N-Z"]);
+}
+
+#[test]
+fn should_scan_folders_named_after_machines_without_chd_as_organisational() {
+    let roms = build_roms();
+    let source = Path::new("target/tests/scanner/named");
+    create_source(source, &["aristmk6/100lions/100lions.bin", "aristmk6/aristmk6.zip"]);
+
+    let options = ScanOptions { max_depth: 2, ..ScanOptions::default() };
+    let paths = scan_source(&roms, source, &options).unwrap();
+    assert_eq!(relative(source, paths), vec!["aristmk6/100lions", "aristmk6/aristmk6.zip"]);
+}
+
+#[test]
+fn should_match_ignore_patterns_by_name_and_relative_path() {
+    let patterns = IgnorePatterns::from_str("*.nfo,bios/*").unwrap();
+    assert_eq!(patterns.patterns(), ["*.nfo", "bios/*"]);
+
+    assert!(patterns.is_ignored(Path::new("roms/robocop.NFO")));
+    assert!(patterns.is_ignored(Path::new("BIOS/neogeo.zip")));
+    assert!(!patterns.is_ignored(Path::new("roms/bios/neogeo.zip")));
+    assert!(!patterns.is_ignored(Path::new("roms/robocop.zip")));
+
+    assert!(!IgnorePatterns::default().is_ignored(Path::new("robocop.zip")));
+    assert!(IgnorePatterns::from_str("roms/[a-").unwrap_err().contains("Invalid ignore pattern 'roms/[a-'"));
+}
+
+#[test]
+fn should_name_roms_consistently() {
+    let source = Path::new("target/tests/scanner/names");
+    create_source(source, &["RoboCop.v2.zip", "area51.old/area51.chd"]);
+
+    assert_eq!(rom_name(&source.join("RoboCop.v2.zip")).unwrap(), "robocop.v2");
+    assert_eq!(rom_name(&source.join("area51.old")).unwrap(), "area51.old");
+    assert_eq!(rom_name(Path::new("robocop")).unwrap(), "robocop");
+    assert_eq!(rom_name(Path::new(".DS_Store")).unwrap(), ".ds_store");
+
+    for name in [".DS_Store", "._robocop.zip", ".trash", "Thumbs.db", "desktop.ini"] {
+        assert!(is_hidden_or_system(OsStr::new(name)), "{name}");
+    }
+    assert!(!is_hidden_or_system(OsStr::new("robocop.zip")));
+}
+
+#[cfg(unix)]
+#[test]
+fn should_list_names_that_are_not_utf8() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let roms = build_roms();
+    let source = Path::new("target/tests/scanner/encoding");
+    create_source(source, &["robocop.zip", ".DS_Store", "Thumbs.db"]);
+    let invalid = source.join(OsStr::from_bytes(b"robo\xffcop.zip"));
+    fs::write(&invalid, "rom").unwrap();
+
+    let paths = scan_source(&roms, source, &ScanOptions::default()).unwrap();
+    assert_eq!(paths, vec![source.join("robocop.zip"), invalid.clone()]);
+    assert!(matches!(rom_name(&invalid), Err(CuratorError::Encoding(_))));
+}
@@ -0,0 +1,306 @@
+//! Read-only JSON HTTP API over a categorized rom collection.
+//!
+//! | Endpoint                 | Response                                               |
+//! |--------------------------|--------------------------------------------------------|
+//! | `GET /roms`              | All machines, sorted by name                           |
+//! | `GET /roms?category=..`  | Machines of a [RomCategory], for ex, `working`         |
+//! | `GET /roms?filter=..`    | Machines matching a [Filter] expression (URL encoded)  |
+//! | `GET /roms/<name>`       | A single machine, with its dependencies and CHDs       |
+//! | `GET /stats`             | [RomsStats] of the collection                          |
+//!
+//! `category` and `filter` can be combined. Errors are returned as `{"error": ".."}`.
+
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::time::Duration;
+
+use log::{debug, error, info};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::core::filter::Filter;
+use crate::core::info::RomsInfoExt;
+use crate::core::stats::{roms_stats, RomsStats};
+use crate::models::roms::{Rom, RomCategory, Roms};
+
+/// Address used by the `serve` command when none is given.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+/// How long to wait for a client to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request line and headers read from a client.
+pub(crate) const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// HTTP response returned by [Catalog::handle].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    /// JSON body.
+    pub body: String,
+}
+
+impl Response {
+    fn ok<T: Serialize>(value: &T) -> Response {
+        match serde_json::to_string(value) {
+            Ok(body) => Response { status: 200, body },
+            Err(err) => Response::error(500, &err.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        let body = serde_json::json!({ "error": message }).to_string();
+        Response { status, body }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            414 => "URI Too Long",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RomSummary<'a> {
+    name: &'a str,
+    description: &'a str,
+    year: &'a str,
+    manufacturer: &'a str,
+    genre: &'a str,
+    category: RomCategory,
+    reason: String,
+    parent: Option<&'a str>,
+    chd: bool,
+}
+
+impl<'a> RomSummary<'a> {
+    fn new(name: &'a str, rom: &'a Rom) -> RomSummary<'a> {
+        RomSummary {
+            name,
+            description: &rom.data.description,
+            year: &rom.data.year,
+            manufacturer: &rom.data.manufacturer,
+            genre: &rom.data.category,
+            category: rom.category,
+            reason: rom.reason.to_string(),
+            parent: rom.data.clone_of.as_deref(),
+            chd: !rom.data.chd.is_empty(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RomDetail<'a> {
+    #[serde(flatten)]
+    summary: RomSummary<'a>,
+    source_file: &'a str,
+    driver: Option<String>,
+    emulation: Option<String>,
+    features: Vec<String>,
+    dependencies: Vec<Dependency>,
+    chds: Vec<ChdDetail<'a>>,
+}
+
+#[derive(Serialize)]
+struct Dependency {
+    name: String,
+    /// `None` if the machine is not in `mame.xml`.
+    category: Option<RomCategory>,
+}
+
+#[derive(Serialize)]
+struct ChdDetail<'a> {
+    name: &'a str,
+    status: String,
+}
+
+///
+/// Categorized roms loaded once and served by [serve](Catalog::serve).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::net::TcpListener;
+/// use roms_curator::core::serve::Catalog;
+///
+/// let roms = roms_curator::load_roms("mame.xml", "catver.ini").unwrap();
+/// let catalog = Catalog::new(roms);
+/// catalog.serve(TcpListener::bind("127.0.0.1:8080").unwrap()).unwrap();
+/// ```
+///
+pub struct Catalog {
+    roms: Roms,
+    stats: RomsStats,
+}
+
+impl Catalog {
+    pub fn new(roms: Roms) -> Catalog {
+        let stats = roms_stats(&roms);
+        Catalog { roms, stats }
+    }
+
+    ///
+    /// Answers requests from `listener`, one at a time. Connections that can't be
+    /// accepted or answered are logged and skipped.
+    ///
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        info!("Serving {} roms on http://{}", self.roms.len(), listener.local_addr()?);
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    error!("Error accepting connection: {err}");
+                    continue;
+                }
+            };
+            if let Err(err) = self.handle_connection(stream) {
+                error!("Error answering request: {err}");
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Reads a single HTTP request, up to [MAX_REQUEST_BYTES], from `stream` and writes the response.
+    ///
+    pub fn handle_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        // headers are not used, but must be read before answering
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+            header.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let response = if !request_line.ends_with('\n') && request_line.len() as u64 >= MAX_REQUEST_BYTES {
+            Response::error(414, "Request line too long.")
+        } else {
+            match (parts.next(), parts.next()) {
+                (Some(method), Some(target)) => self.handle(method, target),
+                _ => Response::error(400, "Invalid request line."),
+            }
+        };
+        debug!("{} -> {}", request_line.trim(), response.status);
+
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status, response.reason(), response.body.len(), response.body,
+        )?;
+        stream.flush()
+    }
+
+    ///
+    /// @return [Response] for `method` and `target`, for ex, `GET` and `/roms?category=working`.
+    ///
+    pub fn handle(&self, method: &str, target: &str) -> Response {
+        if method != "GET" {
+            return Response::error(405, "Only GET requests are supported.");
+        }
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let path = path.trim_end_matches('/');
+
+        match path {
+            "/stats" => Response::ok(&self.stats),
+            "/roms" => self.list(query),
+            _ => match path.strip_prefix("/roms/") {
+                Some(name) => self.machine(&decode(name, false)),
+                None => Response::error(404, &format!("Unknown endpoint {path}.")),
+            },
+        }
+    }
+
+    fn list(&self, query: &str) -> Response {
+        let mut category: Option<String> = None;
+        let mut filter: Option<Filter> = None;
+
+        for (key, value) in query.split('&').filter(|pair| !pair.is_empty()).filter_map(|pair| pair.split_once('=')) {
+            let value = decode(value, true);
+            match key {
+                "category" => category = Some(value.replace('_', "").to_lowercase()),
+                "filter" => match Filter::from_str(&value) {
+                    Ok(value) => filter = Some(value),
+                    Err(err) => return Response::error(400, &err),
+                },
+                _ => return Response::error(400, &format!("Unknown query parameter {key}.")),
+            }
+        }
+
+        if let Some(category) = &category {
+            if !RomCategory::iter().any(|known| known.to_string().to_lowercase() == *category) {
+                return Response::error(400, &format!("Unknown category {category}."));
+            }
+        }
+
+        let mut roms: Vec<RomSummary> = self.roms.iter()
+            .filter(|(_, rom)| category.as_ref().is_none_or(|category| rom.category.to_string().to_lowercase() == *category))
+            .filter(|(name, rom)| filter.as_ref().is_none_or(|filter| filter.matches(name, rom)))
+            .map(|(name, rom)| RomSummary::new(name, rom))
+            .collect();
+        roms.sort_by(|a, b| a.name.cmp(b.name));
+
+        Response::ok(&roms)
+    }
+
+    fn machine(&self, name: &str) -> Response {
+        let Some((name, rom)) = self.roms.get_key_value(&name.to_ascii_lowercase()) else {
+            return Response::error(404, &format!("Machine {name} not found."));
+        };
+        let dependencies = self.roms.info(name).map(|info| info.dependencies).unwrap_or_default();
+
+        let detail = RomDetail {
+            summary: RomSummary::new(name, rom),
+            source_file: &rom.data.source_file,
+            driver: rom.data.status.as_ref().map(|status| status.driver.to_string()),
+            emulation: rom.data.status.as_ref().map(|status| status.emulation.to_string()),
+            features: rom.data.features.iter()
+                .map(|feature| format!("{} {}", feature.typ, feature.status.to_string().to_lowercase()))
+                .collect(),
+            dependencies: dependencies.into_iter()
+                .map(|(name, category)| Dependency { name, category })
+                .collect(),
+            chds: rom.data.chd.iter()
+                .map(|chd| ChdDetail { name: &chd.name, status: chd.status.to_string() })
+                .collect(),
+        };
+
+        Response::ok(&detail)
+    }
+}
+
+///
+/// Decodes an URL encoded value, for ex, `status+%3D%3D+working` to `status == working`.
+/// `+` is only a space in query strings, `plus_as_space`, in paths it's kept as it is.
+///
+fn decode(value: &str, plus_as_space: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' if plus_as_space => decoded.push(b' '),
+            // both hex digits checked first, `from_str_radix` alone accepts a sign, for ex, `%+1`
+            b'%' if i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -0,0 +1,117 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use serde_json::Value;
+
+use crate::core::serve::{Catalog, MAX_REQUEST_BYTES};
+use crate::load_roms;
+
+fn catalog() -> Catalog {
+    Catalog::new(load_roms("tests/resources/listxml_0244.xml", "tests/resources/catver_0244.ini").unwrap())
+}
+
+fn names(body: &str) -> Vec<String> {
+    let roms: Value = serde_json::from_str(body).unwrap();
+    roms.as_array().unwrap().iter().map(|rom| rom["name"].as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn should_list_roms_by_category_and_filter() {
+    let catalog = catalog();
+
+    let response = catalog.handle("GET", "/roms");
+    assert_eq!(response.status, 200);
+    assert_eq!(names(&response.body).len(), 11);
+
+    let response = catalog.handle("GET", "/roms?category=working");
+    assert_eq!(names(&response.body), vec!["005", "area51", "elevatora", "robocop"]);
+
+    let response = catalog.handle("GET", "/roms?category=not_working");
+    assert_eq!(names(&response.body), vec!["100lions", "99bottles", "as_acp"]);
+
+    let response = catalog.handle("GET", "/roms?category=Working&filter=%21chd");
+    assert_eq!(names(&response.body), vec!["005", "elevatora", "robocop"]);
+
+    let response = catalog.handle("GET", "/roms?filter=name+%3D%3D+robocop");
+    assert_eq!(names(&response.body), vec!["robocop"]);
+
+    assert_eq!(catalog.handle("GET", "/roms?category=arcade").status, 400);
+    assert_eq!(catalog.handle("GET", "/roms?filter=status+%3D%3D").status, 400);
+    assert_eq!(catalog.handle("GET", "/roms?sort=name").status, 400);
+}
+
+#[test]
+fn should_show_machine_and_stats() {
+    let catalog = catalog();
+
+    let response = catalog.handle("GET", "/roms/100lions");
+    assert_eq!(response.status, 200);
+    let rom: Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(rom["category"], "NotWorking");
+    assert_eq!(rom["reason"], "preliminary driver");
+    assert_eq!(rom["dependencies"][0]["name"], "aristmk6");
+    assert_eq!(rom["dependencies"][0]["category"], "Bios");
+
+    let response = catalog.handle("GET", "/roms/unknown");
+    assert_eq!(response.status, 404);
+    assert!(response.body.contains("\"error\""));
+
+    // '+' is only a space in query strings
+    let response = catalog.handle("GET", "/roms/robo+cop");
+    assert!(response.body.contains("Machine robo+cop not found."));
+    assert_eq!(catalog.handle("GET", "/roms/%52obocop").status, 200);
+    // only 2 hex digits are decoded, a sign is not one
+    let response = catalog.handle("GET", "/roms/robocop%+9");
+    assert!(response.body.contains("Machine robocop%+9 not found."));
+
+    let response = catalog.handle("GET", "/stats");
+    let stats: Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(stats["total"], 11);
+    assert_eq!(stats["by_category"]["Working"], 4);
+
+    assert_eq!(catalog.handle("POST", "/stats").status, 405);
+    assert_eq!(catalog.handle("GET", "/").status, 404);
+}
+
+#[test]
+fn should_answer_local_http_client() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let catalog = catalog();
+    thread::spawn(move || catalog.serve(listener));
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(b"GET /roms/robocop HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(head.contains("Content-Type: application/json"));
+    assert!(head.contains(&format!("Content-Length: {}", body.len())));
+    let rom: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(rom["description"], "Robocop (World revision 4)");
+}
+
+#[test]
+fn should_reject_too_long_request_lines() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let catalog = catalog();
+    thread::spawn(move || catalog.serve(listener));
+
+    let mut stream = TcpStream::connect(address).unwrap();
+    let request_line = format!("GET /{}", "a".repeat(MAX_REQUEST_BYTES as usize - 5));
+    stream.write_all(request_line.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 414 URI Too Long\r\n"));
+
+    // still answering
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(b"GET /stats HTTP/1.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::models::roms::{RomCategory, Roms};
+
+/// Number of genres shown when printing [RomsStats].
+const TOP_GENRES: usize = 10;
+
+/// Statistics about a categorized rom collection, see [roms_stats].
+#[derive(Default, Debug, Serialize)]
+pub struct RomsStats {
+    pub total: usize,
+    /// Number of roms in each [RomCategory].
+    pub by_category: BTreeMap<String, usize>,
+    /// Number of roms by the reason they are in their category.
+    pub by_reason: BTreeMap<String, usize>,
+    /// Number of roms by genre, the first part of the `catver.ini` category.
+    pub by_genre: BTreeMap<String, usize>,
+    /// Roms that depend on CHDs.
+    pub chd: usize,
+    pub clones: usize,
+    pub mechanical: usize,
+}
+
+///
+/// @return [RomsStats] of the given rom collection.
+///
+pub fn roms_stats(roms: &Roms) -> RomsStats {
+    let mut stats = RomsStats {
+        total: roms.len(),
+        by_category: RomCategory::iter().map(|category| (category.to_string(), 0)).collect(),
+        ..Default::default()
+    };
+
+    roms.values().for_each(|rom| {
+        *stats.by_category.entry(rom.category.to_string()).or_default() += 1;
+        *stats.by_reason.entry(rom.reason.to_string()).or_default() += 1;
+
+        let genre = rom.data.category.split(" / ").next().unwrap_or_default().trim();
+        let genre = if genre.is_empty() { "Unknown" } else { genre };
+        *stats.by_genre.entry(genre.to_string()).or_default() += 1;
+
+        if !rom.data.chd.is_empty() { stats.chd += 1; }
+        if rom.data.clone_of.is_some() { stats.clones += 1; }
+        if rom.data.is_mechanical { stats.mechanical += 1; }
+    });
+
+    stats
+}
+
+impl RomsStats {
+    /// Only the number of roms in each category, as shown by the `categorize` command.
+    pub fn category_summary(&self) -> String {
+        RomCategory::iter()
+            .map(|category| category.to_string())
+            .map(|category| format!("{}: {}\n", category, self.by_category.get(&category).unwrap_or(&0)))
+            .collect()
+    }
+}
+
+impl fmt::Display for RomsStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total roms: {}", self.total)?;
+        writeln!(f, "CHD roms: {}", self.chd)?;
+        writeln!(f, "Clones: {}", self.clones)?;
+        writeln!(f, "Mechanical: {}", self.mechanical)?;
+
+        writeln!(f, "\nBy category:")?;
+        for line in self.category_summary().lines() {
+            writeln!(f, "  {line}")?;
+        }
+
+        writeln!(f, "\nBy reason:")?;
+        for (reason, count) in &self.by_reason {
+            writeln!(f, "  {reason}: {count}")?;
+        }
+
+        let mut genres: Vec<(&String, &usize)> = self.by_genre.iter().collect();
+        genres.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        writeln!(f, "\nTop genres:")?;
+        for (genre, count) in genres.into_iter().take(TOP_GENRES) {
+            writeln!(f, "  {genre}: {count}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+//! Fixtures shared by the unit tests.
+
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::{parse_category_list, read_mame_xml};
+use crate::core::roms_service::{parse, UnfilteredRomsExt};
+use crate::models::roms::Roms;
+
+/// @return The categorized small set, `listxml_0244.xml` with `catver_0244.ini`.
+pub fn build_roms() -> Roms {
+    let rom_categories = parse_category_list(&fs::read_to_string("tests/resources/catver_0244.ini").unwrap()).unwrap();
+    let contents = fs::read_to_string("tests/resources/listxml_0244.xml").unwrap();
+    parse(read_mame_xml(&contents).unwrap(), rom_categories).unwrap().categorize_roms().unwrap()
+}
+
+/// @return An empty `target/tests/{group}/{name}` folder.
+pub fn folder(group: &str, name: &str) -> PathBuf {
+    let folder = Path::new("target/tests").join(group).join(name);
+    if folder.exists() { fs::remove_dir_all(&folder).unwrap(); }
+    fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+/// Creates a zip at `path` with `files`, names ending with `/` are added as directories.
+pub fn create_zip(path: &Path, files: &[(&str, &[u8])], options: SimpleFileOptions) {
+    let mut zip = ZipWriter::new(File::create(path).unwrap());
+    for (name, contents) in files {
+        if name.ends_with('/') {
+            zip.add_directory(*name, options).unwrap();
+        } else {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+    }
+    zip.finish().unwrap();
+}
@@ -0,0 +1,181 @@
+//! Rewrites zip files in a deterministic, TorrentZip-style form, so that the same roms always
+//! give the same zip, byte for byte, whatever tool created the original:
+//! - entries sorted by lowercase name, without directories.
+//! - all timestamps set to 1996-12-24 23:32:00.
+//! - deflate compression, level 9.
+//! - archive comment `ROMS-CURATOR-XXXXXXXX`, the CRC32 of the central directory.
+//!
+//! The deflate streams are not byte for byte those of zlib, so the zips are not TorrentZip files
+//! and don't have its `TORRENTZIPPED-` comment. Zips that do, written by TorrentZip tools, are
+//! already deterministic and left as they are.
+
+use std::ffi::OsString;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use zip::{CompressionMethod, DateTime, ZipWriter};
+use zip::write::SimpleFileOptions;
+
+use crate::core::container::{archive_error, list_entries, open_zip, read_entries};
+use crate::error::CuratorResult;
+
+/// Start of the comment of TorrentZip files, followed by the CRC32 of the central directory.
+pub const TORRENTZIP_COMMENT_PREFIX: &str = "TORRENTZIPPED-";
+
+/// Start of the comment of zips rewritten by [torrentzip], followed by the CRC32 of the central directory.
+pub const REWRITTEN_COMMENT_PREFIX: &str = "ROMS-CURATOR-";
+
+/// Size of the end of central directory record, without comment.
+const END_OF_CENTRAL_DIRECTORY_SIZE: u64 = 22;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+
+///
+/// Rewrites the zip at `path` in deterministic form, see [module](self) documentation. The new zip is
+/// written next to it and only replaces it once its contents are checked to be the same, names,
+/// sizes and CRC32, so a failed rewrite leaves the original untouched.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use roms_curator::core::torrentzip::torrentzip;
+///
+/// let rewritten = torrentzip(Path::new("/roms-new/working/robocop.zip"))?;
+/// # Ok::<(), roms_curator::error::CuratorError>(())
+/// ```
+///
+/// @return false if the zip was already rewritten, or a TorrentZip file, and was left as it is.
+/// An [Archive](crate::error::CuratorError::Archive) error if it can't be read or its contents changed.
+///
+pub fn torrentzip(path: &Path) -> CuratorResult<bool> {
+    if is_rewritten(path)? || is_torrentzipped(path)? { return Ok(false); }
+
+    let temp_path = temp_path(path);
+    let result = write_torrentzip(path, &temp_path)
+        .and_then(|_| check_same_contents(path, &temp_path));
+    match result {
+        Ok(()) => {
+            fs::rename(&temp_path, path)?;
+            Ok(true)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(err)
+        }
+    }
+}
+
+///
+/// @return true if the zip at `path` has a TorrentZip comment matching its central directory,
+/// as written by TorrentZip tools.
+///
+pub fn is_torrentzipped(path: &Path) -> CuratorResult<bool> {
+    has_crc_comment(path, TORRENTZIP_COMMENT_PREFIX)
+}
+
+///
+/// @return true if the zip at `path` was rewritten by [torrentzip] and not changed since.
+///
+pub fn is_rewritten(path: &Path) -> CuratorResult<bool> {
+    has_crc_comment(path, REWRITTEN_COMMENT_PREFIX)
+}
+
+/// @return true if the comment of the zip at `path` is `prefix` followed by the CRC32 of its central directory.
+fn has_crc_comment(path: &Path, prefix: &str) -> CuratorResult<bool> {
+    let archive = open_zip(path)?;
+    let comment = archive.comment().to_vec();
+    let Some(crc) = std::str::from_utf8(&comment).ok()
+        .and_then(|comment| comment.strip_prefix(prefix)) else { return Ok(false); };
+
+    let mut file = archive.into_inner();
+    Ok(central_directory_crc(&mut file, comment.len() as u64)?
+        .is_some_and(|expected| crc == format!("{expected:08X}")))
+}
+
+/// Writes the entries of the zip at `path` to a new zip at `temp_path`, in deterministic form.
+fn write_torrentzip(path: &Path, temp_path: &Path) -> CuratorResult<()> {
+    let mut source = open_zip(path)?;
+
+    let mut names: Vec<(String, usize)> = Vec::new();
+    for index in 0..source.len() {
+        let file = source.by_index_raw(index).map_err(|err| archive_error(path, err))?;
+        if file.is_dir() { continue; }
+        names.push((file.name().to_string(), index));
+    }
+    names.sort_by_cached_key(|(name, _)| (name.to_lowercase(), name.clone()));
+
+    let timestamp = DateTime::from_date_and_time(1996, 12, 24, 23, 32, 0).map_err(|err| archive_error(path, err))?;
+    // read back to compute the comment
+    let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(temp_path)?;
+    let mut writer = ZipWriter::new(BufWriter::new(file));
+    for (name, index) in names {
+        let mut file = source.by_index(index).map_err(|err| archive_error(path, err))?;
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .compression_level(Some(9))
+            .last_modified_time(timestamp)
+            .large_file(file.size() >= u32::MAX as u64);
+        writer.start_file(name, options).map_err(|err| archive_error(temp_path, err))?;
+        io::copy(&mut file, &mut writer)?;
+    }
+
+    let mut file = writer.finish().map_err(|err| archive_error(temp_path, err))?
+        .into_inner().map_err(|err| err.into_error())?;
+
+    // the comment is not part of the central directory, it's added once it's written
+    if let Some(crc) = central_directory_crc(&mut file, 0)? {
+        let comment = format!("{REWRITTEN_COMMENT_PREFIX}{crc:08X}");
+        file.seek(SeekFrom::End(-2))?;
+        file.write_all(&(comment.len() as u16).to_le_bytes())?;
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(comment.as_bytes())?;
+    }
+    file.sync_all()?;
+
+    Ok(())
+}
+
+///
+/// @return CRC32 of the central directory of the zip read from `file`, whose comment is `comment_length`
+/// bytes long. `None` for zip64 files, too big for a CRC32 comment.
+///
+fn central_directory_crc(file: &mut (impl Read + Seek), comment_length: u64) -> CuratorResult<Option<u32>> {
+    let mut record = [0u8; END_OF_CENTRAL_DIRECTORY_SIZE as usize];
+    file.seek(SeekFrom::End(-((END_OF_CENTRAL_DIRECTORY_SIZE + comment_length) as i64)))?;
+    file.read_exact(&mut record)?;
+    if record[..4] != END_OF_CENTRAL_DIRECTORY_SIGNATURE { return Ok(None); }
+
+    let size = u32::from_le_bytes([record[12], record[13], record[14], record[15]]);
+    let offset = u32::from_le_bytes([record[16], record[17], record[18], record[19]]);
+    if size == u32::MAX || offset == u32::MAX { return Ok(None); }
+
+    let mut central_directory = vec![0u8; size as usize];
+    file.seek(SeekFrom::Start(offset as u64))?;
+    file.read_exact(&mut central_directory)?;
+
+    Ok(Some(crc32fast::hash(&central_directory)))
+}
+
+/// Checks both zips have the same entries and that all of the rewritten one can be read back.
+fn check_same_contents(path: &Path, temp_path: &Path) -> CuratorResult<()> {
+    if list_entries(path)? != list_entries(temp_path)? {
+        return Err(archive_error(temp_path, "contents differ from the original"));
+    }
+
+    // reading to the end checks the CRC32 of each entry
+    read_entries(temp_path, |_, reader| {
+        io::copy(reader, &mut io::sink())?;
+        Ok(())
+    })
+}
+
+/// @return Path of the zip being written, for ex, `.robocop.zip` for `robocop.zip`,
+/// [hidden](crate::core::scanner::is_hidden_or_system) so it's never taken for a rom.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    path.with_file_name(name)
+}
@@ -0,0 +1,82 @@
+use std::fs;
+use std::fs::File;
+
+use zip::{CompressionMethod, DateTime, ZipArchive};
+use zip::write::SimpleFileOptions;
+
+use crate::core::container::list_entries;
+use crate::core::test_utils::{create_zip, folder};
+use crate::core::torrentzip::{is_rewritten, is_torrentzipped, REWRITTEN_COMMENT_PREFIX, torrentzip};
+use crate::error::CuratorError;
+
+#[test]
+fn should_rewrite_the_same_roms_to_the_same_zip() {
+    let folder = folder("torrentzip", "same");
+
+    let first = folder.join("first.zip");
+    create_zip(&first, &[("extras/", b""), ("c.bin", b"hello"), ("B.bin", b"abc"), ("a.bin", b"hellO")],
+               SimpleFileOptions::default().compression_method(CompressionMethod::Stored));
+    let second = folder.join("second.zip");
+    create_zip(&second, &[("extras/", b""), ("a.bin", b"hellO"), ("B.bin", b"abc"), ("c.bin", b"hello")],
+               SimpleFileOptions::default().compression_level(Some(1))
+                   .last_modified_time(DateTime::from_date_and_time(2024, 5, 1, 10, 0, 0).unwrap()));
+    let entries = list_entries(&first).unwrap();
+    assert!(!is_rewritten(&first).unwrap());
+
+    assert!(torrentzip(&first).unwrap());
+    assert!(torrentzip(&second).unwrap());
+    assert_eq!(fs::read(&first).unwrap(), fs::read(&second).unwrap());
+    assert_eq!(list_entries(&first).unwrap(), entries);
+    assert!(!folder.join(".first.zip").exists());
+
+    let mut archive = ZipArchive::new(File::open(&first).unwrap()).unwrap();
+    assert_eq!(archive.file_names().collect::<Vec<&str>>(), vec!["a.bin", "B.bin", "c.bin"]);
+    assert!(String::from_utf8_lossy(archive.comment()).starts_with(REWRITTEN_COMMENT_PREFIX));
+    let file = archive.by_index(0).unwrap();
+    assert_eq!(file.compression(), CompressionMethod::Deflated);
+    assert_eq!(file.last_modified(), Some(DateTime::from_date_and_time(1996, 12, 24, 23, 32, 0).unwrap()));
+    drop(file);
+
+    // already rewritten, left as it is, and never passed off as a TorrentZip file
+    assert!(is_rewritten(&first).unwrap());
+    assert!(!is_torrentzipped(&first).unwrap());
+    let before = fs::read(&first).unwrap();
+    assert!(!torrentzip(&first).unwrap());
+    assert_eq!(fs::read(&first).unwrap(), before);
+}
+
+#[test]
+fn should_leave_torrentzipped_files_as_they_are() {
+    let folder = folder("torrentzip", "torrentzipped");
+
+    // hand-crafted following the TorrentZip format, the same as trrntzip writes: local headers with
+    // version 20, flags 2, deflate level 9, 1996-12-24 23:32 and no extra fields, entries sorted by
+    // lowercase name and the CRC32 of the central directory as comment
+    let path = folder.join("torrentzipped.zip");
+    fs::copy("tests/resources/torrentzipped.zip", &path).unwrap();
+    let before = fs::read(&path).unwrap();
+
+    assert!(is_torrentzipped(&path).unwrap());
+    assert!(!torrentzip(&path).unwrap());
+    assert_eq!(fs::read(&path).unwrap(), before);
+}
+
+#[test]
+fn should_leave_zip_untouched_if_it_cant_be_rewritten() {
+    let folder = folder("torrentzip", "corrupt");
+
+    let path = folder.join("corrupt.zip");
+    create_zip(&path, &[("a.bin", b"hello world")], SimpleFileOptions::default().compression_method(CompressionMethod::Stored));
+    let mut contents = fs::read(&path).unwrap();
+    let start = contents.windows(11).position(|window| window == b"hello world").unwrap();
+    contents[start + 4] = b'O';
+    fs::write(&path, &contents).unwrap();
+
+    assert!(matches!(torrentzip(&path), Err(CuratorError::Io(_)) | Err(CuratorError::Archive(_))));
+    assert_eq!(fs::read(&path).unwrap(), contents);
+    assert!(!folder.join(".corrupt.zip").exists());
+
+    let path = folder.join("robocop.7z");
+    File::create(&path).unwrap();
+    assert!(matches!(torrentzip(&path), Err(CuratorError::Archive(_))));
+}
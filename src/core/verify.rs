@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use serde::Serialize;
+
+use crate::core::container::{check_contents, ContainerKind, ContentCheck, list_entries};
+use crate::core::filter::Filter;
+use crate::core::scanner::{rom_name, scan_source, ScanOptions};
+use crate::error::CuratorResult;
+use crate::models::roms::{RomCategory, Roms};
+
+/// Result of checking the source paths against a categorized rom collection, see [verify_sources].
+#[derive(Default, Debug, Serialize)]
+pub struct Verification {
+    /// Roms found in the source paths.
+    pub found: usize,
+    /// Working roms not found in any of the source paths.
+    pub missing_working: Vec<String>,
+    /// Working roms found in the source paths but without their CHD directory.
+    pub missing_chd: Vec<String>,
+    /// Files in the source paths that are not in `mame.xml`, or not a zip, 7z or folder.
+    pub unknown: Vec<String>,
+    /// Containers with missing or bad rom files, or that can't be read, with what's wrong.
+    /// Only checked with [check_contents](VerifyOptions::check_contents).
+    pub bad_contents: Vec<String>,
+}
+
+impl Verification {
+    /// @return true if nothing is missing, unknown or bad.
+    pub fn is_ok(&self) -> bool {
+        self.missing_working.is_empty() && self.missing_chd.is_empty() && self.unknown.is_empty() &&
+            self.bad_contents.is_empty()
+    }
+}
+
+///
+/// Options of [verify_sources].
+///
+#[derive(Default, Debug, Clone)]
+pub struct VerifyOptions {
+    /// How the source paths are scanned for roms.
+    pub scan: ScanOptions,
+    /// If set, only working roms matching this expression are expected.
+    pub filter: Option<Filter>,
+    /// If true, the files in each zip, 7z or folder are checked against `mame.xml`, see [check_contents].
+    pub check_contents: bool,
+}
+
+///
+/// Checks the source paths, scanned with [scan_source], for working roms (matching the filter, if any)
+/// that are missing, working roms whose CHD directory is missing, and files not found in `mame.xml`.
+///
+/// @return A [Verification] with all lists sorted.
+///
+pub fn verify_sources(roms: &Roms, source_paths: &[impl AsRef<Path>], options: &VerifyOptions) -> CuratorResult<Verification> {
+    let mut verification = Verification::default();
+    let mut files: HashSet<String> = HashSet::new();
+    let mut directories: HashSet<String> = HashSet::new();
+
+    for source_path in source_paths {
+        for path in scan_source(roms, source_path.as_ref(), &options.scan)? {
+            let name = rom_name(&path).unwrap_or_default();
+
+            let (Some(rom), Some(kind)) = (roms.get(&name), ContainerKind::from_path(&path)) else {
+                verification.unknown.push(path.to_string_lossy().to_string());
+                continue;
+            };
+
+            let is_chd_directory = kind == ContainerKind::Folder && !rom.data.chd.is_empty();
+            if is_chd_directory {
+                directories.insert(name);
+                continue;
+            }
+
+            if options.check_contents {
+                match list_entries(&path) {
+                    Ok(entries) => {
+                        let check = check_contents(&rom.data, &entries);
+                        if !check.is_ok() {
+                            verification.bad_contents.push(format!("{} ({})", path.to_string_lossy(), describe(&check)));
+                        }
+                    }
+                    Err(err) => verification.bad_contents.push(format!("{} ({err})", path.to_string_lossy())),
+                }
+            }
+            files.insert(name);
+        }
+    }
+
+    verification.found = files.union(&directories).count();
+
+    roms.iter()
+        .filter(|(_, rom)| rom.category == RomCategory::Working)
+        .filter(|(name, rom)| options.filter.as_ref().is_none_or(|filter| filter.matches(name, rom)))
+        .for_each(|(name, rom)| {
+            if !files.contains(name) {
+                verification.missing_working.push(name.clone());
+            } else if !rom.data.chd.is_empty() && !directories.contains(name) {
+                verification.missing_chd.push(name.clone());
+            }
+        });
+
+    verification.missing_working.sort();
+    verification.missing_chd.sort();
+    verification.unknown.sort();
+    verification.bad_contents.sort();
+
+    Ok(verification)
+}
+
+fn describe(check: &ContentCheck) -> String {
+    let mut problems: Vec<String> = Vec::new();
+    if !check.missing.is_empty() { problems.push(format!("missing {}", check.missing.join(", "))); }
+    if !check.bad.is_empty() { problems.push(format!("bad {}", check.bad.join(", "))); }
+    problems.join("; ")
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Roms found: {}", self.found)?;
+        writeln!(f, "Missing working roms: {}", self.missing_working.len())?;
+        for name in &self.missing_working {
+            writeln!(f, "  {name}")?;
+        }
+        writeln!(f, "Working roms missing CHDs: {}", self.missing_chd.len())?;
+        for name in &self.missing_chd {
+            writeln!(f, "  {name}")?;
+        }
+        writeln!(f, "Unknown files: {}", self.unknown.len())?;
+        for path in &self.unknown {
+            writeln!(f, "  {path}")?;
+        }
+        if !self.bad_contents.is_empty() {
+            writeln!(f, "Bad contents: {}", self.bad_contents.len())?;
+            for problem in &self.bad_contents {
+                writeln!(f, "  {problem}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+/// Errors returned by the library.
+#[derive(Debug, Error)]
+pub enum CuratorError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// `mame.xml` is not a valid MAME ROM database.
+    #[error("Invalid MAME xml: {0}")]
+    Xml(String),
+    /// `catver.ini` is not a valid MAME support file.
+    #[error("Invalid catver.ini: {0}")]
+    Ini(String),
+    /// A `mame.xml` attribute has a value this version doesn't know, for ex, a new driver status.
+    #[error("Unknown {field} '{value}' in machine {machine}")]
+    UnknownValue { field: &'static str, value: String, machine: String },
+    /// A path is missing, or can't be used as it is, for ex, the destination is a file.
+    #[error("{message}: {}", path.display())]
+    Path { path: PathBuf, message: String },
+    /// A file name is not valid UTF-8.
+    #[error("File name is not valid UTF-8: {}", .0.display())]
+    Encoding(PathBuf),
+    /// Invalid [Filter](crate::core::filter::Filter) expression.
+    #[error("Invalid filter: {0}")]
+    Filter(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    /// A [catalog](crate::core::catalog) written by an incompatible version.
+    #[error("Invalid catalog: {0}")]
+    Catalog(String),
+    /// A zip or 7z [container](crate::core::container) can't be read.
+    #[error("Invalid archive: {0}")]
+    Archive(String),
+}
+
+pub type CuratorResult<T> = Result<T, CuratorError>;
+
+impl CuratorError {
+    pub(crate) fn path(path: impl Into<PathBuf>, message: &str) -> CuratorError {
+        CuratorError::Path { path: path.into(), message: message.to_string() }
+    }
+}
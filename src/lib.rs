@@ -9,16 +9,15 @@
 //! on how to use this as a command-line Application or as a Library.
 //!
 
-use std::error::Error;
-use std::fs;
 use std::collections::HashMap;
 use roxmltree::Document;
 use crate::core::args::Args;
-use crate::core::roms_service::{UnfilteredRomsExt, parse};
+use crate::core::curator::Curator;
+use crate::error::{CuratorError, CuratorResult};
 use crate::models::roms::Roms;
-use crate::utils::{build_progress_bar, ProgressBarEx};
 
 pub mod core;
+pub mod error;
 pub mod models;
 pub mod utils;
 
@@ -54,34 +53,39 @@ type RomCategories = HashMap<String, String>;
 ///
 /// @return A `HashMap<String, Rom>` collection with all ROMs categorized.
 ///
-pub fn run(args: &Args) -> Result<Roms, Box<dyn Error>> {
-    let progress_bar = if args.progress { Some(build_progress_bar()) } else { None };
-    progress_bar.set_length(3);
-
-    progress_bar.println("* Reading mame database and copying files can last a few minutes, please be patient. *");
-
-    progress_bar.println(format!("Reading {} document...", &args.catver_path).as_str());
-    let rom_categories = build_category_list(args.catver_path.clone())?;
-    progress_bar.inc();
-
-    progress_bar.println(format!("Reading {} document...", &args.mame_xml_path).as_str());
-    let contents = fs::read_to_string(args.mame_xml_path.clone())?;
-    let doc = read_mame_xml(&contents)?;
-    progress_bar.inc();
-
-    progress_bar.println("Categorizing roms...");
-    let unfiltered_roms = parse(doc, rom_categories)?;
-    let roms = unfiltered_roms.categorize_roms()?;
-    progress_bar.inc();
-
-    if let Some(pb) = progress_bar { pb.finish(); }
-
-    Ok(roms)
+pub fn run(args: &Args) -> CuratorResult<Roms> {
+    let mut builder = Curator::builder()
+        .mame_xml_path(&args.mame_xml_path)
+        .unknown_value_policy(args.unknown_values)
+        .feature_rules(args.feature_rules.clone())
+        .progress(args.progress());
+    if !args.catver_path.is_empty() {
+        builder = builder.catver_path(&args.catver_path);
+    }
+    builder.build()?.categorize()
 }
 
-fn build_category_list(file_path: String) -> Result<RomCategories, Box<dyn Error>> {
-    let category_contents = fs::read_to_string(file_path)?;
+///
+/// Reads a MAME ROM database and, optionally, a MAME support file and
+/// creates a categorized ROM collection, like [run](run) but without
+/// needing [Args](Args) or showing progress. Useful to load more than
+/// one MAME release, for ex, to [diff](core::diff::diff_roms) them.
+/// See [Curator] for more options.
+///
+/// @param catver_path Can be empty, roms will have no category (genre).
+///
+pub fn load_roms(mame_xml_path: &str, catver_path: &str) -> CuratorResult<Roms> {
+    let mut builder = Curator::builder().mame_xml_path(mame_xml_path);
+    if !catver_path.is_empty() {
+        builder = builder.catver_path(catver_path);
+    }
+    builder.build()?.categorize()
+}
 
+///
+/// @return Category of each machine, an [Ini](CuratorError::Ini) error if there are none.
+///
+fn parse_category_list(category_contents: &str) -> CuratorResult<RomCategories> {
     let mut rom_category: HashMap<String, String> = HashMap::new();
 
     for line in category_contents.lines() {
@@ -97,11 +101,14 @@ fn build_category_list(file_path: String) -> Result<RomCategories, Box<dyn Error
         rom_category.insert(name.to_string(), category.to_string());
     }
 
+    if rom_category.is_empty() {
+        return Err(CuratorError::Ini("no 'machine=category' lines found.".to_string()));
+    }
+
     Ok(rom_category)
 }
 
-fn read_mame_xml(contents: &str) -> Result<Document, Box<dyn Error>> {
+fn read_mame_xml(contents: &str) -> CuratorResult<Document<'_>> {
     let opt = roxmltree::ParsingOptions { allow_dtd: true, nodes_limit: u32::MAX };
-    let doc = Document::parse_with_options(contents, opt)?;
-    Ok(doc)
+    Document::parse_with_options(contents, opt).map_err(|err| CuratorError::Xml(err.to_string()))
 }
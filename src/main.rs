@@ -1,11 +1,31 @@
 extern crate core;
 
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::str::FromStr;
 
 use log::{error, info};
 
-use roms_curator::core::args::build_args;
+use roms_curator::core::args::{Args, build_args, CommandType};
+use roms_curator::core::catalog::write_catalog;
+use roms_curator::core::curator::Curator;
+use roms_curator::core::diff::diff_roms;
+use roms_curator::core::export::attract_mode::{AttractModeOptions, write_romlists};
+use roms_curator::core::export::destination_report;
+use roms_curator::core::export::folder_ini::write_folder_inis;
+use roms_curator::core::export::gamelist::write_gamelists;
+use roms_curator::core::export::retroarch::{RetroArchOptions, write_playlists};
+use roms_curator::core::export::sqlite::write_sqlite;
+use roms_curator::core::filter::Filter;
+use roms_curator::core::info::RomsInfoExt;
 use roms_curator::core::roms_service::RomsExt;
+use roms_curator::core::serve::Catalog;
+use roms_curator::core::stats::roms_stats;
+use roms_curator::core::verify::verify_sources;
+use roms_curator::error::CuratorError;
+use roms_curator::models::report::Report;
+use roms_curator::models::roms::Roms;
 use roms_curator::utils::set_up_logging;
 
 fn main() {
@@ -18,24 +38,203 @@ fn main() {
 
     info!("Starting roms_curator with arguments: {:?}", args);
 
-    let roms = roms_curator::run(&args).unwrap_or_else(|err| {
-        error!("Application error: {err}");
-        process::exit(1);
-    });
+    let mut roms = roms_curator::run(&args).unwrap_or_else(|err| fail("Application error", err));
+
+    match args.command {
+        CommandType::Categorize => {
+            retain_filtered(&mut roms, &args);
+            print!("{}", roms_stats(&roms).category_summary());
+            write_folder_inis_if_set(&roms, &args);
+            if !args.catalog_path.is_empty() {
+                write_catalog(&roms, &args.catalog_path).unwrap_or_else(|err| fail("Error writing catalog", err));
+                info!("Catalog written: {}", args.catalog_path);
+            }
+            if !args.sqlite_path.is_empty() {
+                write_sqlite(&roms, Path::new(&args.sqlite_path)).unwrap_or_else(|err| fail("Error writing SQLite database", err));
+                info!("SQLite database written: {}", args.sqlite_path);
+            }
+        }
+        CommandType::Copy | CommandType::Upgrade => {
+            write_folder_inis_if_set(&roms, &args);
 
-    if !args.source_path.is_empty() || !args.destination_path.is_empty() {
-        let report = roms.copy_roms(&args).unwrap_or_else(|err| {
-            error!("Failed to copy roms: {err}");
-            process::exit(1);
-        });
-
-        if !args.report_path.is_empty() {
-            report.to_file(&args.report_path).unwrap_or_else(|err| {
-                error!("Error creating report: {err}");
-                println!("Note: The report failed but files should have been correctly copied to destination.");
-                process::exit(0);
+            let options = args.copy_options().unwrap_or_else(|err| fail("Application error", err));
+            let progress = args.progress();
+            let report = if args.command == CommandType::Upgrade {
+                roms.upgrade_roms(&options, progress.as_ref())
+            } else {
+                roms.copy_roms(&options, progress.as_ref())
+            };
+            let report = report.unwrap_or_else(|err| fail("Failed to copy roms", err));
+
+            if !args.report_path.is_empty() {
+                report.to_file_with_format(&args.report_path, args.report_format).unwrap_or_else(|err| {
+                    error!("Error creating report: {err}");
+                    println!("Note: The report failed but files should have been correctly copied to destination.");
+                    process::exit(0);
+                });
+            }
+
+            if !args.simulation { export(&roms, &report, &args); }
+        }
+        CommandType::Verify => {
+            let verification = args.verify_options()
+                .and_then(|options| verify_sources(&roms, &args.source_path, &options))
+                .unwrap_or_else(|err| fail("Failed to verify roms", err));
+            print!("{verification}");
+        }
+        CommandType::Diff => {
+            // categorized as the current release, so only changes in mame.xml show up
+            let mut builder = Curator::builder()
+                .mame_xml_path(&args.previous_mame_xml_path)
+                .unknown_value_policy(args.unknown_values)
+                .feature_rules(args.feature_rules.clone());
+            if !args.previous_catver_path.is_empty() {
+                builder = builder.catver_path(&args.previous_catver_path);
+            }
+            let previous_roms = builder.build().and_then(|curator| curator.categorize())
+                .unwrap_or_else(|err| fail("Error reading previous mame release", err));
+            let diff = diff_roms(&previous_roms, &roms);
+            if args.diff_path.is_empty() {
+                println!("{}", diff.to_markdown());
+            } else if let Err(err) = diff.to_file(&args.diff_path) {
+                fail("Error creating diff report", err);
+            }
+        }
+        CommandType::Export => {
+            write_folder_inis_if_set(&roms, &args);
+
+            let report = args.build_destination_folders_path()
+                .and_then(|destination_folders| destination_report(&roms, &destination_folders))
+                .unwrap_or_else(|err| fail("Failed to read destination folders", err));
+            export(&roms, &report, &args);
+        }
+        CommandType::Info => {
+            // sources are scanned once for all the names
+            let sources = (!args.source_path.is_empty()).then(|| {
+                roms.scan_sources(&args.source_path, &args.scan_options())
+                    .unwrap_or_else(|err| fail("Failed to read source paths", err))
             });
+            let mut all_found = true;
+            for name in &args.names {
+                let info = match &sources {
+                    Some(sources) => roms.info_with_sources(name, sources),
+                    None => roms.info(name),
+                };
+                match info {
+                    Some(info) => println!("{info}"),
+                    None => {
+                        println!("{name}: not found in {}\n", args.mame_xml_path);
+                        all_found = false;
+                    }
+                }
+            }
+            if !all_found { process::exit(1); }
+        }
+        CommandType::Stats => {
+            retain_filtered(&mut roms, &args);
+            print!("{}", roms_stats(&roms));
+        }
+        CommandType::Serve => {
+            let listener = TcpListener::bind(&args.address)
+                .unwrap_or_else(|err| fail(&format!("Cannot listen on {}", args.address), err.into()));
+            println!("Serving roms on http://{}, press Ctrl+C to stop.", args.address);
+            if let Err(err) = Catalog::new(roms).serve(listener) {
+                fail("Server error", err.into());
+            }
+        }
+    }
+}
+
+///
+/// Logs `err` and exits with its [exit_code].
+///
+fn fail(context: &str, err: CuratorError) -> ! {
+    error!("{context}: {err}");
+    process::exit(exit_code(&err))
+}
+
+///
+/// Exit code of each kind of error, 1 is used for invalid arguments
+/// and machines not found, 2 by clap for usage errors.
+///
+fn exit_code(err: &CuratorError) -> i32 {
+    match err {
+        CuratorError::Io(_) => 3,
+        CuratorError::Xml(_) => 4,
+        CuratorError::Ini(_) => 5,
+        CuratorError::UnknownValue { .. } => 6,
+        CuratorError::Path { .. } => 7,
+        CuratorError::Encoding(_) => 8,
+        CuratorError::Filter(_) => 9,
+        CuratorError::Json(_) => 10,
+        CuratorError::Catalog(_) => 11,
+        CuratorError::Sqlite(_) => 12,
+        CuratorError::Archive(_) => 13,
+    }
+}
+
+fn parse_filter(args: &Args) -> Option<Filter> {
+    if args.filter.is_empty() { return None; }
+    Some(Filter::from_str(&args.filter).expect("validated in args parser"))
+}
+
+/// Keeps only the roms matching `--filter`, if set.
+fn retain_filtered(roms: &mut Roms, args: &Args) {
+    if let Some(filter) = parse_filter(args) {
+        roms.retain(|name, rom| filter.matches(name, rom));
+    }
+}
+
+fn write_folder_inis_if_set(roms: &Roms, args: &Args) {
+    if args.folder_ini_path.is_empty() { return; }
+    match write_folder_inis(roms, Path::new(&args.folder_ini_path)) {
+        Ok(paths) => info!("Folder INI files written: {:?}", paths),
+        Err(err) => error!("Error writing folder INI files: {err}"),
+    }
+}
+
+/// Writes all frontend files requested in `args` for the roms in `report`.
+fn export(roms: &Roms, report: &Report, args: &Args) {
+    if !args.gamelist && args.retroarch_playlist_path.is_empty() && args.attract_mode_romlist_path.is_empty() {
+        return;
+    }
+
+    let destination_folders = match args.build_destination_folders_path() {
+        Ok(destination_folders) => destination_folders,
+        Err(err) => {
+            error!("Error reading destination folders: {err}");
+            return;
         }
     };
 
+    if args.gamelist {
+        match write_gamelists(roms, report, &destination_folders) {
+            Ok(paths) => info!("Gamelists written: {:?}", paths),
+            Err(err) => error!("Error writing gamelists: {err}"),
+        }
+    }
+
+    if !args.retroarch_playlist_path.is_empty() {
+        let options = RetroArchOptions {
+            core_path: args.retroarch_core_path.clone(),
+            core_name: args.retroarch_core_name.clone(),
+            grouping: args.retroarch_playlist_grouping,
+            ..RetroArchOptions::new(PathBuf::from(&args.retroarch_playlist_path))
+        };
+        match write_playlists(roms, report, &destination_folders, &options) {
+            Ok(paths) => info!("RetroArch playlists written: {:?}", paths),
+            Err(err) => error!("Error writing RetroArch playlists: {err}"),
+        }
+    }
+
+    if !args.attract_mode_romlist_path.is_empty() {
+        let options = AttractModeOptions {
+            emulator: args.attract_mode_emulator.clone(),
+            ..AttractModeOptions::new(PathBuf::from(&args.attract_mode_romlist_path))
+        };
+        match write_romlists(roms, report, &destination_folders, &options) {
+            Ok(paths) => info!("Attract-Mode romlists written: {:?}", paths),
+            Err(err) => error!("Error writing Attract-Mode romlists: {err}"),
+        }
+    }
 }
@@ -1,27 +1,45 @@
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
+use std::path::PathBuf;
 use crate::core::args::Args;
+use crate::error::{CuratorError, CuratorResult};
 
 pub struct DestinationFolders {
     pub working: PathBuf,
     pub other: PathBuf,
     pub chd_working: PathBuf,
     pub chd_other: PathBuf,
+    /// Source files that don't match any machine, only created when something is copied to it,
+    /// see [create_unknown](DestinationFolders::create_unknown).
+    pub unknown: PathBuf,
 }
 
-impl Args {
-    pub fn build_destination_folders_path(&self) -> DestinationFolders {
-        let destination_dir = Path::new(&self.destination_path);
+impl DestinationFolders {
+    /// Creates, if needed, the `working`, `other`, `chd_working` and `chd_other` folders in `destination_dir`.
+    pub fn new(destination_dir: &Path) -> CuratorResult<DestinationFolders> {
         let working = destination_dir.join("working");
         let other = destination_dir.join("other");
         let chd_working = destination_dir.join("chd_working");
         let chd_other = destination_dir.join("chd_other");
 
-        fs::create_dir_all(&working).expect(&("Error creating ".to_string() + "working directory"));
-        fs::create_dir_all(&other).expect(&("Error creating ".to_string() + "other directory"));
-        fs::create_dir_all(&chd_working).expect(&("Error creating ".to_string() + "chd_working directory"));
-        fs::create_dir_all(&chd_other).expect(&("Error creating ".to_string() + "chd_other directory"));
+        for folder in [&working, &other, &chd_working, &chd_other] {
+            fs::create_dir_all(folder)
+                .map_err(|err| CuratorError::path(folder, &format!("Error creating directory ({err})")))?;
+        }
+
+        Ok(DestinationFolders { working, other, chd_working, chd_other, unknown: destination_dir.join("unknown") })
+    }
 
-        DestinationFolders { working, other, chd_working, chd_other }
+    /// Creates, if needed, the `unknown` folder.
+    pub fn create_unknown(&self) -> CuratorResult<&Path> {
+        fs::create_dir_all(&self.unknown)
+            .map_err(|err| CuratorError::path(&self.unknown, &format!("Error creating directory ({err})")))?;
+        Ok(&self.unknown)
+    }
+}
+
+impl Args {
+    pub fn build_destination_folders_path(&self) -> CuratorResult<DestinationFolders> {
+        DestinationFolders::new(Path::new(&self.destination_path))
     }
 }
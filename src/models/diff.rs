@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, LineWriter, Write};
+use serde::Serialize;
+
+use crate::error::{CuratorError, CuratorResult};
+use crate::models::report::ReportFormat;
+use crate::models::roms::RomCategory;
+
+/// A machine that changed between two MAME releases.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RomChange {
+    /// Name in the new release, or in the old release if it was removed.
+    pub name: String,
+    pub description: String,
+    /// Name in the old release, only set for renamed machines.
+    pub old_name: Option<String>,
+    /// Category in the old release, `None` if the machine was added.
+    pub from: Option<RomCategory>,
+    /// Category in the new release, `None` if the machine was removed.
+    pub to: Option<RomCategory>,
+    /// Why the machine is in its current category (old category if removed).
+    pub reason: String,
+}
+
+impl RomChange {
+    /// @return Transition label, for ex, `NotWorking -> Working`.
+    pub fn transition(&self) -> String {
+        let category = |category: Option<RomCategory>| category
+            .map(|category| category.to_string())
+            .unwrap_or_else(|| "None".to_string());
+        format!("{} -> {}", category(self.from), category(self.to))
+    }
+
+    fn describe(&self) -> String {
+        match &self.old_name {
+            Some(old_name) => format!("{} (was {}): {} [{}]", self.name, old_name, self.description, self.reason),
+            None => format!("{}: {} [{}]", self.name, self.description, self.reason),
+        }
+    }
+}
+
+///
+/// Differences between two categorized rom collections,
+/// see [diff_roms](crate::core::diff::diff_roms).
+///
+#[derive(Default, Debug, Serialize)]
+pub struct RomsDiff {
+    /// Machines only found in the new release.
+    pub added: Vec<RomChange>,
+    /// Machines only found in the old release.
+    pub removed: Vec<RomChange>,
+    /// Machines with a different name but the same description in both releases.
+    pub renamed: Vec<RomChange>,
+    /// Machines found in both releases that changed category.
+    pub changed: Vec<RomChange>,
+}
+
+impl RomsDiff {
+    ///
+    /// Groups changed and renamed machines by category transition,
+    /// for ex, `NotWorking -> Working`.
+    ///
+    pub fn transitions(&self) -> BTreeMap<String, Vec<&RomChange>> {
+        let mut transitions: BTreeMap<String, Vec<&RomChange>> = BTreeMap::new();
+        self.changed.iter()
+            .chain(self.renamed.iter().filter(|change| change.from != change.to))
+            .for_each(|change| transitions.entry(change.transition()).or_default().push(change));
+        transitions
+    }
+
+    /// @return Machines that were not working and now are.
+    pub fn became_working(&self) -> Vec<&RomChange> {
+        self.transitions().get("NotWorking -> Working").cloned().unwrap_or_default()
+    }
+
+    /// @return Machines that were working and now aren't.
+    pub fn regressed(&self) -> Vec<&RomChange> {
+        self.transitions().get("Working -> NotWorking").cloned().unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.renamed.is_empty() && self.changed.is_empty()
+    }
+
+    ///
+    /// Writes the diff to `path` as Markdown, or JSON if the file extension is `.json`.
+    ///
+    pub fn to_file(&self, path: &str) -> CuratorResult<bool> {
+        match ReportFormat::from_path(path) {
+            Some(ReportFormat::Json) => self.to_json_file(path),
+            Some(ReportFormat::Markdown) | None => self.to_markdown_file(path),
+            Some(format) => Err(CuratorError::path(path, &format!("Diff report does not support {format} format, use .md or .json"))),
+        }
+    }
+
+    fn to_json_file(&self, path: &str) -> CuratorResult<bool> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+
+        Ok(true)
+    }
+
+    fn to_markdown_file(&self, path: &str) -> CuratorResult<bool> {
+        let file = File::create(path)?;
+        let mut writer = LineWriter::new(file);
+        writer.write_all(self.to_markdown().as_bytes())?;
+        writer.flush()?;
+
+        Ok(true)
+    }
+
+    ///
+    /// @return The diff as a Markdown document.
+    ///
+    pub fn to_markdown(&self) -> String {
+        let transitions = self.transitions();
+
+        let mut markdown = format!("{}{}{}\n\n", "# Roms-Curator diff [", chrono::offset::Local::now(), "]");
+
+        markdown.push_str("## Summary\n\n");
+        transitions.iter().for_each(|(transition, changes)| {
+            markdown.push_str(&format!("- {}: {}\n", transition, changes.len()));
+        });
+        markdown.push_str(&format!("- Added: {}\n", self.added.len()));
+        markdown.push_str(&format!("- Removed: {}\n", self.removed.len()));
+        markdown.push_str(&format!("- Renamed: {}\n\n", self.renamed.len()));
+
+        markdown.push_str("## Detail\n\n");
+        transitions.iter().for_each(|(transition, changes)| {
+            markdown.push_str(&format!("### {}\n\n", transition));
+            write_changes(&mut markdown, changes.iter().copied());
+        });
+        markdown.push_str("### Added\n\n");
+        write_changes(&mut markdown, self.added.iter());
+        markdown.push_str("### Removed\n\n");
+        write_changes(&mut markdown, self.removed.iter());
+        markdown.push_str("### Renamed\n\n");
+        write_changes(&mut markdown, self.renamed.iter());
+
+        markdown
+    }
+}
+
+fn write_changes<'a>(markdown: &mut String, changes: impl Iterator<Item=&'a RomChange>) {
+    markdown.push_str("```text\n");
+    changes.for_each(|change| {
+        markdown.push_str(&change.describe());
+        markdown.push('\n');
+    });
+    markdown.push_str("```\n\n");
+}
@@ -2,3 +2,4 @@ pub mod roms;
 pub mod report;
 pub mod destination_folders;
 
+pub mod diff;
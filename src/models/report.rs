@@ -1,17 +1,135 @@
-use std::error::Error;
+use std::borrow::Cow;
 use std::fs::File;
-use std::io::{LineWriter, Write};
+use std::io::{BufWriter, LineWriter, Write};
+use std::path::Path;
 use chrono;
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+
+use crate::error::CuratorResult;
+use crate::utils::escape_xml;
+
+/// Report file formats, see [Report::to_file_with_format](Report::to_file_with_format).
+#[derive(Display, Debug, Default, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum ReportFormat {
+    #[default]
+    #[strum(serialize = "md", serialize = "markdown")]
+    Markdown,
+    #[strum(serialize = "json")]
+    Json,
+    #[strum(serialize = "csv")]
+    Csv,
+    #[strum(serialize = "html", serialize = "htm")]
+    Html,
+}
+
+impl ReportFormat {
+    /// @return The format matching the file extension of `path`, if any.
+    pub fn from_path(path: &str) -> Option<ReportFormat> {
+        Path::new(path).extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+    }
+}
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, Serialize)]
 pub struct ReportDetailEntry {
     pub rom_name: String,
     pub moved: bool,
     pub is_chd: bool,
+    /// Where the rom was copied from.
+    pub source_path: String,
+    /// Where the rom was (or would be, in simulation mode) copied to.
+    pub destination_path: String,
+    /// [RomCategory](crate::models::roms::RomCategory) of the rom, empty for ignored roms.
+    pub category: String,
+    /// Why the rom is in its category.
+    pub reason: String,
+    /// Size of the rom file, or directory.
+    pub bytes: u64,
+    /// Time it took to copy the rom.
+    pub duration_ms: u64,
+    /// Why the copy failed.
+    pub error: Option<String>,
+}
+
+impl ReportDetailEntry {
+    /// Single line description used in text based reports.
+    fn describe(&self) -> String {
+        if self.category.is_empty() {
+            return format!("{} (from {})", self.rom_name, self.source_path);
+        }
+
+        let is_chd = if self.is_chd { " (chd)" } else { "" };
+        let mut line = format!(
+            "{}{} from {} -> {} [{}: {}, {} bytes, {} ms]",
+            self.rom_name, is_chd, self.source_path, self.destination_path, self.category, self.reason, self.bytes, self.duration_ms
+        );
+        if let Some(error) = &self.error {
+            line.push_str(" error: ");
+            line.push_str(error);
+        }
+        line
+    }
+}
+
+/// A `mame.xml` value unknown to this version, see [UnknownValuePolicy](crate::models::roms::UnknownValuePolicy).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnrecognizedValue {
+    /// For ex, `driver status`.
+    pub field: String,
+    pub value: String,
+    /// Machines with this value, sorted by name.
+    pub machines: Vec<String>,
+}
+
+impl UnrecognizedValue {
+    /// Single line description used in text based reports.
+    fn describe(&self) -> String {
+        format!("{} '{}': {}", self.field, self.value, self.machines.join(", "))
+    }
+}
+
+/// A source file that doesn't match any machine, copied or moved to the `unknown` folder,
+/// see [UnknownFilesAction](crate::core::identify::UnknownFilesAction).
+#[derive(Default, Debug, Clone, Serialize)]
+pub struct UnknownFile {
+    #[serde(flatten)]
+    pub entry: ReportDetailEntry,
+    /// Machine its roms belong to, found by checksum.
+    pub identified_as: Option<String>,
+    /// Roms of that machine found in the file.
+    pub matched_roms: usize,
+    /// Dumped roms of that machine.
+    pub total_roms: usize,
+}
+
+impl UnknownFile {
+    /// Why it was not identified, or the machine it was identified as, for ex, `robocop (24 of 24 roms)`.
+    fn identification(&self) -> String {
+        match &self.identified_as {
+            Some(machine) => format!("{machine} ({} of {} roms)", self.matched_roms, self.total_roms),
+            None => "not identified".to_string(),
+        }
+    }
+
+    /// Single line description used in text based reports.
+    fn describe(&self) -> String {
+        let mut line = format!(
+            "{} -> {} [{}, {} bytes]",
+            self.entry.source_path, self.entry.destination_path, self.identification(), self.entry.bytes
+        );
+        if let Some(error) = &self.entry.error {
+            line.push_str(" error: ");
+            line.push_str(error);
+        }
+        line
+    }
 }
 
 /// Stores report data
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct Report {
     pub total_working: i32,
     pub total_other: i32,
@@ -21,26 +139,26 @@ pub struct Report {
     // name of rom and if the move was successful
     pub roms_other: Vec<ReportDetailEntry>,
     pub ignored_roms: Vec<ReportDetailEntry>,
+    /// Values found in `mame.xml` unknown to this version.
+    pub unrecognized_values: Vec<UnrecognizedValue>,
+    /// Source files copied or moved to the `unknown` folder, instead of ignored.
+    pub unknown_files: Vec<UnknownFile>,
     pub all_ok: bool,
 }
 
 // Adds extension to LineWriter<File> to reduce duplicated code
 pub trait LineWriterExt {
-    fn write_all_roms(self, roms: &[ReportDetailEntry], moved: bool) -> Result<Box<LineWriter<File>>, Box<dyn Error>>;
+    fn write_all_roms(self, roms: &[ReportDetailEntry], moved: bool) -> CuratorResult<Box<LineWriter<File>>>;
 }
 
 impl LineWriterExt for LineWriter<File> {
-    fn write_all_roms(mut self, roms: &[ReportDetailEntry], moved: bool) -> Result<Box<LineWriter<File>>, Box<dyn Error>> {
+    fn write_all_roms(mut self, roms: &[ReportDetailEntry], moved: bool) -> CuratorResult<Box<LineWriter<File>>> {
         self.write_all(b"<details>\n  <summary>roms</summary>\n\n```text\n")?;
 
-        roms.iter()
-            .filter(|entry| entry.moved == moved)
-            .for_each(|entry| {
-                let is_chd = if entry.is_chd { "(chd)" } else { "" };
-                let to_write = format!("{} {}", entry.rom_name, is_chd);
-                self.write_all(to_write.as_bytes()).unwrap();
-                self.write_all(b"\n").unwrap();
-            });
+        for entry in roms.iter().filter(|entry| entry.moved == moved) {
+            self.write_all(entry.describe().as_bytes())?;
+            self.write_all(b"\n")?;
+        }
 
         self.write_all(b"```\n\n</details>\n\n")?;
 
@@ -48,12 +166,187 @@ impl LineWriterExt for LineWriter<File> {
     }
 }
 
+/// Totals shown at the top of every report format.
+#[derive(Debug, Serialize)]
+pub struct ReportSummary {
+    pub all_ok: bool,
+    pub moved_to_working_folders: usize,
+    pub moved_to_working_folders_chd: usize,
+    pub moved_to_other_folders: usize,
+    pub moved_to_other_folders_chd: usize,
+    pub ignored_roms: usize,
+    pub failed_to_move: usize,
+    pub unrecognized_values: usize,
+    pub unknown_files: usize,
+    pub identified_files: usize,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    generated: String,
+    summary: ReportSummary,
+    #[serde(flatten)]
+    report: &'a Report,
+}
+
 impl Report {
     pub fn new() -> Report {
         Default::default()
     }
 
-    pub fn to_file(&self, path: &str) -> Result<bool, Box<dyn Error>> {
+    ///
+    /// Writes the report to `path`, the format is chosen by the file extension
+    /// (`.md`, `.json`, `.csv` or `.html`), defaults to markdown.
+    ///
+    pub fn to_file(&self, path: &str) -> CuratorResult<bool> {
+        self.to_file_with_format(path, ReportFormat::from_path(path).unwrap_or_default())
+    }
+
+    pub fn to_file_with_format(&self, path: &str, format: ReportFormat) -> CuratorResult<bool> {
+        match format {
+            ReportFormat::Markdown => self.to_markdown_file(path),
+            ReportFormat::Json => self.to_json_file(path),
+            ReportFormat::Csv => self.to_csv_file(path),
+            ReportFormat::Html => self.to_html_file(path),
+        }
+    }
+
+    pub fn summary(&self) -> ReportSummary {
+        let moved_to_working_folders = self.roms_working.iter().filter(|entry| entry.moved).count();
+        let moved_to_other_folders = self.roms_other.iter().filter(|entry| entry.moved).count();
+
+        ReportSummary {
+            all_ok: self.all_ok,
+            moved_to_working_folders,
+            moved_to_working_folders_chd: self.roms_working.iter().filter(|entry| entry.moved && entry.is_chd).count(),
+            moved_to_other_folders,
+            moved_to_other_folders_chd: self.roms_other.iter().filter(|entry| entry.moved && entry.is_chd).count(),
+            ignored_roms: self.ignored_roms.len(),
+            failed_to_move: self.roms_working.len() + self.roms_other.len()
+                - moved_to_working_folders - moved_to_other_folders,
+            unrecognized_values: self.unrecognized_values.len(),
+            unknown_files: self.unknown_files.len(),
+            identified_files: self.unknown_files.iter().filter(|file| file.identified_as.is_some()).count(),
+        }
+    }
+
+    /// @return All entries with the name of the section they belong to (working, other, ignored, unknown).
+    /// Unknown files have their identification as reason.
+    fn entries(&self) -> impl Iterator<Item=(&'static str, Cow<'_, ReportDetailEntry>)> {
+        self.roms_working.iter().map(|entry| ("working", Cow::Borrowed(entry)))
+            .chain(self.roms_other.iter().map(|entry| ("other", Cow::Borrowed(entry))))
+            .chain(self.ignored_roms.iter().map(|entry| ("ignored", Cow::Borrowed(entry))))
+            .chain(self.unknown_files.iter().map(|file| ("unknown", Cow::Owned(ReportDetailEntry {
+                reason: file.identification(),
+                ..file.entry.clone()
+            }))))
+    }
+
+    fn to_json_file(&self, path: &str) -> CuratorResult<bool> {
+        let json_report = JsonReport {
+            generated: chrono::offset::Local::now().to_rfc3339(),
+            summary: self.summary(),
+            report: self,
+        };
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, &json_report)?;
+        writer.flush()?;
+
+        Ok(true)
+    }
+
+    fn to_csv_file(&self, path: &str) -> CuratorResult<bool> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(b"section,rom_name,moved,is_chd,category,reason,source_path,destination_path,bytes,duration_ms,error\n")?;
+        for (section, entry) in self.entries() {
+            let row = [
+                section.to_string(),
+                escape_csv(&entry.rom_name),
+                entry.moved.to_string(),
+                entry.is_chd.to_string(),
+                escape_csv(&entry.category),
+                escape_csv(&entry.reason),
+                escape_csv(&entry.source_path),
+                escape_csv(&entry.destination_path),
+                entry.bytes.to_string(),
+                entry.duration_ms.to_string(),
+                escape_csv(entry.error.as_deref().unwrap_or_default()),
+            ].join(",");
+            writer.write_all(row.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        for unrecognized in &self.unrecognized_values {
+            let reason = escape_csv(&format!("unknown {} '{}'", unrecognized.field, unrecognized.value));
+            for machine in &unrecognized.machines {
+                writer.write_all(format!("unrecognized,{},,,,{reason},,,,,\n", escape_csv(machine)).as_bytes())?;
+            }
+        }
+        writer.flush()?;
+
+        Ok(true)
+    }
+
+    fn to_html_file(&self, path: &str) -> CuratorResult<bool> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let summary = self.summary();
+
+        let summary_items = [
+            ("Source", self.source_dir.clone()),
+            ("Destination", self.destination_dir.clone()),
+            ("All OK", summary.all_ok.to_string()),
+            ("Roms moved to working folders", format!("{} ({} CHDs)", summary.moved_to_working_folders, summary.moved_to_working_folders_chd)),
+            ("Roms moved to other folders", format!("{} ({} CHDs)", summary.moved_to_other_folders, summary.moved_to_other_folders_chd)),
+            ("Ignored roms", summary.ignored_roms.to_string()),
+            ("Roms failed to move", summary.failed_to_move.to_string()),
+            ("Unknown files", format!("{} ({} identified)", summary.unknown_files, summary.identified_files)),
+            ("Unrecognized values", if self.unrecognized_values.is_empty() {
+                "none".to_string()
+            } else {
+                self.unrecognized_values.iter().map(UnrecognizedValue::describe).collect::<Vec<String>>().join("; ")
+            }),
+        ].iter()
+            .map(|(label, value)| format!("<li>{}: <b>{}</b></li>", label, escape_xml(value)))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let rows = self.entries()
+            .map(|(section, entry)| {
+                let cells = [
+                    section.to_string(),
+                    entry.rom_name.clone(),
+                    entry.moved.to_string(),
+                    entry.is_chd.to_string(),
+                    entry.category.clone(),
+                    entry.reason.clone(),
+                    entry.source_path.clone(),
+                    entry.destination_path.clone(),
+                    entry.bytes.to_string(),
+                    entry.duration_ms.to_string(),
+                    entry.error.clone().unwrap_or_default(),
+                ].iter()
+                    .map(|cell| format!("<td>{}</td>", escape_xml(cell)))
+                    .collect::<String>();
+                let class = if entry.moved || section == "ignored" { "" } else { "failed" };
+                format!("<tr class=\"{class}\">{cells}</tr>")
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        write_template(&mut writer, HTML_TEMPLATE, &[
+            ("generated", &chrono::offset::Local::now().to_string()),
+            ("summary", &summary_items),
+            ("header", "<th>Section</th><th>Rom</th><th>Moved</th><th>CHD</th><th>Category</th><th>Reason</th>\
+                <th>Source</th><th>Destination</th><th>Bytes</th><th>Duration (ms)</th><th>Error</th>"),
+            ("rows", &rows),
+        ])?;
+        writer.flush()?;
+
+        Ok(true)
+    }
+
+    fn to_markdown_file(&self, path: &str) -> CuratorResult<bool> {
         let file = File::create(path)?;
         let mut writer = LineWriter::new(file);
 
@@ -92,13 +385,35 @@ impl Report {
         writer.write_all(b"rom or a CHD file (folders should indicate chd while zip file should be a rom).\n\n")?;
         let mut writer = writer.write_all_roms(&self.ignored_roms, false)?;
 
+        writer.write_all(b"### Unrecognized values\n\n")?;
+        writer.write_all(b"Values in mame.xml unknown to this version of roms-curator, most likely from a newer\n")?;
+        writer.write_all(b"MAME release. Machines with these values are categorized according to --unknown-values.\n\n")?;
+        if self.unrecognized_values.is_empty() {
+            writer.write_all(b"None.\n")?;
+        }
+        for unrecognized in &self.unrecognized_values {
+            writer.write_all(format!("- {}\n", unrecognized.describe()).as_bytes())?;
+        }
+        writer.write_all(b"\n")?;
+
+        writer.write_all(b"### Unknown files\n\n")?;
+        writer.write_all(b"Source files not matching any machine, copied or moved to the unknown folder according\n")?;
+        writer.write_all(b"to --unknown-files. Archives are identified by the checksums of their roms, so renamed\n")?;
+        writer.write_all(b"sets show the machine they really are.\n\n")?;
+        if self.unknown_files.is_empty() {
+            writer.write_all(b"None.\n")?;
+        }
+        for file in &self.unknown_files {
+            writer.write_all(format!("- {}\n", file.describe()).as_bytes())?;
+        }
+
         writer.flush()?;
 
         Ok(true)
     }
 
-    fn build_toc() -> Result<String, Box<dyn Error>> {
-        let toc = format!("{}{}{}{}{}{}{}{}",
+    fn build_toc() -> CuratorResult<String> {
+        let toc = format!("{}{}{}{}{}{}{}{}{}{}",
                           "- [Summary](#summary)\n",
                           "- [Detail](#detail)\n",
                           "  - [Moved to Working folder](#moved-to-working-folder)\n",
@@ -106,34 +421,35 @@ impl Report {
                           "  - [Failed moving to Working folder](#failed-moving-to-working-folder)\n",
                           "  - [Failed moving to Other folder](#failed-moving-to-other-folder)\n",
                           "  - [Ignored roms](#ignored-roms)\n",
+                          "  - [Unrecognized values](#unrecognized-values)\n",
+                          "  - [Unknown files](#unknown-files)\n",
                           "\n"
         );
 
         Ok(toc)
     }
 
-    fn build_summary(report: &Report) -> Result<String, Box<dyn Error>> {
-        let moved_to_working_folder = report.roms_working.iter().filter(|entry| entry.moved).count();
-        let moved_to_working_folder_chd = report.roms_working.iter().filter(|entry| entry.moved && entry.is_chd).count();
-
-        let moved_to_other_folder = report.roms_other.iter().filter(|entry| entry.moved).count();
-        let moved_to_other_folder_chd = report.roms_other.iter().filter(|entry| entry.moved && entry.is_chd).count();
+    fn build_summary(report: &Report) -> CuratorResult<String> {
+        let report_summary = report.summary();
 
-        let ignored_roms = report.ignored_roms.len();
+        let working_folders_entry = format!("{}{}{}{}{}", "\n- Roms moved to working folders: ", report_summary.moved_to_working_folders, " (", report_summary.moved_to_working_folders_chd, " CHDs)");
+        let other_folders_entry = format!("{}{}{}{}{}", "\n- Roms moved to other folders: ", report_summary.moved_to_other_folders, " (", report_summary.moved_to_other_folders_chd, " CHDs)");
+        let ignored_roms_entry = format!("{}{}", "\n- Ignored roms: ", report_summary.ignored_roms);
+        let unrecognized_values_entry = format!("{}{}", "\n- Unrecognized values: ", report_summary.unrecognized_values);
+        let unknown_files_entry = format!("{}{}{}{}{}", "\n- Unknown files: ", report_summary.unknown_files, " (", report_summary.identified_files, " identified)");
 
-        let working_folders_entry = format!("{}{}{}{}{}", "\n- Roms moved to working folders: ", moved_to_working_folder, " (", moved_to_working_folder_chd, " CHDs)");
-        let other_folders_entry = format!("{}{}{}{}{}", "\n- Roms moved to other folders: ", moved_to_other_folder, " (", moved_to_other_folder_chd, " CHDs)");
-        let ignored_roms_entry = format!("{}{}", "\n- Ignored roms: ", ignored_roms);
+        let roms_failed_to_move = report_summary.failed_to_move;
 
-        let roms_failed_to_move = report.roms_working.len() + report.roms_other.len()
-            - moved_to_working_folder - moved_to_other_folder;
-
-        let summary = format!("{}{}{}{}{}{}{}{}{}{}{}{}",
+        let summary = format!("{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
                               "## Summary",
-                              "\n\n- All OK: ", report.all_ok, " **",
+                              "\n\n- Source: ", report.source_dir,
+                              "\n- Destination: ", report.destination_dir,
+                              "\n- All OK: ", report.all_ok, " **",
                               working_folders_entry,
                               other_folders_entry,
                               ignored_roms_entry,
+                              unrecognized_values_entry,
+                              unknown_files_entry,
                               "\n- Roms failed to moved: ", roms_failed_to_move,
                               "\n\n",
                               "** (does not consider ignored_roms)",
@@ -152,6 +468,8 @@ impl Report {
             roms_working: self.roms_working.to_owned(),
             roms_other: self.roms_other.to_owned(),
             ignored_roms: self.ignored_roms.to_owned(),
+            unrecognized_values: self.unrecognized_values.to_owned(),
+            unknown_files: self.unknown_files.to_owned(),
             all_ok: self.all_ok,
         }
     }
@@ -191,9 +509,105 @@ impl Report {
         self
     }
 
+    pub fn unrecognized_values(&mut self, value: Vec<UnrecognizedValue>) -> &mut Report {
+        self.unrecognized_values = value;
+        self
+    }
+
+    pub fn add_unknown_file(&mut self, value: UnknownFile) -> &mut Report {
+        self.unknown_files.push(value);
+        self
+    }
+
     pub fn all_ok(&mut self, value: bool) -> &mut Report {
         self.all_ok = value;
         self
     }
 }
 
+
+/// Quotes a csv field if needed.
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+///
+/// Writes `template`, replacing each `{{name}}` placeholder with its value in `values`. Done in a
+/// single pass, so a value that looks like a placeholder, for ex, a path, is written as it is.
+/// Unknown placeholders are written as they are too.
+///
+fn write_template(writer: &mut impl Write, template: &str, values: &[(&str, &str)]) -> CuratorResult<()> {
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|end| start + end + 2) else { break; };
+        writer.write_all(&rest.as_bytes()[..start])?;
+        let name = &rest[start + 2..end - 2];
+        match values.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => writer.write_all(value.as_bytes())?,
+            None => writer.write_all(&rest.as_bytes()[start..end])?,
+        }
+        rest = &rest[end..];
+    }
+    writer.write_all(rest.as_bytes())?;
+    Ok(())
+}
+
+/// Standalone html report, tables can be sorted by clicking a column header and filtered.
+static HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Roms-Curator report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }
+th { background: #eee; cursor: pointer; user-select: none; }
+tr.failed { background: #fdd; }
+input { margin: 1em 0; padding: 4px; width: 30em; }
+</style>
+</head>
+<body>
+<h1>Roms-Curator report [{{generated}}]</h1>
+<h2>Summary</h2>
+<ul>
+{{summary}}
+</ul>
+<h2>Detail</h2>
+<input id="filter" type="search" placeholder="Filter...">
+<table id="detail">
+<thead><tr>{{header}}</tr></thead>
+<tbody>
+{{rows}}
+</tbody>
+</table>
+<script>
+const table = document.getElementById("detail");
+const body = table.tBodies[0];
+document.getElementById("filter").addEventListener("input", (event) => {
+  const value = event.target.value.toLowerCase();
+  for (const row of body.rows) {
+    row.style.display = row.textContent.toLowerCase().includes(value) ? "" : "none";
+  }
+});
+table.tHead.querySelectorAll("th").forEach((th, column) => {
+  th.addEventListener("click", () => {
+    const ascending = th.dataset.order !== "asc";
+    th.dataset.order = ascending ? "asc" : "desc";
+    const rows = Array.from(body.rows);
+    rows.sort((a, b) => {
+      const x = a.cells[column].textContent, y = b.cells[column].textContent;
+      const result = x.localeCompare(y, undefined, { numeric: true });
+      return ascending ? result : -result;
+    });
+    rows.forEach((row) => body.appendChild(row));
+  });
+});
+</script>
+</body>
+</html>
+"#;
@@ -1,32 +1,39 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
 use std::string::ToString;
 use lazy_static::lazy_static;
-use strum_macros::{Display, EnumString};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
 
 pub type Roms = HashMap<String, Rom>;
 pub type UnfilteredRoms = HashMap<String, RomData>;
 
 pub trait RomDataExt {
-    fn to_working_rom(self) -> Rom;
-    fn to_not_working_rom(self) -> Rom;
+    fn to_working_rom(self, reason: CategoryReason) -> Rom;
+    fn to_not_working_rom(self, reason: CategoryReason) -> Rom;
+    fn to_uncategorized_rom(self, reason: CategoryReason) -> Rom;
     fn to_bios_rom(self) -> Rom;
     fn to_system_rom(self) -> Rom;
 }
 
 impl RomDataExt for RomData {
-    fn to_working_rom(self) -> Rom {
-        Rom { data: self, category: RomCategory::Working }
+    fn to_working_rom(self, reason: CategoryReason) -> Rom {
+        Rom { data: self, category: RomCategory::Working, reason }
     }
-    fn to_not_working_rom(self) -> Rom {
-        Rom { data: self, category: RomCategory::NotWorking }
+    fn to_not_working_rom(self, reason: CategoryReason) -> Rom {
+        Rom { data: self, category: RomCategory::NotWorking, reason }
     }
-    fn to_bios_rom(self) -> Rom { Rom { data: self, category: RomCategory::Bios } }
+    fn to_uncategorized_rom(self, reason: CategoryReason) -> Rom {
+        Rom { data: self, category: RomCategory::UnCategorized, reason }
+    }
+    fn to_bios_rom(self) -> Rom { Rom { data: self, category: RomCategory::Bios, reason: CategoryReason::Bios } }
     fn to_system_rom(self) -> Rom {
-        Rom { data: self, category: RomCategory::System }
+        Rom { data: self, category: RomCategory::System, reason: CategoryReason::System }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RomData {
     pub status: Option<RomStatus>,
     pub is_bios: bool,
@@ -36,16 +43,75 @@ pub struct RomData {
     pub clone_of: Option<String>,
     pub rom_of: Option<String>,
     pub chd: Vec<Chd>,
+    /// Rom files of the machine, `rom` elements in `mame.xml`.
+    #[serde(default)]
+    pub rom_files: Vec<RomFile>,
     pub category: String,
+    pub description: String,
+    pub year: String,
+    pub manufacturer: String,
+    pub source_file: String,
+    pub display: Vec<Display>,
+    pub sound: Option<Sound>,
+    pub input: Option<Input>,
+}
+
+impl RomData {
+    /// @return Maximum number of simultaneous players, 0 if unknown.
+    pub fn players(&self) -> u32 {
+        self.input.as_ref().map(|input| input.players).unwrap_or(0)
+    }
+
+    /// @return Maximum number of buttons of any of the controls, 0 if unknown.
+    pub fn buttons(&self) -> u32 {
+        self.input.as_ref()
+            .and_then(|input| input.controls.iter().filter_map(|control| control.buttons).max())
+            .unwrap_or(0)
+    }
+
+    /// @return Rotation of the main screen in degrees.
+    pub fn rotation(&self) -> u16 {
+        self.display.first().map(|display| display.rotate).unwrap_or(0)
+    }
+
+    /// @return Distinct control types, for ex, `joy`, `lightgun`.
+    pub fn control_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = Vec::new();
+        if let Some(input) = &self.input {
+            input.controls.iter().for_each(|control| {
+                if !types.contains(&control.typ.as_str()) { types.push(&control.typ) }
+            });
+        }
+        types
+    }
+
+    /// @return Values unknown to this version as (field, value), for ex, `("driver status", "unemulated")`.
+    pub fn unknown_values(&self) -> Vec<(&'static str, &str)> {
+        let mut values: Vec<(&'static str, &str)> = Vec::new();
+        if let Some(status) = &self.status {
+            if let Status::Unknown(value) = &status.driver { values.push(("driver status", value)) }
+            if let Status::Unknown(value) = &status.emulation { values.push(("emulation status", value)) }
+        }
+        self.features.iter().for_each(|feature| {
+            if let FeatureType::Unknown(value) = &feature.typ { values.push(("feature type", value)) }
+            if let FeatureStatus::Unknown(value) = &feature.status { values.push(("feature status", value)) }
+        });
+        self.chd.iter().for_each(|chd| {
+            if let ChdStatus::Unknown(value) = &chd.status { values.push(("disk status", value)) }
+        });
+        values
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Rom {
     pub data: RomData,
     pub category: RomCategory,
+    /// Why the rom was put in its [RomCategory].
+    pub reason: CategoryReason,
 }
 
-#[derive(Display, PartialEq, Eq, Debug)]
+#[derive(Display, PartialEq, Eq, Debug, Clone, Copy, EnumIter, Serialize, Deserialize)]
 pub enum RomCategory {
     Working,
     NotWorking,
@@ -58,48 +124,228 @@ pub enum RomCategory {
     UnCategorized,
 }
 
+/// Why a rom ended up in a given [RomCategory].
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub enum CategoryReason {
+    Bios,
+    /// Device or one of the [EXCLUDED_CATEGORIES].
+    System,
+    Mechanical,
+    PreliminaryDriver,
+    /// One of [SPECIAL_CASES_DEMOTE].
+    SpecialCase,
+    /// Too many imperfect or unemulated features, see [FeatureRules](crate::core::feature_rules::FeatureRules).
+    Features,
+    /// A feature that alone makes the machine not working, for ex, `unemulated protection`.
+    Feature(String),
+    /// Categorized by emulation status alone.
+    Emulation(Status),
+    /// Depends on a not working rom (`romof`).
+    DependsOn(String),
+    /// Depends on a bad or missing CHD dump.
+    BadChd(String),
+    /// `mame.xml` has no driver status for the machine.
+    NoDriverStatus,
+    /// A value unknown to this version, for ex, `driver status 'unemulated'`, see [UnknownValuePolicy].
+    UnknownValue(String),
+}
+
+impl fmt::Display for CategoryReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CategoryReason::Bios => write!(f, "bios"),
+            CategoryReason::System => write!(f, "system or device"),
+            CategoryReason::Mechanical => write!(f, "mechanical"),
+            CategoryReason::PreliminaryDriver => write!(f, "preliminary driver"),
+            CategoryReason::SpecialCase => write!(f, "special case"),
+            CategoryReason::Features => write!(f, "imperfect or unemulated features"),
+            CategoryReason::Feature(feature) => write!(f, "{feature}"),
+            CategoryReason::Emulation(status) => write!(f, "{} emulation", status.to_string().to_lowercase()),
+            CategoryReason::DependsOn(name) => write!(f, "depends on not working rom {name}"),
+            CategoryReason::BadChd(name) => write!(f, "bad or missing dump of CHD {name}"),
+            CategoryReason::NoDriverStatus => write!(f, "no driver status"),
+            CategoryReason::UnknownValue(value) => write!(f, "unknown {value}"),
+        }
+    }
+}
 
-#[derive(Display, Debug, PartialEq, Eq, EnumString, Clone)]
+#[derive(Display, Debug, PartialEq, Eq, EnumString, Clone, Serialize, Deserialize)]
 #[strum(ascii_case_insensitive)]
+#[serde(from = "String", into = "String")]
 pub enum Status {
     Imperfect,
     Preliminary,
     Good,
+    /// Value unknown to this version, for ex, from a newer `mame.xml`.
+    #[strum(default)]
+    Unknown(String),
+}
+
+/// How to categorize machines with values unknown to this version, see [RomData::unknown_values].
+#[derive(Display, Debug, Default, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum UnknownValuePolicy {
+    /// Categorized as [RomCategory::NotWorking].
+    #[default]
+    #[strum(serialize = "not-working")]
+    NotWorking,
+    /// Unknown values are considered good, the machine is categorized by the known values.
+    #[strum(serialize = "working")]
+    Working,
+    /// Categorized as [RomCategory::UnCategorized].
+    #[strum(serialize = "uncategorized")]
+    UnCategorized,
+    /// Categorization fails with an [UnknownValue](crate::error::CuratorError::UnknownValue) error.
+    #[strum(serialize = "fail")]
+    Fail,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub struct RomStatus {
     pub driver: Status,
     pub emulation: Status,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Chd {
     pub name: String,
     pub status: ChdStatus,
 }
 
-#[derive(Display, Debug, PartialEq, Eq, EnumString)]
+/// A rom file of a machine, inside its zip, 7z or folder, see [container](crate::core::container).
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct RomFile {
+    pub name: String,
+    pub size: u64,
+    /// CRC32 as 8 lowercase hex digits, `None` if the rom was never dumped.
+    pub crc: Option<String>,
+    pub sha1: Option<String>,
+    /// Name of the same rom in the parent or bios, if it's shared with them.
+    pub merge: Option<String>,
+}
+
+#[derive(Display, Debug, PartialEq, Eq, EnumString, Clone, Serialize, Deserialize)]
 #[strum(ascii_case_insensitive)]
+#[serde(from = "String", into = "String")]
 pub enum ChdStatus {
+    /// Good dump, `mame.xml` status `good`.
+    #[strum(to_string = "NoStatus", serialize = "good")]
     NoStatus,
     NoDump,
     BadDump,
+    /// Value unknown to this version, for ex, from a newer `mame.xml`.
+    #[strum(default)]
+    Unknown(String),
 }
 
-#[derive(PartialEq, Eq, Debug)]
-pub struct Feature {
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Display {
+    /// raster, vector, lcd, svg or unknown
     pub typ: String,
+    /// 0, 90, 180 or 270
+    pub rotate: u16,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl Display {
+    /// @return Resolution as `width`x`height`, if known.
+    pub fn resolution(&self) -> Option<String> {
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => Some(format!("{width}x{height}")),
+            _ => None
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Sound {
+    pub channels: u32,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Input {
+    pub players: u32,
+    pub coins: Option<u32>,
+    pub controls: Vec<Control>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Control {
+    /// joy, stick, paddle, lightgun, keyboard, etc.
+    pub typ: String,
+    pub player: Option<u32>,
+    pub buttons: Option<u32>,
+    pub ways: Option<String>,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Feature {
+    pub typ: FeatureType,
     pub status: FeatureStatus,
 }
 
-#[derive(Display, Debug, PartialEq, Eq, EnumString)]
+/// Emulation areas MAME reports as imperfect or unemulated, `type` of a listxml `feature`.
+#[derive(Display, Debug, PartialEq, Eq, Hash, Clone, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[serde(from = "String", into = "String")]
+pub enum FeatureType {
+    Protection,
+    Timing,
+    Graphics,
+    Palette,
+    Sound,
+    Capture,
+    Camera,
+    Microphone,
+    Controls,
+    Keyboard,
+    Mouse,
+    Media,
+    Disk,
+    Printer,
+    Tape,
+    Punch,
+    Drum,
+    Rom,
+    Comms,
+    Lan,
+    Wan,
+    /// Value unknown to this version, for ex, from a newer `mame.xml`.
+    #[strum(default)]
+    Unknown(String),
+}
+
+#[derive(Display, Debug, PartialEq, Eq, Hash, Clone, EnumString, Serialize, Deserialize)]
 #[strum(ascii_case_insensitive)]
+#[serde(from = "String", into = "String")]
 pub enum FeatureStatus {
     Imperfect,
     Unemulated,
+    /// Value unknown to this version, for ex, from a newer `mame.xml`.
+    #[strum(default)]
+    Unknown(String),
 }
 
+/// Serializes listxml values with an `Unknown(String)` variant as plain strings, for ex, `"Good"`.
+macro_rules! serde_as_string {
+    ($($name:ident),*) => {$(
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                $name::from_str(&value).unwrap_or($name::Unknown(value))
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.to_string()
+            }
+        }
+    )*};
+}
+
+serde_as_string!(Status, ChdStatus, FeatureType, FeatureStatus);
+
 lazy_static! {
     pub static ref SPECIAL_CASES_DEMOTE: HashSet<String> = {
         let mut m = HashSet::new();
@@ -6,30 +6,27 @@ use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use log4rs::append::console::ConsoleAppender;
 use log4rs::config::{Appender, Root};
 use log4rs::encode::pattern::PatternEncoder;
-use log::{info, LevelFilter};
+use log::LevelFilter;
 
-pub fn create_dir(path: &Path, ignore_if_exists: bool) {
-    match path.try_exists() {
-        Ok(exists) => {
-            if !exists {
-                fs::create_dir_all(path)
-                    .expect(&("Error creating ".to_string() + path.to_str().unwrap() + " directory"));
-            } else if !ignore_if_exists {
-                panic!("{} directory already exists.", path.to_str().unwrap());
-            }
-        }
-        Err(err) => {
-            panic!("Error creating directory {err}");
-        }
+///
+/// Creates `path` and its parents, fails with [AlreadyExists](io::ErrorKind::AlreadyExists)
+/// if it exists and `ignore_if_exists` is false.
+///
+pub fn create_dir(path: &Path, ignore_if_exists: bool) -> io::Result<()> {
+    if !path.try_exists()? {
+        fs::create_dir_all(path)
+    } else if ignore_if_exists {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} directory already exists.", path.display())))
     }
 }
 
-pub fn remove_dir(path: &Path) {
-    if let Ok(exists) = path.try_exists() {
-        if exists {
-            fs::remove_dir_all(path).expect("Error deleting folder.");
-        }
+pub fn remove_dir(path: &Path) -> io::Result<()> {
+    if path.try_exists()? {
+        fs::remove_dir_all(path)?;
     }
+    Ok(())
 }
 
 ///
@@ -43,18 +40,74 @@ pub fn sanitize_path(path: &str) -> String {
     }
 }
 
-pub fn copy_dir_recursive(path: &Path, destination: &Path) -> io::Result<()> {
-    create_dir(destination, true);
+///
+/// Escapes the characters that cannot be used as is in xml text or attribute values.
+///
+pub fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    value.chars().for_each(|c| match c {
+        '&' => escaped.push_str("&amp;"),
+        '<' => escaped.push_str("&lt;"),
+        '>' => escaped.push_str("&gt;"),
+        '"' => escaped.push_str("&quot;"),
+        '\'' => escaped.push_str("&apos;"),
+        _ => escaped.push(c),
+    });
+    escaped
+}
+
+///
+/// @return Number of bytes copied.
+///
+pub fn copy_dir_recursive(path: &Path, destination: &Path) -> io::Result<u64> {
+    create_dir(destination, true)?;
+    let mut bytes = 0;
     for entry in fs::read_dir(path)? {
         let entry = entry?;
         let filetype = entry.file_type()?;
         if filetype.is_dir() {
-            copy_dir_recursive(&entry.path(), &destination.join(entry.file_name()))?;
+            bytes += copy_dir_recursive(&entry.path(), &destination.join(entry.file_name()))?;
         } else {
-            fs::copy(entry.path(), destination.join(entry.file_name()))?;
+            bytes += fs::copy(entry.path(), destination.join(entry.file_name()))?;
         }
     }
-    Ok(())
+    Ok(bytes)
+}
+
+///
+/// Moves a file or directory with [fs::rename], or, if `destination` is on another
+/// filesystem, copies it and then removes `path`. An existing `destination` is never replaced.
+///
+pub fn move_path(path: &Path, destination: &Path) -> io::Result<()> {
+    if destination.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", destination.display())));
+    }
+    match fs::rename(path, destination) {
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+            if path.is_dir() {
+                copy_dir_recursive(path, destination)?;
+                fs::remove_dir_all(path)
+            } else {
+                fs::copy(path, destination)?;
+                fs::remove_file(path)
+            }
+        }
+        result => result,
+    }
+}
+
+///
+/// @return Size of the file or total size of all files in the directory.
+///
+pub fn path_size(path: &Path) -> io::Result<u64> {
+    if !path.is_dir() {
+        return Ok(fs::metadata(path)?.len());
+    }
+    let mut bytes = 0;
+    for entry in fs::read_dir(path)? {
+        bytes += path_size(&entry?.path())?;
+    }
+    Ok(bytes)
 }
 
 pub fn set_up_logging() {
@@ -100,31 +153,3 @@ pub fn build_progress_bar() -> ProgressBar {
 
     progress_bar
 }
-
-// Progress bar extension methods
-
-pub trait ProgressBarEx {
-    fn println(&self, message: &str);
-    fn inc(&self);
-    fn set_length(&self, length: u64);
-}
-
-impl ProgressBarEx for Option<ProgressBar> {
-    fn println(&self, message: &str) {
-        if self.is_none() {
-            info!("{}", message);
-        } else {
-            self.as_ref().unwrap().println(message);
-        }
-    }
-    fn inc(&self) {
-        if self.is_some() {
-            self.as_ref().unwrap().inc(1);
-        }
-    }
-    fn set_length(&self, length: u64) {
-        if self.is_some() {
-            self.as_ref().unwrap().set_length(length);
-        }
-    }
-}
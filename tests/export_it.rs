@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::Connection;
+
+use roms_curator::core::args::Args;
+use roms_curator::core::export::destination_report;
+use roms_curator::core::export::attract_mode::{AttractModeOptions, write_romlists};
+use roms_curator::core::export::folder_ini::{FOLDER_INI_FILE_NAME, WORKING_FOLDER_INI_FILE_NAME, write_folder_inis};
+use roms_curator::core::export::gamelist::{GAMELIST_FILE_NAME, write_gamelists};
+use roms_curator::core::export::retroarch::{DETECT, PlaylistGrouping, RetroArchOptions, write_playlists};
+use roms_curator::core::export::sqlite::write_sqlite;
+use roms_curator::core::progress::NoProgress;
+use roms_curator::core::roms_service::RomsExt;
+
+use crate::utils::{CATEGORIZED_CHD_OTHER_FOLDER_NAME, CATEGORIZED_CHD_WORKING_FOLDER_NAME, CATEGORIZED_OTHER_FOLDER_NAME, CATEGORIZED_ROMS_FOLDER_NAME, CATEGORIZED_WORKING_FOLDER_NAME, clean_up, get_test_tag, ROMS_SOURCE_PATH, set_up, TARGET_FOLDER};
+
+mod utils;
+
+#[test]
+fn should_write_gamelist_to_each_destination_folder() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = utils::build_args(
+        &tag, false, String::new(), String::new(),
+    );
+
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let destination_folders = args.build_destination_folders_path().unwrap();
+    let written = write_gamelists(&roms, &report, &destination_folders).unwrap();
+    assert_eq!(written.len(), 4);
+
+    let categorized_folder = Path::new(TARGET_FOLDER).join(&tag).join(CATEGORIZED_ROMS_FOLDER_NAME);
+
+    let contents = fs::read_to_string(
+        categorized_folder.join(CATEGORIZED_WORKING_FOLDER_NAME).join(GAMELIST_FILE_NAME)
+    ).unwrap();
+    let doc = roxmltree::Document::parse(&contents).unwrap();
+    let games: Vec<roxmltree::Node> = doc.descendants().filter(|node| node.has_tag_name("game")).collect();
+    assert_eq!(games.len(), 3);
+
+    let robocop = games.iter()
+        .find(|game| child_text(game, "path") == "./robocop.zip")
+        .expect("robocop should be in working gamelist");
+    assert_eq!(child_text(robocop, "name"), "Robocop (World revision 4)");
+    assert_eq!(child_text(robocop, "releasedate"), "19880101T000000");
+    assert_eq!(child_text(robocop, "developer"), "Data East Corporation");
+    assert_eq!(child_text(robocop, "genre"), "Platform / Shooter Scrolling");
+    assert_eq!(child_text(robocop, "players"), "2");
+
+    // only area51.zip, the chd directory is not a game
+    let contents = fs::read_to_string(
+        categorized_folder.join(CATEGORIZED_CHD_WORKING_FOLDER_NAME).join(GAMELIST_FILE_NAME)
+    ).unwrap();
+    assert!(contents.contains("<path>./area51.zip</path>"));
+    assert!(!contents.contains("<path>./Area51</path>"));
+
+    // year '200?' is not a valid release date, but the rest should still be there
+    let contents = fs::read_to_string(
+        categorized_folder.join(CATEGORIZED_OTHER_FOLDER_NAME).join(GAMELIST_FILE_NAME)
+    ).unwrap();
+    assert!(contents.contains("<name>unknown Astra &apos;ACP&apos; (Astra, V403)</name>"));
+    assert!(!contents.contains("200?"));
+
+    assert!(categorized_folder.join(CATEGORIZED_CHD_OTHER_FOLDER_NAME).join(GAMELIST_FILE_NAME).is_file());
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_write_roms_stored_as_folders_to_gamelist() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let source = Path::new(TARGET_FOLDER).join(&tag).join("source");
+    fs::create_dir_all(source.join("robocop")).unwrap();
+    fs::write(source.join("robocop").join("ep05-4.11c"), b"hello").unwrap();
+    fs::copy(Path::new(ROMS_SOURCE_PATH).join("005.zip"), source.join("005.zip")).unwrap();
+
+    let args = Args {
+        source_path: vec![source.to_str().unwrap().to_string()],
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let destination_folders = args.build_destination_folders_path().unwrap();
+    write_gamelists(&roms, &report, &destination_folders).unwrap();
+
+    let categorized_folder = Path::new(TARGET_FOLDER).join(&tag).join(CATEGORIZED_ROMS_FOLDER_NAME);
+    let contents = fs::read_to_string(
+        categorized_folder.join(CATEGORIZED_WORKING_FOLDER_NAME).join(GAMELIST_FILE_NAME)
+    ).unwrap();
+    assert!(contents.contains("<path>./robocop</path>"));
+    assert!(contents.contains("<path>./005.zip</path>"));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_write_retroarch_playlists_per_folder_and_per_category() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = utils::build_args(
+        &tag, false, String::new(), String::new(),
+    );
+
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+    let destination_folders = args.build_destination_folders_path().unwrap();
+
+    let playlist_dir = Path::new(TARGET_FOLDER).join(&tag).join("playlists");
+    let options = RetroArchOptions {
+        core_path: "/cores/mame_libretro.so".to_string(),
+        core_name: "Arcade (MAME)".to_string(),
+        ..RetroArchOptions::new(playlist_dir.clone())
+    };
+
+    let written = write_playlists(&roms, &report, &destination_folders, &options).unwrap();
+    assert_eq!(written.len(), 4);
+
+    let playlist: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(playlist_dir.join("MAME - working.lpl")).unwrap()
+    ).unwrap();
+    assert_eq!(playlist["default_core_path"], "/cores/mame_libretro.so");
+
+    let items = playlist["items"].as_array().unwrap();
+    let labels: Vec<&str> = items.iter().map(|item| item["label"].as_str().unwrap()).collect();
+    assert_eq!(labels, vec!["005", "Elevator Action (EA, 5 PCB version, 1.1)", "Robocop (World revision 4)"]);
+
+    let robocop = &items[2];
+    let robocop_path = Path::new(robocop["path"].as_str().unwrap());
+    assert!(robocop_path.is_absolute());
+    assert!(robocop_path.ends_with(Path::new(CATEGORIZED_WORKING_FOLDER_NAME).join("robocop.zip")));
+    assert!(robocop_path.is_file());
+    assert_eq!(robocop["core_name"], "Arcade (MAME)");
+    assert_eq!(robocop["db_name"], "MAME.lpl");
+
+    // group by rom category instead
+    let options = RetroArchOptions {
+        playlist_dir: playlist_dir.join("by_category"),
+        grouping: PlaylistGrouping::Category,
+        ..RetroArchOptions::new(playlist_dir.clone())
+    };
+
+    let mut written: Vec<String> = write_playlists(&roms, &report, &destination_folders, &options).unwrap()
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+        .collect();
+    written.sort();
+    assert_eq!(written, vec!["MAME - Bios.lpl", "MAME - NotWorking.lpl", "MAME - System.lpl", "MAME - Working.lpl"]);
+
+    let playlist: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(playlist_dir.join("by_category").join("MAME - Working.lpl")).unwrap()
+    ).unwrap();
+    assert_eq!(playlist["items"].as_array().unwrap().len(), 4);
+    assert_eq!(playlist["default_core_path"], "");
+    assert_eq!(playlist["items"][0]["core_path"], DETECT);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_write_attract_mode_romlist_per_folder() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = utils::build_args(
+        &tag, false, String::new(), String::new(),
+    );
+
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+    let destination_folders = args.build_destination_folders_path().unwrap();
+
+    let romlist_dir = Path::new(TARGET_FOLDER).join(&tag).join("romlists");
+    let options = AttractModeOptions::new(romlist_dir.clone());
+
+    let written = write_romlists(&roms, &report, &destination_folders, &options).unwrap();
+    assert_eq!(written.len(), 4);
+
+    let contents = fs::read_to_string(romlist_dir.join("mame - working.txt")).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("#Name;Title;Emulator;CloneOf;Year;Manufacturer;Category;Players;Rotation;Control;Status"));
+    assert_eq!(lines[1], "005;005;mame;;1981;Sega;Maze / Shooter Small;2;270;joystick (4-way);imperfect;1;raster;;;;1");
+    assert_eq!(lines[2], "elevatora;Elevator Action (EA, 5 PCB version, 1.1);mame;elevator;1983;Taito Corporation;Platform / Shooter;2;0;joystick (8-way);good;1;raster;;;;2");
+    assert_eq!(lines[3], "robocop;Robocop (World revision 4);mame;;1988;Data East Corporation;Platform / Shooter Scrolling;2;0;joystick (8-way);imperfect;1;raster;;;;2");
+
+    let contents = fs::read_to_string(romlist_dir.join("mame - chd_working.txt")).unwrap();
+    assert!(contents.contains("area51;Area 51 (R3000);mame;;1996;Atari Games;;2;0;lightgun;good;1;raster;;;;1"));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_write_categorization_as_folder_ini_files() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = utils::build_args(
+        &tag, false, String::new(), String::new(),
+    );
+
+    let roms = roms_curator::run(&args).unwrap();
+
+    let folder_ini_dir = Path::new(TARGET_FOLDER).join(&tag).join("folders");
+    let written = write_folder_inis(&roms, &folder_ini_dir).unwrap();
+    assert_eq!(written.len(), 2);
+
+    // read back the same way as the progettosnaps files
+    let contents = fs::read_to_string(folder_ini_dir.join(FOLDER_INI_FILE_NAME)).unwrap();
+    assert!(contents.starts_with("[FOLDER_SETTINGS]\r\n"));
+
+    let mut sections: HashMap<String, Vec<String>> = HashMap::new();
+    let mut section = String::new();
+    for line in contents.lines().filter(|line| !line.is_empty() && !line.starts_with(';')) {
+        if line.starts_with('[') {
+            section = line.trim_matches(['[', ']']).to_string();
+        } else {
+            sections.entry(section.clone()).or_default().push(line.to_string());
+        }
+    }
+
+    assert_eq!(sections["Working"], vec!["005", "area51", "elevatora", "robocop"]);
+    assert_eq!(sections["NotWorking"], vec!["100lions", "99bottles", "as_acp"]);
+    assert_eq!(sections["Bios"], vec!["3dobios", "aristmk6"]);
+    assert_eq!(sections["System"], vec!["a24play", "sv801"]);
+    assert!(!sections.contains_key("UnCategorized"));
+    assert!(!sections.contains_key("ROOT_FOLDER"));
+
+    let working: Vec<String> = fs::read_to_string(folder_ini_dir.join(WORKING_FOLDER_INI_FILE_NAME)).unwrap()
+        .lines().skip(8)
+        .map(|line| line.to_string())
+        .collect();
+    assert_eq!(working, vec!["005", "area51", "elevatora", "robocop"]);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_build_report_from_destination_folders() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = utils::build_args(
+        &tag, false, String::new(), String::new(),
+    );
+
+    let roms = roms_curator::run(&args).unwrap();
+    let copy_report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let destination_folders = args.build_destination_folders_path().unwrap();
+    let report = destination_report(&roms, &destination_folders).unwrap();
+
+    let names = |entries: &Vec<roms_curator::models::report::ReportDetailEntry>| {
+        let mut names: Vec<String> = entries.iter().map(|entry| entry.rom_name.clone()).collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&report.roms_working), names(&copy_report.roms_working));
+    assert_eq!(names(&report.roms_other), names(&copy_report.roms_other));
+
+    // exporting from the destination gives the same result as exporting after copying
+    let written = write_gamelists(&roms, &report, &destination_folders).unwrap();
+    let contents = fs::read_to_string(&written[0]).unwrap();
+    write_gamelists(&roms, &copy_report, &destination_folders).unwrap();
+    assert_eq!(fs::read_to_string(&written[0]).unwrap(), contents);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_write_catalog_to_sqlite_database() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = utils::build_args(
+        &tag, false, String::new(), String::new(),
+    );
+
+    let roms = roms_curator::run(&args).unwrap();
+
+    let path = Path::new(TARGET_FOLDER).join(&tag).join("roms.db");
+    write_sqlite(&roms, &path).unwrap();
+    // replaces an existing database
+    write_sqlite(&roms, &path).unwrap();
+
+    let connection = Connection::open(&path).unwrap();
+    let count = |sql: &str| connection.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+
+    assert_eq!(count("SELECT COUNT(*) FROM machines"), 11);
+    assert_eq!(count("SELECT COUNT(*) FROM machines WHERE category = 'Working'"), 4);
+
+    let (category, reason, genre, driver): (String, String, String, String) = connection.query_row(
+        "SELECT category, reason, genre, driver_status FROM machines_with_genre WHERE name = 'robocop'", [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    ).unwrap();
+    assert_eq!((category.as_str(), reason.as_str()), ("Working", "good emulation"));
+    assert_eq!(genre, "Platform / Shooter Scrolling");
+    assert_eq!(driver, "Imperfect");
+
+    assert_eq!(count("SELECT COUNT(*) FROM dependencies WHERE machine = '100lions' AND dependency = 'aristmk6' AND depth = 1"), 1);
+    assert_eq!(count("SELECT COUNT(*) FROM disks WHERE machine = 'area51'"), 1);
+    assert_eq!(count("SELECT COUNT(*) FROM features WHERE machine = '005' AND type = 'sound'"), 1);
+    assert_eq!(count("SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name LIKE 'idx_machines_%'"), 3);
+
+    clean_up(&tag);
+}
+
+fn child_text<'a>(node: &roxmltree::Node<'a, '_>, tag: &str) -> &'a str {
+    node.children()
+        .find(|child| child.has_tag_name(tag))
+        .and_then(|child| child.text())
+        .unwrap_or_default()
+}
@@ -1,15 +1,27 @@
+use std::cell::Cell;
 use std::collections::HashSet;
+use std::fs;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::iter::Filter;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::string::ToString;
 
+use roms_curator::core::args::{Args, CommandType};
+use roms_curator::core::curator::{CopyOptions, Curator};
+use roms_curator::core::identify::UnknownFilesAction;
+use roms_curator::core::progress::{NoProgress, Progress};
 use roms_curator::core::roms_service::RomsExt;
+use roms_curator::core::scanner::IgnorePatterns;
+use roms_curator::core::torrentzip::is_rewritten;
+use roms_curator::models::report::ReportFormat;
 use roms_curator::models::roms::RomCategory::Working;
 use roms_curator::models::roms::Roms;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
 
-use crate::utils::{CATEGORIZED_CHD_OTHER_FOLDER_NAME, CATEGORIZED_CHD_WORKING_FOLDER_NAME, CATEGORIZED_OTHER_FOLDER_NAME, CATEGORIZED_ROMS_FOLDER_NAME, CATEGORIZED_WORKING_FOLDER_NAME, clean_up, get_files_from_folder, get_test_tag, run_expensive_tests, set_up, TARGET_FOLDER, WORKING_ARCADE_LIST_PATH};
+use crate::utils::{CATEGORIZED_CHD_OTHER_FOLDER_NAME, CATEGORY_LIST_FILE_NAME_SMALL_SET, CATEGORIZED_CHD_WORKING_FOLDER_NAME, CATEGORIZED_OTHER_FOLDER_NAME, CATEGORIZED_ROMS_FOLDER_NAME, CATEGORIZED_WORKING_FOLDER_NAME, CHDS_SOURCE_PATH, clean_up, get_files_from_folder, get_test_tag, MAME_XML_FILE_NAME_SMALL_SET, MAME_XML_FILE_NAME_SMALL_SET_NEXT, ROMS_SOURCE_PATH, run_expensive_tests, set_up, TARGET_FOLDER, WORKING_ARCADE_LIST_PATH};
 
 mod utils;
 
@@ -95,7 +107,7 @@ fn should_copy_files_to_destination_folder_and_create_report() {
 
     let results = roms_curator::run(&args).unwrap();
 
-    let report = results.copy_roms(&args).expect("Error copying roms");
+    let report = results.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
 
     assert_eq!(report.total_working, 5);
     assert_eq!(report.total_other, 7);
@@ -151,6 +163,54 @@ fn should_copy_files_to_destination_folder_and_create_report() {
     clean_up(&tag);
 }
 
+#[derive(Default)]
+struct CountingProgress {
+    started: Cell<u64>,
+    done: Cell<u64>,
+}
+
+impl Progress for CountingProgress {
+    fn start(&self, length: u64) { self.started.set(self.started.get() + length); }
+    fn inc(&self) { self.done.set(self.done.get() + 1); }
+    fn finish(&self) {}
+    fn message(&self, _message: &str) {}
+}
+
+#[test]
+fn should_categorize_and_copy_without_args() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let roms = Curator::builder()
+        .mame_xml_reader(File::open(MAME_XML_FILE_NAME_SMALL_SET).unwrap())
+        .catver_path(CATEGORY_LIST_FILE_NAME_SMALL_SET)
+        .build().unwrap()
+        .categorize().unwrap();
+    assert_eq!(roms.len(), 11);
+    assert_eq!(roms["robocop"].data.category, "Platform / Shooter Scrolling");
+
+    assert!(Curator::builder().catver_path(CATEGORY_LIST_FILE_NAME_SMALL_SET).build().is_err());
+
+    let destination_path = Path::new(TARGET_FOLDER).join(&tag).join(CATEGORIZED_ROMS_FOLDER_NAME);
+    let options = CopyOptions {
+        filter: Some(roms_curator::core::filter::Filter::from_str("status == working && !chd").unwrap()),
+        ..CopyOptions::new(vec![PathBuf::from(ROMS_SOURCE_PATH)], destination_path.clone())
+    };
+    let progress = CountingProgress::default();
+    let report = roms.copy_roms(&options, &progress).expect("Error copying roms");
+
+    assert_eq!(report.total_working, 3);
+    assert_eq!(report.total_other, 0);
+    assert_eq!(progress.started.get(), progress.done.get());
+    assert!(progress.done.get() > 0);
+
+    let mut working_roms = get_files_from_folder(destination_path.join(CATEGORIZED_WORKING_FOLDER_NAME).to_str().unwrap());
+    working_roms.sort();
+    assert_eq!(working_roms, vec!["005.zip", "elevatora.zip", "robocop.zip"]);
+
+    clean_up(&tag);
+}
+
 #[test]
 fn simulation_should_generate_report_but_not_copy_roms() {
     let tag = get_test_tag();
@@ -162,7 +222,7 @@ fn simulation_should_generate_report_but_not_copy_roms() {
 
     let results = roms_curator::run(&args).unwrap();
 
-    let report = results.copy_roms(&args).expect("Error copying roms");
+    let report = results.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
 
     assert_eq!(report.total_working, 5);
     assert_eq!(report.total_other, 7);
@@ -201,7 +261,7 @@ fn should_copy_files_to_destination_folder_excluding_subsets() {
 
     let results = roms_curator::run(&args).unwrap();
 
-    let report = results.copy_roms(&args).expect("Error copying roms");
+    let report = results.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
 
     assert_eq!(report.total_working, 1);
     assert_eq!(report.total_other, 0);
@@ -236,6 +296,543 @@ fn should_copy_files_to_destination_folder_excluding_subsets() {
     clean_up(&tag);
 }
 
+#[test]
+fn should_copy_files_to_destination_folder_matching_filter() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = Args {
+        filter: "category ~ shooter || (chd && status == working)".to_string(),
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+
+    let results = roms_curator::run(&args).unwrap();
+
+    let report = results.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    assert_eq!(report.total_working, 5);
+    assert_eq!(report.total_other, 0);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+
+    let path = test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join(CATEGORIZED_WORKING_FOLDER_NAME);
+    let mut working_roms = get_files_from_folder(path.to_str().unwrap());
+    let mut expected = vec!(
+        "005.zip".to_string(),
+        "elevatora.zip".to_string(),
+        "robocop.zip".to_string(),
+    );
+    working_roms.sort();
+    expected.sort();
+    assert_eq!(working_roms, expected);
+
+    let path = test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join(CATEGORIZED_OTHER_FOLDER_NAME);
+    let other_roms = get_files_from_folder(path.to_str().unwrap());
+    assert!(other_roms.is_empty());
+
+    let path = test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join(CATEGORIZED_CHD_WORKING_FOLDER_NAME);
+    let mut chd_working_roms = get_files_from_folder(path.to_str().unwrap());
+    let mut expected = vec!(
+        "Area51".to_string(),
+        "area51.zip".to_string(),
+    );
+    chd_working_roms.sort();
+    expected.sort();
+    assert_eq!(chd_working_roms, expected);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_create_report_in_all_formats() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let args = utils::build_args(
+        &tag, true, String::new(), String::new(),
+    );
+
+    let results = roms_curator::run(&args).unwrap();
+
+    let report = results.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+
+    // format by extension
+    let json_path = test_folder.join("report.json");
+    assert!(matches!(report.to_file(json_path.to_str().unwrap()), Ok(true)));
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(json["summary"]["moved_to_working_folders"], 5);
+    assert_eq!(json["summary"]["moved_to_other_folders_chd"], 1);
+    assert_eq!(json["total_other"], 7);
+    assert_eq!(json["roms_working"].as_array().unwrap().len(), 5);
+    assert_eq!(json["summary"]["unrecognized_values"], 0);
+    assert_eq!(json["unrecognized_values"].as_array().unwrap().len(), 0);
+
+    let csv_path = test_folder.join("report.csv");
+    assert!(matches!(report.to_file(csv_path.to_str().unwrap()), Ok(true)));
+    let csv = fs::read_to_string(&csv_path).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("section,rom_name,moved,is_chd,category,reason,source_path,destination_path,bytes,duration_ms,error"));
+    assert_eq!(lines.count(), 12);
+    assert!(csv.contains("working,robocop.zip,true,false,Working,good emulation,tests/resources/merged_roms/robocop.zip,"));
+    assert!(csv.contains("other,99bottles.zip,true,true,NotWorking,"));
+
+    let robocop = json["roms_working"].as_array().unwrap().iter()
+        .find(|entry| entry["rom_name"] == "robocop.zip")
+        .unwrap();
+    assert_eq!(robocop["category"], "Working");
+    assert!(robocop["destination_path"].as_str().unwrap().ends_with("robocop.zip"));
+    assert_eq!(robocop["bytes"], 0);
+    assert!(robocop["error"].is_null());
+
+    // explicit format
+    let html_path = test_folder.join("report.txt");
+    assert!(matches!(report.to_file_with_format(html_path.to_str().unwrap(), ReportFormat::Html), Ok(true)));
+    let html = fs::read_to_string(&html_path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<td>robocop.zip</td>"));
+    assert!(html.contains("Roms moved to working folders: <b>5 (2 CHDs)</b>"));
+    assert!(html.contains("Unrecognized values: <b>none</b>"));
+    assert!(html.contains(&format!("Source: <b>{},{}</b>", ROMS_SOURCE_PATH, CHDS_SOURCE_PATH)));
+    assert!(html.contains(&format!("Destination: <b>{}</b>", args.destination_path)));
+
+    let markdown_path = test_folder.join("report.md");
+    assert!(matches!(report.to_file(markdown_path.to_str().unwrap()), Ok(true)));
+    let markdown = fs::read_to_string(&markdown_path).unwrap();
+    assert!(markdown.contains(&format!("- Source: {},{}\n", ROMS_SOURCE_PATH, CHDS_SOURCE_PATH)));
+    assert!(markdown.contains(&format!("- Destination: {}\n", args.destination_path)));
+    assert!(markdown.contains("robocop.zip from tests/resources/merged_roms/robocop.zip -> "));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_upgrade_destination_in_place() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+
+    // curate with the older mame version
+    let args = Args {
+        mame_xml_path: MAME_XML_FILE_NAME_SMALL_SET.to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![ROMS_SOURCE_PATH.to_string()],
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    // newer mame version, with one new rom in the source
+    let new_source = test_folder.join("new_roms");
+    fs::create_dir_all(&new_source).unwrap();
+    File::create(new_source.join("005a.zip")).unwrap();
+    File::create(new_source.join("robocop.zip")).unwrap();
+
+    let args = Args {
+        mame_xml_path: MAME_XML_FILE_NAME_SMALL_SET_NEXT.to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![new_source.to_str().unwrap().to_string()],
+        command: CommandType::Upgrade,
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.upgrade_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error upgrading roms");
+
+    let folder = |name: &str| {
+        let mut files = get_files_from_folder(test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join(name).to_str().unwrap());
+        files.sort();
+        files
+    };
+    assert_eq!(folder(CATEGORIZED_WORKING_FOLDER_NAME), vec!["005.zip", "005a.zip", "100lions.zip", "elevatora.zip"]);
+    assert!(folder(CATEGORIZED_OTHER_FOLDER_NAME).contains(&"robocop.zip".to_string()));
+    assert!(!folder(CATEGORIZED_OTHER_FOLDER_NAME).contains(&"100lions.zip".to_string()));
+
+    assert!(report.all_ok);
+    assert_eq!(report.total_working, 2);
+    assert_eq!(report.total_other, 1);
+    let robocop = report.roms_other.iter().find(|entry| entry.rom_name == "robocop.zip").unwrap();
+    assert!(robocop.source_path.contains(CATEGORIZED_WORKING_FOLDER_NAME));
+    assert!(report.roms_working.iter().any(|entry| entry.rom_name == "005a.zip" && entry.source_path.contains("new_roms")));
+
+    let mut ignored: Vec<&str> = report.ignored_roms.iter().map(|entry| entry.rom_name.as_str()).collect();
+    ignored.sort();
+    assert_eq!(ignored, vec!["elevatora.zip", "sv801.zip"]);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_not_replace_roms_already_in_the_new_folder_when_upgrading() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let args = Args {
+        mame_xml_path: MAME_XML_FILE_NAME_SMALL_SET.to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![ROMS_SOURCE_PATH.to_string()],
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    // a stale robocop left in other, where the newer version moves it to
+    let destination_path = test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME);
+    fs::write(destination_path.join(CATEGORIZED_OTHER_FOLDER_NAME).join("robocop.zip"), b"stale").unwrap();
+
+    let args = Args {
+        mame_xml_path: MAME_XML_FILE_NAME_SMALL_SET_NEXT.to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![ROMS_SOURCE_PATH.to_string()],
+        command: CommandType::Upgrade,
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.upgrade_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error upgrading roms");
+
+    assert!(destination_path.join(CATEGORIZED_WORKING_FOLDER_NAME).join("robocop.zip").is_file());
+    assert_eq!(fs::read(destination_path.join(CATEGORIZED_OTHER_FOLDER_NAME).join("robocop.zip")).unwrap(), b"stale");
+
+    assert!(!report.all_ok);
+    let robocop = report.roms_other.iter().find(|entry| entry.rom_name == "robocop.zip").unwrap();
+    assert!(!robocop.moved);
+    assert!(robocop.error.as_ref().unwrap().contains("already exists"));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_copy_from_nested_source_folders() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    // archive organised in sub-folders, with a CHD folder two levels down
+    let source = Path::new(TARGET_FOLDER).join(&tag).join("archive");
+    for (folder, file) in [("roms/A-M", "005.zip"), ("roms/A-M", "area51.zip"), ("roms/N-Z", "robocop.zip"), (".trash", "sv801.zip")] {
+        fs::create_dir_all(source.join(folder)).unwrap();
+        fs::copy(Path::new(ROMS_SOURCE_PATH).join(file), source.join(folder).join(file)).unwrap();
+    }
+    fs::create_dir_all(source.join("chds/Area51")).unwrap();
+    fs::copy(Path::new(CHDS_SOURCE_PATH).join("Area51/area51.chd"), source.join("chds/Area51/area51.chd")).unwrap();
+    File::create(source.join("roms/readme.txt")).unwrap();
+
+    let args = Args {
+        mame_xml_path: MAME_XML_FILE_NAME_SMALL_SET.to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![source.to_str().unwrap().to_string()],
+        scan_depth: 2,
+        ignore: IgnorePatterns::from_str("*.txt,.trash").unwrap(),
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let destination_path = Path::new(TARGET_FOLDER).join(&tag).join(CATEGORIZED_ROMS_FOLDER_NAME);
+    let mut working_roms = get_files_from_folder(destination_path.join(CATEGORIZED_WORKING_FOLDER_NAME).to_str().unwrap());
+    working_roms.sort();
+    assert_eq!(working_roms, vec!["005.zip", "robocop.zip"]);
+    let mut chd_roms = get_files_from_folder(destination_path.join(CATEGORIZED_CHD_WORKING_FOLDER_NAME).to_str().unwrap());
+    chd_roms.sort();
+    assert_eq!(chd_roms, vec!["Area51", "area51.zip"]);
+    assert!(destination_path.join(CATEGORIZED_CHD_WORKING_FOLDER_NAME).join("Area51/area51.chd").is_file());
+
+    assert!(report.all_ok);
+    assert!(report.ignored_roms.is_empty());
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_copy_oddly_named_source_files_without_panicking() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let source = Path::new(TARGET_FOLDER).join(&tag).join("odd_names");
+    fs::create_dir_all(&source).unwrap();
+    fs::copy(Path::new(ROMS_SOURCE_PATH).join("robocop.zip"), source.join("RoboCop.zip")).unwrap();
+    fs::copy(Path::new(ROMS_SOURCE_PATH).join("005.zip"), source.join("005.zip")).unwrap();
+    for name in [".DS_Store", "Thumbs.db", "._005.zip", "robocop.zip.bak"] {
+        File::create(source.join(name)).unwrap();
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        File::create(source.join(std::ffi::OsStr::from_bytes(b"robo\xffcop.zip"))).unwrap();
+    }
+
+    let args = Args {
+        mame_xml_path: MAME_XML_FILE_NAME_SMALL_SET.to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![source.to_str().unwrap().to_string()],
+        subset_start: "R".to_string(),
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let working: Vec<&str> = report.roms_working.iter().map(|entry| entry.rom_name.as_str()).collect();
+    assert_eq!(working, vec!["RoboCop.zip"]);
+
+    let mut ignored: Vec<&str> = report.ignored_roms.iter().map(|entry| entry.rom_name.as_str()).collect();
+    ignored.sort();
+    if cfg!(unix) {
+        assert_eq!(ignored, vec!["robocop.zip.bak", "robo\u{FFFD}cop.zip"]);
+        let invalid = report.ignored_roms.iter().find(|entry| entry.error.is_some()).unwrap();
+        assert!(invalid.error.as_ref().unwrap().contains("not valid UTF-8"));
+    } else {
+        assert_eq!(ignored, vec!["robocop.zip.bak"]);
+    }
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_move_unknown_files_and_identify_them_by_checksum() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let mame_xml_path = write_robocop_mame_xml(&test_folder);
+
+    let source = test_folder.join("unknown_source");
+    fs::create_dir_all(&source).unwrap();
+    File::create(source.join("robocop.zip")).unwrap();
+    File::create(source.join("readme.txt")).unwrap();
+    create_zip(&source.join("RoboCop (World).zip"), &[("rc1.bin", b"abc"), ("rc2.bin", b"hello")]);
+
+    let args = Args {
+        mame_xml_path: mame_xml_path.to_str().unwrap().to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![source.to_str().unwrap().to_string()],
+        unknown_files: UnknownFilesAction::Move,
+        rename_identified: true,
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let unknown_path = test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join("unknown");
+    let mut unknown_files = get_files_from_folder(unknown_path.to_str().unwrap());
+    unknown_files.sort();
+    assert_eq!(unknown_files, vec!["readme.txt", "robocop.zip"]);
+    assert!(!source.join("RoboCop (World).zip").exists());
+    assert!(source.join("robocop.zip").exists());
+
+    assert!(report.all_ok);
+    assert!(report.ignored_roms.is_empty());
+    assert_eq!(report.roms_working.len(), 1);
+    let identified: Vec<(&str, Option<&str>, usize, usize)> = report.unknown_files.iter()
+        .map(|file| (file.entry.rom_name.as_str(), file.identified_as.as_deref(), file.matched_roms, file.total_roms))
+        .collect();
+    assert_eq!(identified, vec![("robocop.zip", Some("robocop"), 2, 2), ("readme.txt", None, 0, 0)]);
+    assert_eq!(report.summary().identified_files, 1);
+
+    let report_path = test_folder.join("report.md");
+    report.to_file(report_path.to_str().unwrap()).unwrap();
+    let markdown = fs::read_to_string(report_path).unwrap();
+    assert!(markdown.contains("- Unknown files: 2 (1 identified)"));
+    assert!(markdown.contains("RoboCop (World).zip -> "));
+    assert!(markdown.contains("[robocop (2 of 2 roms), "));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_write_html_report_values_as_they_are() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let mame_xml_path = write_robocop_mame_xml(&test_folder);
+
+    // a value that looks like a placeholder of the html template
+    let source = test_folder.join("{{rows}}");
+    fs::create_dir_all(&source).unwrap();
+    create_zip(&source.join("robocop.zip"), &[("a.bin", b"abc"), ("b.bin", b"hello")]);
+
+    let args = Args {
+        mame_xml_path: mame_xml_path.to_str().unwrap().to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![source.to_str().unwrap().to_string()],
+        ..utils::build_args(&tag, true, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let html_path = test_folder.join("report.html");
+    assert!(matches!(report.to_file(html_path.to_str().unwrap()), Ok(true)));
+    let html = fs::read_to_string(&html_path).unwrap();
+    assert!(html.contains(&format!("Source: <b>{}</b>", source.display())));
+    assert_eq!(html.matches("<td>robocop.zip</td>").count(), 1);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_not_create_unknown_folder_when_simulating() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let mame_xml_path = write_robocop_mame_xml(&test_folder);
+
+    let source = test_folder.join("unknown_source");
+    fs::create_dir_all(&source).unwrap();
+    create_zip(&source.join("RoboCop (World).zip"), &[("rc1.bin", b"abc"), ("rc2.bin", b"hello")]);
+
+    let args = Args {
+        mame_xml_path: mame_xml_path.to_str().unwrap().to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![source.to_str().unwrap().to_string()],
+        unknown_files: UnknownFilesAction::Move,
+        ..utils::build_args(&tag, true, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    assert!(!test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join("unknown").exists());
+    assert!(source.join("RoboCop (World).zip").is_file());
+    assert_eq!(report.unknown_files.len(), 1);
+    assert_eq!(report.unknown_files[0].identified_as.as_deref(), Some("robocop"));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_leave_organisational_directories_in_the_source() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let mame_xml_path = write_robocop_mame_xml(&test_folder);
+
+    // a backup folder with roms in it, not scanned with --scan-depth=0
+    let source = test_folder.join("unknown_source");
+    fs::create_dir_all(source.join("roms-backup")).unwrap();
+    create_zip(&source.join("roms-backup").join("robocop.zip"), &[("a.bin", b"abc"), ("b.bin", b"hello")]);
+    File::create(source.join("roms-backup").join("readme.txt")).unwrap();
+    File::create(source.join("readme.txt")).unwrap();
+
+    let args = Args {
+        mame_xml_path: mame_xml_path.to_str().unwrap().to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![source.to_str().unwrap().to_string()],
+        unknown_files: UnknownFilesAction::Move,
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let unknown_path = test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join("unknown");
+    assert_eq!(get_files_from_folder(unknown_path.to_str().unwrap()), vec!["readme.txt"]);
+    assert!(source.join("roms-backup").join("robocop.zip").is_file());
+    assert!(source.join("roms-backup").join("readme.txt").is_file());
+
+    assert!(report.all_ok);
+    assert_eq!(report.ignored_roms.len(), 1);
+    assert_eq!(report.ignored_roms[0].rom_name, "roms-backup");
+    assert_eq!(report.unknown_files.len(), 1);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_not_replace_unknown_files_with_the_same_name() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let mame_xml_path = write_robocop_mame_xml(&test_folder);
+
+    // two sets identified as robocop, and two unrelated files with the same name
+    let sources = [test_folder.join("first_source"), test_folder.join("second_source")];
+    for (source, zip_name) in sources.iter().zip(["RoboCop (World).zip", "robocop-alt.zip"]) {
+        fs::create_dir_all(source).unwrap();
+        create_zip(&source.join(zip_name), &[("rc1.bin", b"abc"), ("rc2.bin", b"hello")]);
+        fs::write(source.join("readme.txt"), source.to_str().unwrap()).unwrap();
+    }
+
+    let args = Args {
+        mame_xml_path: mame_xml_path.to_str().unwrap().to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: sources.iter().map(|source| source.to_str().unwrap().to_string()).collect(),
+        unknown_files: UnknownFilesAction::Move,
+        rename_identified: true,
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let unknown_path = test_folder.join(CATEGORIZED_ROMS_FOLDER_NAME).join("unknown");
+    let mut unknown_files = get_files_from_folder(unknown_path.to_str().unwrap());
+    unknown_files.sort();
+    assert_eq!(unknown_files, vec!["readme (1).txt", "readme.txt", "robocop (1).zip", "robocop.zip"]);
+    assert_eq!(fs::read_to_string(unknown_path.join("readme.txt")).unwrap(), sources[0].to_str().unwrap());
+    assert_eq!(fs::read_to_string(unknown_path.join("readme (1).txt")).unwrap(), sources[1].to_str().unwrap());
+    assert!(report.all_ok);
+    assert_eq!(report.summary().identified_files, 2);
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_rewrite_copied_zips_in_torrentzip_form() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let source = Path::new(TARGET_FOLDER).join(&tag).join("zips");
+    fs::create_dir_all(&source).unwrap();
+    create_zip(&source.join("robocop.zip"), &[("ep05-4.11c", b"hello"), ("ep01-4.11b", b"abc")]);
+    // not a valid zip, copied as it is and reported
+    fs::copy(Path::new(ROMS_SOURCE_PATH).join("005.zip"), source.join("005.zip")).unwrap();
+
+    let args = Args {
+        mame_xml_path: MAME_XML_FILE_NAME_SMALL_SET.to_string(),
+        catver_path: CATEGORY_LIST_FILE_NAME_SMALL_SET.to_string(),
+        source_path: vec![source.to_str().unwrap().to_string()],
+        torrentzip: true,
+        ..utils::build_args(&tag, false, String::new(), String::new())
+    };
+    let roms = roms_curator::run(&args).unwrap();
+    let report = roms.copy_roms(&args.copy_options().unwrap(), &NoProgress).expect("Error copying roms");
+
+    let working_path = Path::new(TARGET_FOLDER).join(&tag).join(CATEGORIZED_ROMS_FOLDER_NAME).join(CATEGORIZED_WORKING_FOLDER_NAME);
+    let mut working_roms = get_files_from_folder(working_path.to_str().unwrap());
+    working_roms.sort();
+    assert_eq!(working_roms, vec!["005.zip", "robocop.zip"]);
+    assert!(is_rewritten(&working_path.join("robocop.zip")).unwrap());
+    assert_ne!(fs::read(working_path.join("robocop.zip")).unwrap(), fs::read(source.join("robocop.zip")).unwrap());
+
+    assert!(!report.all_ok);
+    let errors: Vec<(&str, bool)> = report.roms_working.iter()
+        .map(|entry| (entry.rom_name.as_str(), entry.error.is_some()))
+        .collect();
+    assert_eq!(errors, vec![("005.zip", true), ("robocop.zip", false)]);
+
+    clean_up(&tag);
+}
+
+/// Writes a `mame.xml` with only robocop, whose roms are `abc` and `hello`.
+fn write_robocop_mame_xml(test_folder: &Path) -> PathBuf {
+    let mame_xml_path = test_folder.join("mame.xml");
+    fs::write(&mame_xml_path, r#"<mame><machine name="robocop"><description>Robocop</description>
+        <rom name="a.bin" size="3" crc="352441c2" sha1="a9993e364706816aba3e25717850c26c9cd0d89d"/>
+        <rom name="b.bin" size="5" crc="3610a686" sha1="aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d"/>
+        <driver status="good" emulation="good"/></machine></mame>"#).unwrap();
+    mame_xml_path
+}
+
+fn create_zip(path: &Path, files: &[(&str, &[u8])]) {
+    let mut zip = ZipWriter::new(File::create(path).unwrap());
+    for (name, contents) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
 #[allow(dead_code)]
 fn debug_roms_set_diff(
     mut included_in_working_arcade: Vec<&String>,
@@ -1,8 +1,11 @@
+use std::fs;
 use std::fs::File;
 use std::path::Path;
 use assert_cmd::Command;
 use predicates::prelude::predicate;
-use crate::utils::{CATEGORIZED_ROMS_FOLDER_NAME, CATEGORY_LIST_FILE_NAME_SMALL_SET, get_test_tag, MAME_XML_FILE_NAME_SMALL_SET, ROMS_SOURCE_PATH, set_up, TARGET_FOLDER};
+use roms_curator::core::catalog::read_catalog;
+use sevenz_rust::{SevenZArchiveEntry, SevenZWriter};
+use crate::utils::{CATEGORIZED_ROMS_FOLDER_NAME, CATEGORY_LIST_FILE_NAME_SMALL_SET, clean_up, get_test_tag, MAME_XML_FILE_NAME_SMALL_SET, MAME_XML_FILE_NAME_SMALL_SET_NEXT, ROMS_SOURCE_PATH, set_up, TARGET_FOLDER};
 
 mod utils;
 
@@ -18,6 +21,7 @@ fn should_run_successfully() {
     let report_path = test_folder.join("report.md").to_str().unwrap().to_string();
 
     let assert = cmd
+        .arg("copy")
         .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
         .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
         .arg(format!("--source-path={}", ROMS_SOURCE_PATH))
@@ -45,7 +49,7 @@ fn should_validate_missing_mame_xml_path() {
     ));
 
     // should warn that file needs to be a .xml
-    let assert = cmd.arg("--mame-xml-path=some-invalid-path").assert();
+    let assert = cmd.arg("copy").arg("--mame-xml-path=some-invalid-path").assert();
 
     assert.stderr(predicate::str::contains(
         "File needs to be a XML file, for ex, mame.xml."
@@ -53,7 +57,7 @@ fn should_validate_missing_mame_xml_path() {
 
     // should warn that file was not found
     let mut cmd = Command::cargo_bin("roms-curator").unwrap();
-    let assert = cmd.arg("--mame-xml-path=some-valid-path.xml").assert();
+    let assert = cmd.arg("copy").arg("--mame-xml-path=some-valid-path.xml").assert();
 
     assert.stderr(predicate::str::contains(
         "File not found."
@@ -68,7 +72,7 @@ fn should_validate_missing_catver_path() {
 
     let (mame_xml_file, _) = create_mame_and_catver_files_to_bypass_file_not_found_error(&tag);
 
-    let assert = cmd.arg(format!("--mame-xml-path={}", mame_xml_file)).assert();
+    let assert = cmd.arg("copy").arg(format!("--mame-xml-path={}", mame_xml_file)).assert();
 
     // should want about the other missing mandatory arguments
     assert
@@ -96,6 +100,7 @@ fn should_parse_source_paths() {
     let fs_sp = std::path::MAIN_SEPARATOR;
 
     let assert = cmd
+        .arg("copy")
         .arg(format!("--mame-xml-path={}", mame_xml_file))
         .arg(format!("--catver-path={}", carver_init_file))
         .arg(format!("--source-path=.{},target{},target", fs_sp, fs_sp))
@@ -121,6 +126,7 @@ fn source_path_and_destination_path_cannot_be_equal() {
         create_mame_and_catver_files_to_bypass_file_not_found_error(&tag);
 
     let assert = cmd
+        .arg("copy")
         .arg(format!("--mame-xml-path={}", mame_xml_file))
         .arg(format!("--catver-path={}", carver_init_file))
         .arg("--source-path=target/")
@@ -142,6 +148,7 @@ fn simulation_mode_needs_report_path() {
         create_mame_and_catver_files_to_bypass_file_not_found_error(&tag);
 
     let assert = cmd
+        .arg("copy")
         .arg(format!("--mame-xml-path={}", mame_xml_file))
         .arg(format!("--catver-path={}", carver_init_file))
         .arg("--source-path=target/")
@@ -154,6 +161,311 @@ fn simulation_mode_needs_report_path() {
     ));
 }
 
+#[test]
+fn report_format_is_chosen_by_extension_or_argument() {
+    let tag = get_test_tag();
+    set_up(&tag);
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let (mame_xml_file, carver_init_file) =
+        create_mame_and_catver_files_to_bypass_file_not_found_error(&tag);
+
+    let assert = cmd
+        .arg("copy")
+        .arg(format!("--mame-xml-path={}", mame_xml_file))
+        .arg(format!("--catver-path={}", carver_init_file))
+        .arg("--source-path=target/")
+        .arg("--destination-path=/")
+        .arg(format!("--report-path={}", Path::new(TARGET_FOLDER).join(&tag).join("report.txt").to_str().unwrap()))
+        .assert();
+
+    assert.stdout(predicate::str::contains(
+        "Report file should have one of the extensions .md, .json, .csv or .html"
+    ));
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+    let assert = cmd
+        .arg("copy")
+        .arg(format!("--mame-xml-path={}", mame_xml_file))
+        .arg(format!("--catver-path={}", carver_init_file))
+        .arg("--source-path=target/")
+        .arg("--destination-path=/")
+        .arg(format!("--report-path={}", Path::new(TARGET_FOLDER).join(&tag).join("report.json").to_str().unwrap()))
+        .assert();
+
+    assert.stdout(predicate::str::contains("report_format: Json"));
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+    let assert = cmd
+        .arg("copy")
+        .arg(format!("--mame-xml-path={}", mame_xml_file))
+        .arg(format!("--catver-path={}", carver_init_file))
+        .arg("--source-path=target/")
+        .arg("--destination-path=/")
+        .arg(format!("--report-path={}", Path::new(TARGET_FOLDER).join(&tag).join("report.txt").to_str().unwrap()))
+        .arg("--report-format=html")
+        .assert();
+
+    assert.stdout(predicate::str::contains("report_format: Html"));
+}
+
+#[test]
+fn should_write_diff_against_previous_release() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let diff_path = test_folder.join("diff.md").to_str().unwrap().to_string();
+
+    let assert = cmd
+        .arg("diff")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET_NEXT))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg(format!("--previous-mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--diff-path={}", diff_path))
+        .arg("--progress=false")
+        .assert();
+
+    assert.success();
+
+    let diff = fs::read_to_string(&diff_path).unwrap();
+    assert!(diff.contains("### NotWorking -> Working\n\n```text\n100lions: "));
+    assert!(diff.contains("### Working -> NotWorking\n\n```text\nrobocop: "));
+    assert!(diff.contains("elevatorb (was elevatora)"));
+    assert!(diff.contains("- Removed: 1"));
+}
+
+#[test]
+fn should_categorize_previous_release_with_the_same_rules() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let test_folder = Path::new(TARGET_FOLDER).join(&tag);
+    let diff_path = test_folder.join("diff.md").to_str().unwrap().to_string();
+
+    let assert = cmd
+        .arg("diff")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--previous-mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--diff-path={}", diff_path))
+        .arg("--feature-rules=sound=not-working")
+        .arg("--progress=false")
+        .assert();
+
+    assert.success();
+
+    let diff = fs::read_to_string(&diff_path).unwrap();
+    assert!(!diff.contains(" -> "));
+}
+
+#[test]
+fn diff_needs_previous_mame_xml_path() {
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("diff")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .assert();
+
+    assert.failure()
+        .stderr(predicate::str::contains("--previous-mame-xml-path"));
+}
+
+#[test]
+fn should_print_stats_and_categories_without_copying() {
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("stats")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg("--progress=false")
+        .assert();
+
+    assert.success()
+        .stdout(predicate::str::contains("Total roms: 11"))
+        .stdout(predicate::str::contains("Working: 4"));
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("categorize")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg("--filter=status == working && !chd")
+        .arg("--progress=false")
+        .assert();
+
+    assert.success()
+        .stdout(predicate::str::contains("Working: 3\nNotWorking: 0\nBios: 0"));
+}
+
+#[test]
+fn should_save_categorized_roms_as_catalog() {
+    let tag = get_test_tag();
+    set_up(&tag);
+    let catalog_path = Path::new(TARGET_FOLDER).join(&tag).join("roms.json.gz");
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("categorize")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg(format!("--catalog-path={}", catalog_path.to_str().unwrap()))
+        .arg("--progress=false")
+        .assert();
+
+    assert.success();
+
+    let roms = read_catalog(&catalog_path).unwrap();
+    assert_eq!(roms.len(), 11);
+    assert_eq!(roms["robocop"].data.category, "Platform / Shooter Scrolling");
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_take_arguments_from_config_file_and_env() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let config_path = Path::new(TARGET_FOLDER).join(&tag).join("config.toml");
+    fs::write(&config_path, format!(
+        "mame_xml_path = \"{}\"\nprogress = false\n\n[profile.working]\nfilter = \"status == working\"\n",
+        MAME_XML_FILE_NAME_SMALL_SET,
+    )).unwrap();
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("stats")
+        .arg(format!("--config={}", config_path.to_str().unwrap()))
+        .arg("--profile=working")
+        .env("ROMS_CURATOR_CATVER_PATH", CATEGORY_LIST_FILE_NAME_SMALL_SET)
+        .assert();
+
+    assert.success()
+        .stdout(predicate::str::contains("Total roms: 4"));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_verify_source_paths() {
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("verify")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET_NEXT))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg(format!("--source-path={}", ROMS_SOURCE_PATH))
+        .arg("--progress=false")
+        .assert();
+
+    assert.success()
+        .stdout(predicate::str::contains("Missing working roms: 2\n  005a\n  elevatorb"))
+        .stdout(predicate::str::contains("Working roms missing CHDs: 1\n  area51"))
+        .stdout(predicate::str::contains("Unknown files: 2"));
+}
+
+#[test]
+fn should_verify_contents_of_7z_and_zip_containers() {
+    let tag = get_test_tag();
+    set_up(&tag);
+
+    let source = Path::new(TARGET_FOLDER).join(&tag).join("source");
+    fs::create_dir_all(&source).unwrap();
+    // robocop with a bad first rom and the others missing, 005 an empty file
+    let mut seven_zip = SevenZWriter::create(source.join("robocop.7z")).unwrap();
+    let mut entry = SevenZArchiveEntry::new();
+    entry.name = "ep05-4.11c".to_string();
+    entry.has_stream = true;
+    seven_zip.push_archive_entry(entry, Some("bad dump".as_bytes())).unwrap();
+    seven_zip.finish().unwrap();
+    File::create(source.join("005.zip")).unwrap();
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("verify")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg(format!("--source-path={}", source.to_str().unwrap()))
+        .arg("--check-contents=true")
+        .arg("--progress=false")
+        .assert();
+
+    assert.success()
+        .stdout(predicate::str::contains("Roms found: 2"))
+        .stdout(predicate::str::contains("Bad contents: 2"))
+        .stdout(predicate::str::contains("005.zip (Invalid archive"))
+        .stdout(predicate::str::contains("robocop.7z (missing ep01-4.11b, ep04-3"))
+        .stdout(predicate::str::contains("; bad ep05-4.11c)"));
+
+    clean_up(&tag);
+}
+
+#[test]
+fn should_show_machine_info() {
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("info")
+        .arg("robocop")
+        .arg("100lions")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg(format!("--source-path={}", ROMS_SOURCE_PATH))
+        .arg("--progress=false")
+        .assert();
+
+    assert.success()
+        .stdout(predicate::str::contains("robocop: Robocop (World revision 4)\n"))
+        .stdout(predicate::str::contains("  Category: Working (good emulation)\n"))
+        .stdout(predicate::str::contains("  Depends on: aristmk6 (Bios)\n"));
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("info")
+        .arg("nonexistent")
+        .arg(format!("--mame-xml-path={}", MAME_XML_FILE_NAME_SMALL_SET))
+        .arg(format!("--catver-path={}", CATEGORY_LIST_FILE_NAME_SMALL_SET))
+        .arg("--progress=false")
+        .assert();
+
+    assert.failure()
+        .stdout(predicate::str::contains("nonexistent: not found"));
+}
+
+#[test]
+fn should_exit_with_error_code_on_malformed_mame_xml() {
+    let tag = get_test_tag();
+    set_up(&tag);
+    let (mame_xml_file, catver_ini_file) = create_mame_and_catver_files_to_bypass_file_not_found_error(&tag);
+    fs::write(&mame_xml_file, "<mame><machine").unwrap();
+    fs::write(&catver_ini_file, "robocop=Platform / Shooter Scrolling").unwrap();
+
+    let mut cmd = Command::cargo_bin("roms-curator").unwrap();
+
+    let assert = cmd
+        .arg("stats")
+        .arg(format!("--mame-xml-path={}", mame_xml_file))
+        .arg(format!("--catver-path={}", catver_ini_file))
+        .arg("--progress=false")
+        .assert();
+
+    assert.code(4)
+        .stdout(predicate::str::contains("Invalid MAME xml"));
+
+    clean_up(&tag);
+}
+
 fn create_mame_and_catver_files_to_bypass_file_not_found_error(test_tag: &str) -> (String, String) {
     let test_folder = Path::new(TARGET_FOLDER).join(test_tag);
     let mame_xml_file = test_folder.join("some-valid-path.xml").to_str().unwrap().to_string();
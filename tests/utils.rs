@@ -12,13 +12,20 @@ use log4rs::config::{Appender, Root};
 use log4rs::encode::pattern::PatternEncoder;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
-use roms_curator::core::args::Args;
+use roms_curator::core::args::{Args, CommandType};
+use roms_curator::core::export::retroarch::{DETECT, PlaylistGrouping};
+use roms_curator::core::feature_rules::FeatureRules;
+use roms_curator::core::identify::UnknownFilesAction;
+use roms_curator::core::scanner::IgnorePatterns;
+use roms_curator::models::report::ReportFormat;
+use roms_curator::models::roms::UnknownValuePolicy;
 
 static RESOURCES_PROD_PATH: &str = "tests/resources/prod_lists_0244.zip";
 
 pub static TARGET_FOLDER: &str = "target/tests/";
 pub static MAME_XML_FILE_NAME_FULL_SET: &str = "mame-roms.xml";
 pub static MAME_XML_FILE_NAME_SMALL_SET: &str = "tests/resources/listxml_0244.xml";
+pub static MAME_XML_FILE_NAME_SMALL_SET_NEXT: &str = "tests/resources/listxml_0245.xml";
 pub static CATEGORY_LIST_FILE_NAME_FULL_SET: &str = "catver.ini";
 pub static CATEGORY_LIST_FILE_NAME_SMALL_SET: &str = "tests/resources/catver_0244.ini";
 pub static WORKING_ARCADE_LIST_PATH: &str = "tests/resources/working_arcade_0244.ini";
@@ -117,7 +124,7 @@ fn create_test_dir(path: &Path) {
         Ok(exists) => {
             if !exists {
                 fs::create_dir_all(path)
-                    .expect(&("Error creating ".to_string() + path.to_str().unwrap() + " directory"));
+                    .unwrap_or_else(|_| panic!("Error creating {} directory", path.to_str().unwrap()));
             } else {
                 panic!("{} directory already exists. Try deleting the directory before running tests.", path.to_str().unwrap());
             }
@@ -163,15 +170,41 @@ pub fn build_args(
     let progress = true;
 
     Args {
+        command: CommandType::Copy,
+        names: Vec::new(),
         mame_xml_path,
         catver_path,
+        unknown_values: UnknownValuePolicy::default(),
+        feature_rules: FeatureRules::default(),
         source_path: vec![ROMS_SOURCE_PATH.to_string(), CHDS_SOURCE_PATH.to_string()],
+        scan_depth: 0,
+        ignore: IgnorePatterns::default(),
+        check_contents: false,
         destination_path,
         report_path,
+        report_format: ReportFormat::Markdown,
         ignore_not_working_chd,
         simulation,
         subset_start,
         subset_end,
+        filter: String::new(),
+        unknown_files: UnknownFilesAction::Keep,
+        rename_identified: false,
+        torrentzip: false,
+        gamelist: false,
+        retroarch_playlist_path: String::new(),
+        retroarch_core_path: DETECT.to_string(),
+        retroarch_core_name: DETECT.to_string(),
+        retroarch_playlist_grouping: PlaylistGrouping::Folder,
+        attract_mode_romlist_path: String::new(),
+        attract_mode_emulator: "mame".to_string(),
+        folder_ini_path: String::new(),
+        catalog_path: String::new(),
+        sqlite_path: String::new(),
+        previous_mame_xml_path: String::new(),
+        previous_catver_path: String::new(),
+        diff_path: String::new(),
+        address: String::new(),
         progress,
     }
 }